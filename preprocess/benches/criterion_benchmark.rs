@@ -1,52 +1,11 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use std::time::Duration;
-use std::mem::size_of;
 use std::collections::{BTreeSet, HashSet};
 use std::vec::Vec;
 use preprocess::traits::{Container};
 use preprocess::library::eager_unique_vector::{EagerUniqueVec};
 use preprocess::library::lazy_unique_vector::{LazyUniqueVec};
-use rand::{Rng,SeedableRng};
-use rand::rngs::StdRng;
-use rand::seq::SliceRandom;
-use rand::thread_rng;
-
-// one search at the 
-fn gen_dataset_1() -> Vec<u32> { // avoid duplication
-    let size = 1024 * 1024; // 1 MB
-    let amount = size / size_of::<u32>();
-    let mut data: Vec<u32> = (1..amount as u32).collect(); //ensure no duplication
-    let mut rng = StdRng::seed_from_u64(222);
-    data.shuffle(&mut rng);
-    data
-}
-
-fn gen_dataset_128() -> Vec<u32> { // avoid duplication
-    let size = 128 * 1024 * 1024; // 128 MB
-    let amount = size / size_of::<u32>();
-    let mut data: Vec<u32> = (1..amount as u32).collect(); //ensure no duplication
-    let mut rng = StdRng::seed_from_u64(222);
-    data.shuffle(&mut rng);
-    data
-}
-
-fn gen_dataset_256() -> Vec<u32> { // avoid duplication
-    let size = 256 * 1024 * 1024; // 256 MB
-    let amount = size / size_of::<u32>();
-    let mut data: Vec<u32> = (1..amount as u32).collect(); //ensure no duplication
-    let mut rng = StdRng::seed_from_u64(222);
-    data.shuffle(&mut rng);
-    data
-}
-
-fn gen_dataset_512() -> Vec<u32> { // avoid duplication
-    let size = 512 * 1024 * 1024; // 512 MB
-    let amount = size / size_of::<u32>();
-    let mut data: Vec<u32> = (1..amount as u32).collect(); //ensure no duplication
-    let mut rng = StdRng::seed_from_u64(222);
-    data.shuffle(&mut rng);
-    data
-}
+use preprocess::tools::{gen_dataset_1, gen_dataset_128, gen_dataset_256, gen_dataset_512};
 
 fn btreeset_insertion_1m(c: &mut Criterion) {
     let s: &mut dyn Container<u32> = &mut BTreeSet::new();
@@ -1,4 +1,4 @@
-use preprocess::generator::{gen_output_code};
+use preprocess::generator::{gen_output_code, Wrappers};
 fn main() {
-    println!("{}", gen_output_code("UniqueCon", "T", "std::collections::BTreeSet", "UniqueConTrait", "none"));
+    println!("{}", gen_output_code("UniqueCon", "T", "std::collections::BTreeSet", "UniqueConTrait", "none", "the only matching struct", &Wrappers::default()));
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
 use preprocess::lib_spec_processor::{process_lib_specs};
+use preprocess::run_matching::{WORKDIR, setup_dirs};
 fn main() {
-    let lib_specs = process_lib_specs("./src/library/".to_string());
+    setup_dirs(WORKDIR).unwrap();
+    let lib_specs = process_lib_specs("./src/library/".to_string(), WORKDIR);
     println!("{:?}", lib_specs)
 }
\ No newline at end of file
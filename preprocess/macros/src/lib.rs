@@ -0,0 +1,224 @@
+// Declaring container types with attributes in place of spec blocks, e.g.
+//
+//     #[container(impl(Container, RandomAccess), unique, ascending)]
+//     struct StrictlyAscendingCon<T>;
+//
+// The attribute is replaced by the container type generated for the declaration it stands
+// for, here
+//
+//     type StrictlyAscendingCon<T> = {c impl (Container, RandomAccess) | ((unique c) and (ascending c))}
+//
+// parsed, type checked, analysed and matched by the same pipeline as the spec blocks. Each
+// attribute is expanded on its own: the properties it uses are built in, or declared in its
+// arguments, as string literals or in the files named by properties("path").
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenTree, Delimiter};
+use syn::{DeriveInput, GenericParam, ImplItem, Item, LitStr};
+
+use preprocess::build_script::{build_options};
+use preprocess::generator::{MatchOptions, check_spec_text, process_spec_text};
+use preprocess::match_cache::{cache_key};
+use preprocess::parser::{Decl, Dispatch};
+
+const WORKDIR: &str = "preprocess-macros"; // for the generated specifications and the match cache
+const IMPORT: &str = "use preprocess::traits::container_constructor::ContainerConstructor;";
+const CONSTRUCTOR: &str = "preprocess::traits::container_constructor::ContainerConstructor";
+
+// The properties provided without a declaration
+const BUILTINS: [(&str, &str); 4] = [
+    ("unique", r"property unique<T> {
+    \c <: (Container) -> ((for-all-elems c) \a -> ((unique-count? a) c))
+}"),
+    ("ascending", r"property ascending<T> {
+    \c -> ((for-all-consecutive-pairs c) leq?)
+}"),
+    ("descending", r"property descending<T> {
+    \c -> ((for-all-consecutive-pairs c) geq?)
+}"),
+    ("lifo", r"property lifo<T> {
+    \c <: (Stack) -> (forall \x -> ((equal? (pop ((push c) x))) x))
+}"),
+];
+
+type ErrorMessage = String;
+
+// The working directory of the crate being compiled, in its OUT_DIR if it has a build script,
+// so that the builds of different crates do not share their generated specifications
+fn work_dir() -> String {
+    match env::var("OUT_DIR") {
+        Ok(out_dir) => Path::new(&out_dir).join(WORKDIR).to_string_lossy().to_string() + "/",
+        Err(_) => {
+            let package = env::var("CARGO_PKG_NAME").unwrap_or_default();
+            let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+            let dir = WORKDIR.to_string() + "-" + &package + "-" + &cache_key(&[&manifest_dir]);
+            env::temp_dir().join(dir).to_string_lossy().to_string() + "/"
+        }
+    }
+}
+
+// The names of the properties declared by a string literal or a file of declarations
+fn property_names(source: &str, decls: &str) -> Result<Vec<String>, ErrorMessage> {
+    let blocks = check_spec_text(source, decls).map_err(|e| e.to_string())?;
+    let decls: Vec<Decl> = blocks.iter().flat_map(|b| b.extract_spec()).collect();
+    if decls.iter().any(|d| !d.is_prop_decl()) {
+        return Err("Error, ".to_string() + source + " declares something other than properties");
+    }
+    Ok(decls.iter().map(|d| d.get_name()).collect())
+}
+
+// The declaration of a container type given by the arguments of #[container(...)]:
+// impl(<bounds>), the names of its properties, workload(<op>: <n>, ...), dispatch(static|dynamic),
+// and the declarations of properties, as string literals or in properties("<path>") files
+struct ConArgs {
+    bounds: Vec<String>,
+    properties: Vec<String>,
+    workload: Option<String>,
+    dispatch: Option<Dispatch>,
+    declarations: Vec<(String, String)>, // where each declaration comes from, with its text
+    files: Vec<String>, // the absolute paths of the properties files, rebuilding the crate when they change
+}
+
+fn parse_lit(tokens: proc_macro2::TokenStream) -> Result<LitStr, ErrorMessage> {
+    syn::parse2::<LitStr>(tokens.clone()).map_err(|_| "Error, expected a string literal, found ".to_string() + &tokens.to_string())
+}
+
+// A properties file, relative to the directory of the crate being compiled
+fn read_properties(path: &str) -> Result<(String, String), ErrorMessage> {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let file = Path::new(&manifest_dir).join(path);
+    let decls = fs::read_to_string(&file).map_err(|_| "Error, unable to read the properties file ".to_string() + &file.to_string_lossy())?;
+    Ok((file.to_string_lossy().to_string(), decls))
+}
+
+fn parse_args(args: proc_macro2::TokenStream) -> Result<ConArgs, ErrorMessage> {
+    let mut con_args = ConArgs { bounds: Vec::new(), properties: Vec::new(), workload: None, dispatch: None, declarations: Vec::new(), files: Vec::new() };
+    let tokens: Vec<TokenTree> = args.into_iter().collect();
+    for arg in tokens.split(|t| matches!(t, TokenTree::Punct(p) if p.as_char() == ',')).filter(|a| !a.is_empty()) {
+        match arg {
+            [TokenTree::Ident(key), TokenTree::Group(g)] if g.delimiter() == Delimiter::Parenthesis => {
+                let contents = g.stream().to_string();
+                match key.to_string().as_str() {
+                    "impl" => con_args.bounds = contents.split(',').map(|b| b.trim().to_string()).filter(|b| !b.is_empty()).collect(),
+                    // op names may contain dashes, e.g. is-empty
+                    "workload" => con_args.workload = Some(contents.replace(" - ", "-")),
+                    "dispatch" => con_args.dispatch = match contents.trim() {
+                        "static" => Some(Dispatch::Static),
+                        "dynamic" => Some(Dispatch::Dynamic),
+                        d => return Err("Error, unknown dispatch ".to_string() + d + ", expected static or dynamic")
+                    },
+                    "properties" => {
+                        let (file, decls) = read_properties(&parse_lit(g.stream())?.value())?;
+                        con_args.declarations.push((file.to_string(), decls));
+                        con_args.files.push(file);
+                    },
+                    k => return Err("Error, unknown argument ".to_string() + k + "(...)")
+                }
+            },
+            [TokenTree::Ident(property)] => con_args.properties.push(property.to_string()),
+            [TokenTree::Literal(lit)] => {
+                let decls = parse_lit(TokenTree::Literal(lit.clone()).into())?.value();
+                con_args.declarations.push(("#[container]".to_string(), decls));
+            },
+            _ => return Err("Error, expected impl(...), workload(...), dispatch(...), properties(...), a property name or declaration, found ".to_string()
+                + &arg.iter().cloned().collect::<proc_macro2::TokenStream>().to_string())
+        }
+    }
+    if con_args.properties.is_empty() {
+        return Err("Error, a container type needs at least one property".to_string());
+    }
+    Ok(con_args)
+}
+
+// The spec block declaring the container type and the properties it uses. A declared property
+// replaces a built-in property of the same name.
+fn spec_decls(name: &str, elem: &str, con_args: &ConArgs) -> Result<String, ErrorMessage> {
+    let mut decls = Vec::new();
+    let mut declared = Vec::new();
+    for (source, d) in con_args.declarations.iter() {
+        declared.append(&mut property_names(source, d)?);
+        decls.push(d.to_string());
+    }
+    for p in con_args.properties.iter().filter(|p| !declared.contains(p)) {
+        let builtin = BUILTINS.iter().find(|(n, _)| n == p)
+            .ok_or_else(|| "Error, unknown property ".to_string() + p + ", declare it in the arguments of the attribute")?;
+        decls.push(builtin.1.to_string());
+    }
+    // (p1 c) and ((p2 c) and (p3 c)) for several properties
+    let refinement = con_args.properties.iter().rev()
+        .map(|p| "(".to_string() + p + " c)")
+        .reduce(|r, p| "(".to_string() + &p + " and " + &r + ")")
+        .unwrap_or_default();
+    let bounds = if con_args.bounds.is_empty() { "Container".to_string() } else { con_args.bounds.join(", ") };
+    let mut con = format!("type {}<{}> = {{c impl ({}) | {}}}", name, elem, bounds, refinement);
+    if let Some(workload) = &con_args.workload {
+        con = con + " workload { " + workload + " }";
+    }
+    match con_args.dispatch {
+        Some(Dispatch::Static) => con += " dispatch static",
+        Some(Dispatch::Dynamic) => con += " dispatch dynamic",
+        None => ()
+    }
+    decls.push(con);
+    Ok(decls.join("\n"))
+}
+
+// The generated code of the container type, with the attributes and visibility of the annotated
+// struct. The constructor trait is imported anonymously, so that several container types can be
+// declared in one module.
+fn con_items(code: &str, item: &DeriveInput) -> Result<proc_macro2::TokenStream, ErrorMessage> {
+    let code = "use ".to_string() + CONSTRUCTOR + " as _;\n" + &code.replace(IMPORT, "").replace("ContainerConstructor", CONSTRUCTOR);
+    let mut file = syn::parse_file(&code).map_err(|e| "Error, unable to parse the generated code: ".to_string() + &e.to_string())?;
+    let name = item.ident.to_string();
+    for i in file.items.iter_mut() {
+        match i {
+            Item::Struct(s) if s.ident == name => {
+                s.attrs = item.attrs.clone();
+                s.vis = item.vis.clone();
+            },
+            Item::Trait(t) => t.vis = item.vis.clone(),
+            // the inherent new() of a statically dispatched container type
+            Item::Impl(imp) if imp.trait_.is_none() => {
+                for m in imp.items.iter_mut() {
+                    if let ImplItem::Method(m) = m {
+                        m.vis = item.vis.clone();
+                    }
+                }
+            },
+            _ => ()
+        }
+    }
+    Ok(quote::quote!(#file))
+}
+
+fn expand_container(args: proc_macro2::TokenStream, item: &DeriveInput) -> Result<proc_macro2::TokenStream, ErrorMessage> {
+    let con_args = parse_args(args)?;
+    let params: Vec<&GenericParam> = item.generics.params.iter().collect();
+    let elem = match params.as_slice() {
+        [GenericParam::Type(t)] => t.ident.to_string(),
+        _ => return Err("Error, a container type takes one type parameter, its element type".to_string())
+    };
+    let name = item.ident.to_string();
+    let decls = spec_decls(&name, &elem, &con_args)?;
+    let options = MatchOptions { work_dir: work_dir(), ..build_options() };
+    let code = process_spec_text(&name, &decls, &options).map_err(|e| e.to_string())?;
+    let mut tokens = con_items(&code, item)?;
+    // tell rustc that the expansion depends on the properties files
+    for file in con_args.files.iter() {
+        tokens.extend(quote::quote!(const _: &str = include_str!(#file);));
+    }
+    Ok(tokens)
+}
+
+// Replace the annotated struct with the container type it declares
+#[proc_macro_attribute]
+pub fn container(args: TokenStream, input: TokenStream) -> TokenStream {
+    let item = syn::parse_macro_input!(input as DeriveInput);
+    match expand_container(args.into(), &item) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => syn::Error::new(Span::call_site(), e).to_compile_error().into()
+    }
+}
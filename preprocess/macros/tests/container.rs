@@ -0,0 +1,58 @@
+use preprocess::traits::{Container, RandomAccess};
+use preprocess_macros::{container};
+
+#[container(impl(Container), distinct, r"property distinct<T> {
+    \c <: (Container) -> ((for-all-elems c) \a -> ((unique-count? a) c))
+}")]
+struct DistinctCon<T>;
+
+#[container(impl(Container, RandomAccess), distinct, sorted, properties("tests/properties.spec"))]
+struct SortedSetCon<T>;
+
+#[container(impl(Container, RandomAccess), unique, ascending)]
+pub struct StrictlyAscendingCon<T>;
+
+#[container(impl(Container, RandomAccess), ascending, workload(insert: 80, first: 20, size: 100), dispatch(static))]
+struct AscendingCon<S>;
+
+#[test]
+fn test_declared_property() {
+    let mut c = DistinctCon::<u32>::new();
+    for x in 0..10 {
+        c.insert(x);
+        c.insert(x);
+    }
+    assert_eq!(c.len(), 10);
+}
+
+#[test]
+fn test_properties_file() {
+    let mut c = SortedSetCon::<u32>::new();
+    for x in [3, 1, 2, 1] {
+        c.insert(x);
+    }
+    assert_eq!(c.len(), 3);
+    assert_eq!(c.first(), Some(&1));
+}
+
+#[test]
+fn test_builtin_properties() {
+    let mut c = StrictlyAscendingCon::<u32>::new();
+    for x in (0..10).rev() {
+        c.insert(x);
+        c.insert(x);
+    }
+    assert_eq!(c.len(), 10);
+    assert_eq!(c.first(), Some(&0));
+}
+
+#[test]
+fn test_static_dispatch() {
+    let mut c = AscendingCon::<u32>::new();
+    for x in [3, 1, 2] {
+        c.insert(x);
+    }
+    assert_eq!(c.len(), 3);
+    assert_eq!(c.first(), Some(&1));
+    assert_eq!(c.last(), Some(&3));
+}
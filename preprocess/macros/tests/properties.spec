@@ -0,0 +1,7 @@
+property distinct<T> {
+    \c <: (Container) -> ((for-all-elems c) \a -> ((unique-count? a) c))
+}
+
+property sorted<T> {
+    \c -> ((for-all-consecutive-pairs c) leq?)
+}
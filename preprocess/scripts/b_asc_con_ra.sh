@@ -5,5 +5,5 @@ syntactic property: Container, RandomAsscess
 search time: %R seconds
 '
 time {
-cargo run b_asc_con_ra.rs gen_asc_con_ra.rs
+cargo run -- generate spec_code/b_asc_con_ra.rs -o gen_code/gen_asc_con_ra.rs
 }
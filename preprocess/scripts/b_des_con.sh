@@ -5,5 +5,5 @@ syntactic property: Container
 search time: %R seconds
 '
 time {
-cargo run b_des_con.rs gen_des_con.rs
+cargo run -- generate spec_code/b_des_con.rs -o gen_code/gen_des_con.rs
 }
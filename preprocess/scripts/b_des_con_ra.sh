@@ -5,5 +5,5 @@ syntactic property: Container, RandomAccess
 search time: %R seconds
 '
 time {
-cargo run b_des_con_ra.rs gen_des_con_ra.rs
+cargo run -- generate spec_code/b_des_con_ra.rs -o gen_code/gen_des_con_ra.rs
 }
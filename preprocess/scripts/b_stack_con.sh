@@ -5,5 +5,5 @@ syntactic property: Container, Stack
 search time: %R seconds
 '
 time {
-cargo run b_stack_con.rs gen_stack_con.rs
+cargo run -- generate spec_code/b_stack_con.rs -o gen_code/gen_stack_con.rs
 }
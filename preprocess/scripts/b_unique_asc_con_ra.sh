@@ -5,5 +5,5 @@ syntactic property: Container, RandomAccess
 search time: %R seconds
 '
 time {
-cargo run b_unique_asc_con_ra.rs gen_unique_asc_con_ra.rs
+cargo run -- generate spec_code/b_unique_asc_con_ra.rs -o gen_code/gen_unique_asc_con_ra.rs
 }
@@ -5,5 +5,5 @@ syntactic property: Container
 search time: %R seconds
 '
 time {
-cargo run b_unique_con.rs gen_unique_con.rs
+cargo run -- generate spec_code/b_unique_con.rs -o gen_code/gen_unique_con.rs
 }
@@ -5,5 +5,5 @@ syntactic properties: Container, RandomAccess
 search time: %R seconds
 '
 time {
-cargo run b_unique_con_ra.rs gen_unique_con_ra.rs
+cargo run -- generate spec_code/b_unique_con_ra.rs -o gen_code/gen_unique_con_ra.rs
 }
@@ -1,28 +1,34 @@
 use crate::description::{Tag, InforMap};
 use crate::parser::{Prog, Block, Spec, Decl, Term, Refinement, Id, spec};
-use crate::spec_map::{PropSpecs};
+use crate::spec_map::{PropSpecs, PropTerms};
+use crate::native_matching::{MODELSIZE};
+use crate::run_matching::{WORKDIR, PROPDIR};
+use crate::error::{SpecError, DeclKind};
+use crate::types::{Type, Bounds};
 
 use std::ops::Deref;
 use std::env;
 use std::fs;
 use std::io::{Write, BufReader, BufRead, Error, ErrorKind};
 
-type AnalyserError = String;
+type AnalyserError = SpecError;
+type RefError = String;
 const LANGDECL: &str = "#lang rosette\n";
 const REQUIRE: &str = "(require \"../combinators.rkt\")\n";
 const EXTRAREQUIRE: &str = "(require \"../gen_lib_spec/ops.rkt\")\n";
-const GENPATH: &str = "./racket_specs/gen_prop_spec/";
 
 // length can be adjusted
 // set to 5 to speed up testing
-const LISTMODEL: &str =
+fn gen_list_model(len: usize) -> String {
+    format!(
 r#"
 (define (generate-list n)
     (define-symbolic* y integer? #:length n)
     y)
 (define-symbolic len (bitvector 32))
-(define ls (take-bv (generate-list 5) len))
-"#;
+(define ls (take-bv (generate-list {len}) len))
+"#)
+}
 
 fn gen_symbolic(n: &str) -> String {
     format!(
@@ -45,16 +51,30 @@ r#"
     code
 }
 
+// The bounds of a container type in the order of their names, so that the code generated
+// from them does not change between runs
+fn sorted_bounds(ins: &Bounds) -> Vec<String> {
+    let mut bounds: Vec<String> = ins.iter().cloned().collect();
+    bounds.sort();
+    bounds
+}
+
 pub struct Analyser {
     ctx: InforMap,
     prop_specs: PropSpecs,
+    prop_terms: PropTerms,
+    model_size: usize,
+    work_dir: String, // the property specifications are written under it
 }
 
 impl Analyser {
     pub fn new() -> Analyser {
         Analyser {
             ctx: InforMap::new(),
-            prop_specs: PropSpecs::new()
+            prop_specs: PropSpecs::new(),
+            prop_terms: PropTerms::new(),
+            model_size: MODELSIZE,
+            work_dir: WORKDIR.to_string()
         }
     }
 
@@ -66,6 +86,18 @@ impl Analyser {
         &self.prop_specs
     }
 
+    pub fn get_prop_terms(&self) -> &PropTerms {
+        &self.prop_terms
+    }
+
+
+    pub fn set_model_size(&mut self, model_size: usize) {
+        self.model_size = model_size;
+    }
+
+    pub fn set_work_dir(&mut self, work_dir: &str) {
+        self.work_dir = work_dir.to_string();
+    }
 
     pub fn analyse_prog(&mut self, prog: Prog) -> Result<(), AnalyserError> {
         let specs: Vec<Spec> = 
@@ -118,9 +150,12 @@ impl Analyser {
                 if (symbolics.len() != 0) {
                     symbolics_provided = gen_symbolics(&symbolics);
                 } 
-                self.write_prop_spec_file(filename.clone(), code, symbolics_provided);
+                if self.write_prop_spec_file(filename.clone(), code, symbolics_provided).is_err() {
+                    return Err(SpecError::Io("Error, unable to write the property specification: ".to_string() + &filename));
+                }
                 let prop_tag = Tag::Prop(Box::new(id.to_string()));
                 self.ctx.put(id.to_string(), prop_tag);
+                self.prop_terms.insert(id.to_string(), *term.clone());
                 if (symbolics.len() == 0) {
                     self.prop_specs.insert(id.to_string(), (filename, vec!["n".to_string()]));
                 } else {
@@ -128,7 +163,7 @@ impl Analyser {
                 }
                 Ok(())
             },
-            _ => Err("Not a valid property declaration".to_string())
+            _ => Err(SpecError::Analysis { decl: None, message: "Not a valid property declaration".to_string(), span: None })
         }
     }
 
@@ -145,10 +180,10 @@ impl Analyser {
 
     pub fn analyse_bound_decl(&mut self, decl: &Decl) -> Result<(), AnalyserError> {
         match decl {
-            Decl::ConTypeDecl(con_ty, (_, ins, tags)) => {
+            Decl::ConTypeDecl(con_ty, (_, ins, tags), _, _) => {
                 let (c, t) = con_ty.get_con_elem().unwrap();
                 let mut name = c.clone() + "Trait";
-                let bound_tag = Tag::Bound((c.clone(), t), Box::new(ins.clone().into_iter().collect::<Vec<String>>()));
+                let bound_tag = Tag::Bound((c.clone(), t), Box::new(sorted_bounds(ins)));
                 let immut_ctx = self.ctx.clone();
                 // prevent generating existing name
                 let mut i: usize = 0;
@@ -162,13 +197,13 @@ impl Analyser {
                         self.ctx.update(c.clone(), Tag::Con(elem_ty.to_string(), name.clone(), Box::new(tags.to_vec())));
                     },
                     _ => { 
-                        return Err("Not a valid container declaration.".to_string());
+                        return Err(SpecError::analysis_error(DeclKind::ConType, &c, "Not a valid container declaration.".to_string()));
                     }
                 }
                 self.ctx.put(name, bound_tag);
                 Ok(())
             },
-            _ => Err("Not a valid bound declaration".to_string())
+            _ => Err(SpecError::Analysis { decl: None, message: "Not a valid bound declaration".to_string(), span: None })
         }
     }
 
@@ -186,26 +221,88 @@ impl Analyser {
     pub fn analyse_contype_decl(&mut self, decl: &Decl) -> Result<(), AnalyserError> {
         let mut tags = Vec::<Tag>::new();
         match decl {
-            Decl::ConTypeDecl(con_ty, (vid, ins, r)) => {
+            Decl::ConTypeDecl(con_ty, (vid, ins, r), workload, dispatch) => {
                 let (c, t) = con_ty.get_con_elem().unwrap();
-                let i_tag = Tag::Bound((c.clone(), t.clone()), Box::new(ins.clone().into_iter().collect::<Vec<String>>()));
+                let i_tag = Tag::Bound((c.clone(), t.clone()), Box::new(sorted_bounds(ins)));
                 tags.push(i_tag);
-                match self.analyse_ref(r.deref(), vid) {
+                let prop_tags = if self.is_prop_conjunction(r) {
+                    self.analyse_ref(r.deref(), vid)
+                } else {
+                    self.lower_ref(&c, r, vid)
+                };
+                match prop_tags {
                     Ok(prop_tags) => {
                         let mut prop_tags_mut = prop_tags.clone();
                         tags.append(&mut prop_tags_mut);
+                        if !workload.is_empty() {
+                            tags.push(Tag::Workload(workload.clone()));
+                        }
+                        if let Some(d) = dispatch {
+                            tags.push(Tag::Dispatch(*d));
+                        }
                         let con_tag = Tag::Con(t, String::new(), Box::new(tags));
                         self.ctx.put(c, con_tag);
                         Ok(())
                     },
-                    Err(e) => Err(e)
+                    Err(e) => Err(SpecError::analysis_error(DeclKind::ConType, &c, e))
+                }
+            },
+            _ => Err(SpecError::Analysis { decl: None, message: "Not a valid container type declaration".to_string(), span: None })
+        }
+    }
+
+    // Whether the refinement is a conjunction of properties applied to the container,
+    // each of which is checked on its own
+    fn is_prop_conjunction(&self, r: &Refinement) -> bool {
+        match r {
+            Refinement::Prop(term) => {
+                match term.deref() {
+                    Term::AppTerm(t1, t2) => matches!(t2.deref(), Term::VarTerm(_)) && self.retrive_ref_term(t1).is_ok(),
+                    _ => false
                 }
             },
-            _ => Err("Not a valid container type declaration".to_string())
+            Refinement::AndProps(r1, r2) => self.is_prop_conjunction(r1) && self.is_prop_conjunction(r2),
+            _ => false
         }
     }
 
-    fn analyse_ref<'a>(&self, r: &'a Refinement, vid: &'a Box<String>) -> Result<Vec<Tag>, AnalyserError> {
+    // Lower a refinement combining properties with or, not or => into a property of its own,
+    // so that library structs are checked against the formula as a whole
+    fn lower_ref(&mut self, c: &str, r: &Refinement, vid: &str) -> Result<Vec<Tag>, RefError> {
+        let mut name = c.to_string() + "-refinement";
+        let mut i: usize = 0;
+        while self.ctx.contains(&name) {
+            name = name + &i.to_string();
+            i += 1;
+        }
+        let body = self.inline_props(&r.to_term(), &mut Vec::new());
+        let term = Term::LambdaTerm((Box::new(vid.to_string()), Box::new(Bounds::new())), Box::new(body));
+        let decl = Decl::PropertyDecl((Box::new(name.clone()), Box::new(Type::Bool())), Box::new(term));
+        match self.analyse_prop_decl(&decl) {
+            Ok(_) => Ok(vec![Tag::Prop(Box::new(name))]),
+            Err(e) => Err(e.to_string())
+        }
+    }
+
+    // Replace the properties referred to in a term by their definitions,
+    // as each property specification is a module of its own
+    fn inline_props(&self, term: &Term, bound: &mut Vec<Id>) -> Term {
+        match term {
+            Term::VarTerm(id) if !bound.contains(id) && self.prop_terms.contains_key(id.as_str()) => {
+                self.inline_props(self.prop_terms.get(id.as_str()).unwrap(), &mut Vec::new())
+            },
+            Term::LambdaTerm((id, bounds), t) => {
+                bound.push(id.to_string());
+                let t = self.inline_props(t, bound);
+                bound.pop();
+                Term::LambdaTerm((id.clone(), bounds.clone()), Box::new(t))
+            },
+            Term::AppTerm(t1, t2) => Term::AppTerm(Box::new(self.inline_props(t1, bound)), Box::new(self.inline_props(t2, bound))),
+            _ => term.clone()
+        }
+    }
+
+    fn analyse_ref<'a>(&self, r: &'a Refinement, vid: &'a Box<String>) -> Result<Vec<Tag>, RefError> {
         match r {
             Refinement::Prop(term) => {
                 match term.deref() {
@@ -233,11 +330,12 @@ impl Analyser {
                     },
                     Err(e) => Err(e)
                 }
-            }
+            },
+            _ => Err("Should be a conjunction of properties".to_string())
         }
     }
 
-    fn retrive_ref_term(&self, term: &Term) -> Result<&Tag, AnalyserError> {
+    fn retrive_ref_term(&self, term: &Term) -> Result<&Tag, RefError> {
         match term {
             Term::VarTerm(id) => {
                 match self.ctx.get_id(id.to_string()) {
@@ -257,10 +355,10 @@ impl Analyser {
     pub fn analyse_term(&self, term: &mut Term, is_outter_app: bool, is_quantifier: bool, cdr_added: &mut Vec<String>, symbolics: &mut Vec<String>) -> String {
         match term {
             Term::LitTerm(lit) => {
-                if (lit.to_string() == "true".to_string()) {
-                    "#t".to_string()
-                } else {
-                    "#f".to_string()
+                match lit.as_str() {
+                    "true" => "#t".to_string(),
+                    "false" => "#f".to_string(),
+                    n => n.to_string()
                 }
             },
             Term::VarTerm(id) => {
@@ -268,7 +366,9 @@ impl Analyser {
             },
             Term::LambdaTerm((id, _), t) => {
                 if (is_quantifier) {
-                    symbolics.push(id.to_string());
+                    if !symbolics.contains(id) {
+                        symbolics.push(id.to_string());
+                    }
                     "(list ".to_string() + id + ") " + &self.analyse_term(t, true, false, cdr_added, symbolics) 
                 } else {
                     "(lambda (".to_string() + id + ") " + &self.analyse_term(t, true, false, cdr_added, symbolics) + ")" 
@@ -312,11 +412,11 @@ impl Analyser {
     }
 
     fn write_prop_spec_file(&self, filename : String, contents: String, symbolics: String) -> Result<(), Error> {
-        let mut output = fs::File::create(GENPATH.to_owned() + &filename)?;
+        let mut output = fs::File::create(self.work_dir.to_owned() + PROPDIR + &filename)?;
         write!(output, "{}", LANGDECL.to_string())?;
         write!(output, "{}", REQUIRE.to_string())?;
         write!(output, "{}", EXTRAREQUIRE.to_string())?;
-        write!(output, "{}", LISTMODEL.to_string())?;
+        write!(output, "{}", gen_list_model(self.model_size))?;
         write!(output, "{}", contents)?;
         write!(output, "{}", symbolics)?;
         Ok(())
@@ -0,0 +1,263 @@
+// Criterion benchmarks of the candidate structs of a container type, and the
+// summary of their results choosing the fastest struct for an operation mix
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::parser::{Workload};
+use crate::cost::{NOMINAL_SIZE};
+
+pub const BENCHSIZES: [usize; 3] = [100, 1000, 10000];
+pub const CRITERIONDIR: &str = "./target/criterion/";
+
+type ErrorMessage = String;
+
+// The benchmarked operations of a bound
+fn bound_ops(bound: &str) -> &'static [&'static str] {
+    match bound {
+        "Container" => &["insert", "contains", "remove"],
+        "Stack" => &["push", "pop"],
+        "RandomAccess" => &["first", "last", "nth"],
+        _ => &[]
+    }
+}
+
+// The benchmarked operations of a container type, in the order of its bounds
+pub fn bench_ops(bounds: &[String]) -> Vec<&'static str> {
+    let mut ops = Vec::<&'static str>::new();
+    for b in ["Container", "Stack", "RandomAccess"].iter().filter(|b| bounds.iter().any(|i| i == *b)) {
+        ops.extend_from_slice(bound_ops(b));
+    }
+    ops
+}
+
+// The name of a struct without its path, e.g. BTreeSet
+pub fn short_name(lib_struct: &str) -> &str {
+    lib_struct.rsplit("::").next().unwrap_or(lib_struct)
+}
+
+// e.g. UniqueCon to unique_con
+pub fn snake_case(name: &str) -> String {
+    let mut s = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            s.push('_');
+        }
+        s.extend(c.to_lowercase());
+    }
+    s
+}
+
+// Whether the operation is measured on a container holding the dataset, and the loop measured
+fn gen_op_body(op: &str) -> (bool, String) {
+    match op {
+        "insert" => (false, "for x in data.iter() { Container::<u32>::insert(&mut s, *x); }".to_string()),
+        "contains" => (true, "for x in data.iter() { black_box(Container::<u32>::contains(&mut s, x)); }".to_string()),
+        "remove" => (true, "for x in data.iter() { black_box(Container::<u32>::remove(&mut s, *x)); }".to_string()),
+        "push" => (false, "for x in data.iter() { Stack::<u32>::push(&mut s, *x); }".to_string()),
+        "pop" => (true, "for _ in data.iter() { black_box(Stack::<u32>::pop(&mut s)); }".to_string()),
+        "nth" => (true, "for i in 0..data.len() { black_box(RandomAccess::<u32>::nth(&mut s, i)); }".to_string()),
+        _ => (true, format!("for _ in data.iter() {{ black_box(RandomAccess::<u32>::{}(&mut s)); }}", op)),
+    }
+}
+
+fn gen_bench_fn(con: &str, op: &str, candidates: &[String]) -> String {
+    let (is_filled, body) = gen_op_body(op);
+    let benches = candidates.iter().map(|lib_struct| {
+        let new = format!("<{}<u32>>::new()", lib_struct);
+        let setup = if is_filled { format!("fill({}, data)", new) } else { new };
+        let name = short_name(lib_struct);
+        format!(
+r#"        group.bench_with_input(BenchmarkId::new("{name}", n), &data, |b, data| b.iter_batched(
+            || {setup},
+            |mut s| {{
+                {body}
+                s
+            }},
+            BatchSize::SmallInput));
+"#)
+    }).collect::<String>();
+    let f = snake_case(con) + "_" + op;
+    format!(
+r#"
+fn {f}(c: &mut Criterion) {{
+    let mut group = c.benchmark_group("{con}-{op}");
+    for n in SIZES.iter() {{
+        let data = gen_dataset(*n);
+{benches}    }}
+    group.finish();
+}}
+"#)
+}
+
+// A criterion benchmark of the operations of the bounds on each candidate struct, at each of BENCHSIZES
+pub fn gen_benchmark(con: &str, candidates: &[String], bounds: &[String]) -> String {
+    let ops = bench_ops(bounds);
+    let traits = ["Container", "Stack", "RandomAccess"].iter()
+        .filter(|b| bounds.iter().any(|i| i == *b))
+        .cloned()
+        .collect::<Vec<&str>>()
+        .join(", ");
+    let sizes = BENCHSIZES.iter().map(|n| n.to_string()).collect::<Vec<String>>().join(", ");
+    let fns = ops.iter().map(|op| gen_bench_fn(con, op, candidates)).collect::<String>();
+    let group = snake_case(con);
+    let targets = ops.iter().map(|op| group.to_string() + "_" + op).collect::<Vec<String>>().join(", ");
+    format!(
+r#"// Benchmarks of the candidate structs of {con}, generated by preprocess
+use criterion::{{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion}};
+use preprocess::traits::{{{traits}}};
+use preprocess::tools::{{gen_dataset, fill}};
+
+const SIZES: [usize; {count}] = [{sizes}];
+{fns}
+criterion_group! {{
+    name = {group};
+    config = Criterion::default().sample_size(10);
+    targets = {targets}
+}}
+
+criterion_main!({group});
+"#, count = BENCHSIZES.len())
+}
+
+// The mean time of a benchmark in ns, from the estimates.json written by criterion
+fn read_estimate(path: &str) -> Option<f64> {
+    let contents = fs::read_to_string(path).ok()?;
+    let mean = &contents[contents.find("\"mean\"")?..];
+    let estimate = &mean[mean.find("\"point_estimate\"")? + "\"point_estimate\"".len()..];
+    let value = estimate.trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+    let end = value.find([',', '}']).unwrap_or(value.len());
+    value[..end].trim().parse::<f64>().ok()
+}
+
+// The names of the subdirectories, except the reports of criterion
+fn read_subdirs(dir: &str) -> Vec<String> {
+    let mut names: Vec<String> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|name| name != "report")
+            .collect(),
+        Err(_) => Vec::new()
+    };
+    names.sort();
+    names
+}
+
+// The time per operation in ns of each benchmarked struct, operation and size
+type Results = BTreeMap<String, BTreeMap<(String, usize), f64>>;
+
+fn read_results(criterion_dir: &str, con: &str, ops: &[&str]) -> Results {
+    let mut results = Results::new();
+    for op in ops.iter() {
+        let group = criterion_dir.trim_end_matches('/').to_string() + "/" + con + "-" + op;
+        for name in read_subdirs(&group) {
+            for size in read_subdirs(&(group.to_string() + "/" + &name)) {
+                let n = match size.parse::<usize>() {
+                    Ok(n) if n > 0 => n,
+                    _ => continue
+                };
+                let path = format!("{}/{}/{}/new/estimates.json", group, name, size);
+                if let Some(t) = read_estimate(&path) {
+                    results.entry(name.to_string()).or_default().insert((op.to_string(), n), t / n as f64);
+                }
+            }
+        }
+    }
+    results
+}
+
+// The fastest struct for each operation and for the operation mix of the workload at each
+// benchmarked size, choosing the struct at the size nearest to the size of the workload
+pub fn summarise(criterion_dir: &str, con: &str, bounds: &[String], workload: &Workload) -> Result<String, ErrorMessage> {
+    let ops = bench_ops(bounds);
+    let results = read_results(criterion_dir, con, &ops);
+    if results.is_empty() {
+        return Err(format!("Error, no benchmark results of {} in {}, run its benchmark first", con, criterion_dir));
+    }
+    let mut mix: Vec<(String, u64)> = workload.ops.iter().filter(|(op, _)| ops.contains(&op.as_str())).cloned().collect();
+    if mix.is_empty() {
+        mix = ops.iter().map(|op| (op.to_string(), 1)).collect();
+    }
+    let mut sizes: Vec<usize> = results.values().flat_map(|r| r.keys().map(|(_, n)| *n)).collect();
+    sizes.sort();
+    sizes.dedup();
+    let mix_str = mix.iter().map(|(op, w)| format!("{} x{}", op, w)).collect::<Vec<String>>().join(", ");
+    let mut summary = format!("{}: fastest for {}", con, mix_str);
+    let mut chosen = BTreeMap::<usize, String>::new();
+    for n in sizes.iter() {
+        let fastest = |op: &str| results.iter()
+            .filter_map(|(name, r)| r.get(&(op.to_string(), *n)).map(|t| (name, *t)))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        let per_op = ops.iter()
+            .filter_map(|op| fastest(op).map(|(name, t)| format!("{} {} ({:.1} ns)", op, name, t)))
+            .collect::<Vec<String>>()
+            .join(", ");
+        // the weighted mean time of the mix, for the structs with results for all of its operations
+        let total: u64 = mix.iter().map(|(_, w)| w).sum();
+        let best = results.iter()
+            .filter_map(|(name, r)| {
+                let times: Option<Vec<f64>> = mix.iter().map(|(op, w)| r.get(&(op.to_string(), *n)).map(|t| t * *w as f64)).collect();
+                times.map(|t| (name, t.iter().sum::<f64>() / total as f64))
+            })
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        summary = summary + &format!("\n  n = {}: ", n);
+        if let Some((name, t)) = best {
+            summary = summary + &format!("{} ({:.1} ns per operation); ", name, t);
+            chosen.insert(*n, name.to_string());
+        }
+        summary = summary + &per_op;
+    }
+    let size = workload.size.map(|s| s as f64).unwrap_or(NOMINAL_SIZE);
+    let nearest = chosen.keys().min_by(|a, b| {
+        let d = |n: &usize| ((*n as f64).ln() - size.ln()).abs();
+        d(a).partial_cmp(&d(b)).unwrap()
+    });
+    if let Some(n) = nearest {
+        summary = summary + &format!("\n  chosen {}, the fastest at n = {}, nearest to the size {}", chosen[n], n, size);
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::benchmark::{gen_benchmark, snake_case, summarise};
+    use crate::parser::{Workload};
+
+    #[test]
+    fn test_gen_benchmark() {
+        let candidates = vec!["std::collections::BTreeSet".to_string(), "std::vec::Vec".to_string()];
+        let bench = gen_benchmark("UniqueCon", &candidates, &["Container".to_string()]);
+        assert_eq!(snake_case("UniqueCon"), "unique_con");
+        assert!(bench.contains("use preprocess::traits::{Container};"));
+        assert!(bench.contains("c.benchmark_group(\"UniqueCon-remove\")"));
+        assert!(bench.contains("BenchmarkId::new(\"BTreeSet\", n)"));
+        assert!(bench.contains("|| fill(<std::vec::Vec<u32>>::new(), data),"));
+        assert!(bench.contains("targets = unique_con_insert, unique_con_contains, unique_con_remove"));
+        assert!(!bench.contains("push"));
+    }
+
+    #[test]
+    fn test_summarise() {
+        let dir = std::env::temp_dir().join("preprocess-criterion-test");
+        let dir = dir.to_str().unwrap();
+        let _ = fs::remove_dir_all(dir);
+        // time of n operations at n = 10
+        for (op, name, t) in [("insert", "A", 100.0), ("insert", "B", 300.0), ("contains", "A", 500.0), ("contains", "B", 200.0), ("remove", "A", 10.0), ("remove", "B", 10.0)] {
+            let path = format!("{}/Con-{}/{}/10/new", dir, op, name);
+            fs::create_dir_all(&path).unwrap();
+            let estimates = format!("{{\"mean\":{{\"confidence_interval\":{{\"confidence_level\":0.95}},\"point_estimate\":{},\"standard_error\":1.0}}}}", t);
+            fs::write(path + "/estimates.json", estimates).unwrap();
+        }
+        let bounds = vec!["Container".to_string()];
+        let insert_heavy = Workload { ops: vec![("insert".to_string(), 9), ("contains".to_string(), 1), ("len".to_string(), 5)], size: None };
+        let summary = summarise(dir, "Con", &bounds, &insert_heavy).unwrap();
+        assert!(summary.starts_with("Con: fastest for insert x9, contains x1\n  n = 10: A (14.0 ns per operation); insert A (10.0 ns), contains B (20.0 ns)"));
+        assert!(summary.ends_with("chosen A, the fastest at n = 10, nearest to the size 1000"));
+        let lookup_heavy = Workload { ops: vec![("contains".to_string(), 1)], size: None };
+        assert!(summarise(dir, "Con", &bounds, &lookup_heavy).unwrap().contains("chosen B"));
+        assert!(summarise(dir, "Other", &bounds, &lookup_heavy).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
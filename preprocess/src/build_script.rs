@@ -0,0 +1,138 @@
+// Compiling the container types of a crate from its build script, e.g. in build.rs
+//
+//     fn main() {
+//         preprocess::build_script::compile_specs(&["src/"]).unwrap();
+//     }
+//
+// Each source file with spec blocks, e.g. src/sets.rs, then includes its container types with
+//
+//     include!(concat!(env!("OUT_DIR"), "/sets.rs"));
+//
+// where a file in a subdirectory, e.g. src/coll/sets.rs, is written to coll_sets.rs.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::generator::{MatchOptions, process_specs};
+
+const CRATEDIR: &str = env!("CARGO_MANIFEST_DIR");
+const SPEC: &str = "/*SPEC*";
+const WORKDIR: &str = "preprocess"; // within OUT_DIR, for the generated specifications and the match cache
+
+type ErrorMessage = String;
+
+// The options of a build, matching against the library of this crate
+pub fn build_options() -> MatchOptions {
+    MatchOptions {
+        lib_dir: CRATEDIR.to_string() + "/src/library/",
+        quiet: true,
+        ..MatchOptions::default()
+    }
+}
+
+// The Rust files under a path with spec blocks, each with the name of its generated file
+fn spec_sources(path: &Path) -> Result<Vec<(PathBuf, String)>, ErrorMessage> {
+    let unreadable = |p: &Path| "Error, unable to read ".to_string() + &p.to_string_lossy();
+    let mut files = Vec::<(PathBuf, String)>::new();
+    if path.is_dir() {
+        let mut pending = vec![path.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(&dir).map_err(|_| unreadable(&dir))? {
+                let p = entry.map_err(|_| unreadable(&dir))?.path();
+                if p.is_dir() {
+                    pending.push(p);
+                } else if p.extension().is_some_and(|e| e == "rs") {
+                    let name = p.strip_prefix(path).unwrap_or(&p).iter()
+                        .map(|c| c.to_string_lossy()).collect::<Vec<_>>().join("_");
+                    files.push((p, name));
+                }
+            }
+        }
+    } else {
+        let name = path.file_name().ok_or_else(|| unreadable(path))?.to_string_lossy().to_string();
+        files.push((path.to_path_buf(), name));
+    }
+    let mut sources = Vec::new();
+    for (file, name) in files.into_iter() {
+        if fs::read_to_string(&file).map_err(|_| unreadable(&file))?.contains(SPEC) {
+            sources.push((file, name));
+        }
+    }
+    sources.sort();
+    Ok(sources)
+}
+
+// The library files the matching depends on
+fn lib_files(lib_dir: &Path) -> Result<Vec<PathBuf>, ErrorMessage> {
+    let mut files: Vec<PathBuf> = fs::read_dir(lib_dir)
+        .map_err(|_| "Error, unable to read the library directory ".to_string() + &lib_dir.to_string_lossy())?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|e| e == "rs"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+pub fn compile_specs(paths: &[&str]) -> Result<Vec<PathBuf>, ErrorMessage> {
+    compile_specs_with(paths, &build_options())
+}
+
+// Generate the container types of the source files with spec blocks under the paths into
+// OUT_DIR, telling cargo to rerun the build script when they or the library change.
+// Gives the generated files.
+pub fn compile_specs_with(paths: &[&str], options: &MatchOptions) -> Result<Vec<PathBuf>, ErrorMessage> {
+    let out_dir = PathBuf::from(env::var("OUT_DIR").map_err(|_| "Error, OUT_DIR is not set, compile the specifications from a build script".to_string())?);
+    let absolute = |p: &Path| fs::canonicalize(p).map_err(|_| "Error, unable to find ".to_string() + &p.to_string_lossy());
+    let mut roots = Vec::<PathBuf>::new();
+    let mut sources = Vec::<(PathBuf, String)>::new();
+    for path in paths.iter() {
+        let root = absolute(Path::new(path))?;
+        for (file, name) in spec_sources(&root)?.into_iter() {
+            if sources.iter().any(|(_, n)| *n == name) {
+                return Err("Error, two source files generate ".to_string() + &name);
+            }
+            sources.push((file, name));
+        }
+        roots.push(root);
+    }
+    let lib_dir = absolute(Path::new(&options.lib_dir))?;
+    // the directories too, so that adding a file to them reruns the build script
+    let lib = lib_files(&lib_dir)?;
+    for file in roots.iter().chain(sources.iter().map(|(f, _)| f)).chain(std::iter::once(&lib_dir)).chain(lib.iter()) {
+        println!("cargo:rerun-if-changed={}", file.display());
+    }
+    let options = MatchOptions {
+        lib_dir: lib_dir.to_string_lossy().to_string() + "/",
+        work_dir: out_dir.join(WORKDIR).to_string_lossy().to_string() + "/",
+        ..options.clone()
+    };
+    sources.iter()
+        .map(|(file, name)| {
+            let code = process_specs(file.to_string_lossy().to_string(), &options).map_err(|e| e.to_string())?;
+            let output = out_dir.join(name);
+            fs::write(&output, code).map_err(|_| "Error, unable to write ".to_string() + &output.to_string_lossy())?;
+            Ok(output)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::build_script::{spec_sources};
+
+    #[test]
+    fn test_spec_sources() {
+        let dir = std::env::temp_dir().join("preprocess-build-test");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("coll")).unwrap();
+        fs::write(dir.join("main.rs"), "fn main() {}").unwrap();
+        fs::write(dir.join("sets.rs"), "/*SPEC*\n*ENDSPEC*/").unwrap();
+        fs::write(dir.join("coll/stacks.rs"), "/*SPEC*\n*ENDSPEC*/").unwrap();
+        let names: Vec<String> = spec_sources(&dir).unwrap().into_iter().map(|(_, n)| n).collect();
+        assert_eq!(names, vec!["coll_stacks.rs", "sets.rs"]);
+        let names: Vec<String> = spec_sources(&dir.join("coll/stacks.rs")).unwrap().into_iter().map(|(_, n)| n).collect();
+        assert_eq!(names, vec!["stacks.rs"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,206 @@
+// Checking that a generated program compiles before it is written. The program is placed
+// in a scratch crate of its own depending on this crate by path and checked with cargo check
+// --offline, with and without cfg(test), so that the generated test modules are checked too.
+// This crate is found in the directory named by PREPROCESS_CRATE_DIR, by default where it was
+// built from.
+// Each error is mapped back to the container type whose generated code it is in, or to its
+// position in the code blocks of the source.
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::parser::{Id};
+use crate::error::{CompileError, DeclKind, SourceMap, SpecError};
+
+const CRATEDIR: &str = env!("CARGO_MANIFEST_DIR");
+const CRATEDIRVAR: &str = "PREPROCESS_CRATE_DIR";
+const CHECKCRATE: &str = "preprocess-check";
+const CHECKFILE: &str = "src/lib.rs";
+const CHECKTARGET: &str = "preprocess-check-target"; // in the temporary directory
+
+// The checks made by this process so far, naming their scratch crates
+static CHECKS: AtomicUsize = AtomicUsize::new(0);
+
+type ErrorMessage = String;
+
+// Where a part of a generated program comes from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Origin {
+    Generated,   // shared by the container types, e.g. the imports
+    Con(Id),     // generated for a container type
+    Code(usize), // copied from the code block at this offset of the marked source
+}
+
+// The offset in the generated program at which each part starts, in order
+pub type Origins = Vec<(usize, Origin)>;
+
+// Each check has a scratch crate of its own, so that checks in parallel do not overwrite each
+// other's, removed after the check
+fn check_dir() -> PathBuf {
+    let n = CHECKS.fetch_add(1, Ordering::SeqCst);
+    std::env::temp_dir().join(format!("{}-{}-{}", CHECKCRATE, process::id(), n))
+}
+
+// The scratch crates share their target directory, so that the dependencies are built once;
+// cargo locks it while a check builds
+fn target_dir() -> PathBuf {
+    std::env::temp_dir().join(CHECKTARGET)
+}
+
+// The directory of this crate, which the scratch crates depend on
+fn crate_dir() -> Result<PathBuf, ErrorMessage> {
+    let dir = PathBuf::from(env::var(CRATEDIRVAR).unwrap_or_else(|_| CRATEDIR.to_string()));
+    if dir.join("Cargo.toml").exists() {
+        Ok(dir)
+    } else {
+        Err(format!("Error, no preprocess crate in {} to check the generated program with, set {} to its directory", dir.to_string_lossy(), CRATEDIRVAR))
+    }
+}
+
+fn setup_crate(dir: &Path, code: &str) -> Result<(), ErrorMessage> {
+    let crate_dir = crate_dir()?;
+    let crate_path = crate_dir.to_string_lossy().replace('\\', "\\\\").replace('"', "\\\"");
+    let manifest = format!(
+r#"[package]
+name = "{CHECKCRATE}"
+version = "0.1.0"
+edition = "2018"
+
+[dependencies]
+preprocess = {{ path = "{crate_path}" }}
+
+[workspace]
+"#);
+    let write = |path: PathBuf, contents: &str| fs::write(&path, contents)
+        .map_err(|_| "Error, unable to write the scratch crate: ".to_string() + &path.to_string_lossy());
+    fs::create_dir_all(dir.join("src")).map_err(|_| "Error, unable to create the scratch crate: ".to_string() + &dir.to_string_lossy())?;
+    write(dir.join("Cargo.toml"), &manifest)?;
+    write(dir.join(CHECKFILE), code)?;
+    // the dependencies resolved and the toolchain used by this crate
+    for file in ["Cargo.lock", "rust-toolchain.toml"] {
+        let from = crate_dir.join(file);
+        if from.exists() && fs::copy(&from, dir.join(file)).is_err() {
+            return Err("Error, unable to copy to the scratch crate: ".to_string() + file);
+        }
+    }
+    Ok(())
+}
+
+// The errors reported by cargo check --message-format=short, each with its line and column
+// in the checked file, if it is in it; an error reported with and without cfg(test) once
+fn parse_errors(stderr: &str) -> Vec<(Option<(usize, usize)>, String)> {
+    let mut errors = Vec::new();
+    for line in stderr.lines() {
+        let located = line.strip_prefix(CHECKFILE).and_then(|l| l.strip_prefix(':')).and_then(|l| {
+            let mut fields = l.splitn(3, ':');
+            let line = fields.next()?.parse::<usize>().ok()?;
+            let column = fields.next()?.parse::<usize>().ok()?;
+            Some(((line, column), fields.next()?.trim()))
+        });
+        let error = match located {
+            Some((position, message)) if message.starts_with("error") => (Some(position), message.to_string()),
+            Some(_) => continue,
+            None if line.starts_with("error") && !line.starts_with("error: could not compile") => (None, line.to_string()),
+            None => continue
+        };
+        if !errors.contains(&error) {
+            errors.push(error);
+        }
+    }
+    errors
+}
+
+// The offset in the code of a line and column, from 1
+fn offset_of(code: &str, line: usize, column: usize) -> usize {
+    let start: usize = code.split_inclusive('\n').take(line - 1).map(|l| l.len()).sum();
+    let rest = &code[start.min(code.len())..];
+    start + rest.chars().take(column - 1).map(|c| c.len_utf8()).sum::<usize>()
+}
+
+// The compile errors of the generated code, located in the source
+pub fn compile_errors(stderr: &str, code: &str, origins: &Origins, src_map: &SourceMap) -> Vec<CompileError> {
+    parse_errors(stderr).into_iter()
+        .map(|(position, message)| {
+            let origin = position.map(|(line, column)| offset_of(code, line, column))
+                .and_then(|offset| origins.iter().rev().find(|(start, _)| *start <= offset).map(|(start, origin)| (offset - start, origin)));
+            match origin {
+                Some((_, Origin::Con(con))) => CompileError { message, con: Some(con.to_string()), span: src_map.decl_span(DeclKind::ConType, con) },
+                Some((offset, Origin::Code(n))) => CompileError { message, con: None, span: Some(src_map.marked_span(n + offset)) },
+                _ => CompileError { message, con: None, span: None }
+            }
+        })
+        .collect()
+}
+
+// Check that the generated code compiles, with its test modules
+pub fn check_output(code: &str, origins: &Origins, src_map: &SourceMap) -> Result<(), SpecError> {
+    let dir = check_dir();
+    let output = setup_crate(&dir, code).map_err(SpecError::Io).and_then(|_| Command::new("cargo")
+        .args(["check", "--offline", "--lib", "--tests", "--message-format=short"])
+        .env("CARGO_TARGET_DIR", target_dir())
+        .current_dir(&dir)
+        .output()
+        .map_err(|_| SpecError::Io("Error, unable to run cargo check in ".to_string() + &dir.to_string_lossy())));
+    let _ = fs::remove_dir_all(&dir);
+    let output = output?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let mut errors = compile_errors(&stderr, code, origins, src_map);
+    if errors.is_empty() {
+        errors.push(CompileError { message: stderr.trim().to_string(), con: None, span: None });
+    }
+    Err(SpecError::Compile(errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::compile_check::{Origin, compile_errors};
+    use crate::error::{SourceMap, SpecError};
+    use crate::generator::{MatchOptions, process_and_check_src};
+
+    #[test]
+    fn test_compile_errors() {
+        let src = "/*SPEC*\ntype UniqueCon<T> = {c impl (Container) | (unique c)}\n*ENDSPEC*/\nfn main() {\n    let c = 1;\n}\n";
+        let code_start = src.find("fn main").unwrap();
+        // the marked source, the spec block followed by /*CODE*/ and the code
+        let marked_code = code_start + "/*CODE*/".len();
+        let src_map = SourceMap::new("a.rs", src, vec![(0, 0), (marked_code, code_start)]);
+        let generated = "use a;\nstruct UniqueCon<T> {\n    c: Vec<T>,\n}\nfn main() {\n    let c = 1;\n}\n";
+        let con_start = generated.find("struct").unwrap();
+        let code = generated.find("fn main").unwrap();
+        let origins = vec![(0, Origin::Generated), (con_start, Origin::Con("UniqueCon".to_string())), (code, Origin::Code(marked_code))];
+        let stderr = "warning: unused variable\nsrc/lib.rs:3:8: error[E0412]: cannot find type `Vec2`\nsrc/lib.rs:6:9: error[E0308]: mismatched types\nerror: could not compile `preprocess-check`\n";
+        let errors = compile_errors(stderr, generated, &origins, &src_map);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].con, Some("UniqueCon".to_string()));
+        assert_eq!(errors[0].span.as_ref().map(|s| s.line), Some(2));
+        assert_eq!(errors[1].con, None);
+        let span = errors[1].span.as_ref().unwrap();
+        assert_eq!((span.line, span.column, span.text.as_str()), (5, 9, "    let c = 1;"));
+    }
+
+    #[test]
+    fn test_check_output() {
+        let dir = std::env::temp_dir().join("preprocess-check-test");
+        fs::create_dir_all(&dir).unwrap();
+        // the error is in a test module, so only found checking with cfg(test)
+        let src = "/*SPEC*\nproperty unique<T> {\n    \\c <: (Container) -> ((for-all-elems c) \\a -> ((unique-count? a) c))\n}\ntype UniqueCon<T> = {c impl (Container) | (unique c)}\n*ENDSPEC*/\n#[cfg(test)]\nmod tests {\n    fn one() {\n        let n: u32 = \"one\";\n    }\n}\nfn main() {}\n";
+        let input = dir.join("bad.rs");
+        fs::write(&input, src).unwrap();
+        let options = MatchOptions { work_dir: dir.to_string_lossy().to_string() + "/", quiet: true, ..MatchOptions::default() };
+        let errors = match process_and_check_src(input.to_string_lossy().to_string(), &options) {
+            Err(SpecError::Compile(errors)) => errors,
+            r => panic!("expected a compile error, got {:?}", r)
+        };
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("mismatched types"));
+        let span = errors[0].span.as_ref().unwrap();
+        assert_eq!((span.line, span.text.as_str()), (10, "        let n: u32 = \"one\";"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
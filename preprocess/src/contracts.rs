@@ -0,0 +1,141 @@
+// Checking the properties of the container types at run time. A container type generated
+// with --contracts wraps its implementation in a Checked, which evaluates the properties
+// declared for the container type after each call changing the container, and panics with
+// the name of the property violated. This catches a library struct which does not behave as
+// its specification says, or a specification of the wrong struct. The checks are made in
+// debug builds only: in release builds the predicates are not even built, and a Checked
+// only holds its implementation.
+use crate::traits::{Container, Stack, RandomAccess};
+
+// Whether a property holds of the implementation of a container type, as translated by
+// predicates::gen_predicate
+pub type Predicate<C> = fn(&mut C) -> bool;
+
+// The properties of a container type, by name, built only in debug builds
+pub type Props<C> = fn() -> Vec<(&'static str, Predicate<C>)>;
+
+pub struct Checked<C> {
+    inner: C,
+    #[cfg(debug_assertions)]
+    con: &'static str,
+    #[cfg(debug_assertions)]
+    lib_struct: &'static str,
+    #[cfg(debug_assertions)]
+    props: Vec<(&'static str, Predicate<C>)>,
+}
+
+impl<C> Checked<C> {
+    #[cfg(debug_assertions)]
+    pub fn new(inner: C, con: &'static str, lib_struct: &'static str, props: Props<C>) -> Checked<C> {
+        Checked { inner, con, lib_struct, props: props() }
+    }
+
+    #[cfg(not(debug_assertions))]
+    pub fn new(inner: C, _con: &'static str, _lib_struct: &'static str, _props: Props<C>) -> Checked<C> {
+        Checked { inner }
+    }
+
+    #[cfg(debug_assertions)]
+    fn check(&mut self, op: &str) {
+        for (name, holds) in self.props.iter() {
+            if !holds(&mut self.inner) {
+                panic!("{}: property {} violated by {} after {}", self.con, name, self.lib_struct, op);
+            }
+        }
+    }
+
+    #[cfg(not(debug_assertions))]
+    fn check(&mut self, _op: &str) {}
+}
+
+impl<T, C: Container<T>> Container<T> for Checked<C> {
+    fn len(&mut self) -> usize {
+        self.inner.len()
+    }
+
+    fn contains(&mut self, x: &T) -> bool {
+        self.inner.contains(x)
+    }
+
+    fn is_empty(&mut self) -> bool {
+        self.inner.is_empty()
+    }
+
+    fn insert(&mut self, elt: T) {
+        self.inner.insert(elt);
+        self.check("insert");
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+        self.check("clear");
+    }
+
+    fn remove(&mut self, elt: T) -> Option<T> {
+        let result = self.inner.remove(elt);
+        self.check("remove");
+        result
+    }
+}
+
+impl<T, C: Container<T> + Stack<T>> Stack<T> for Checked<C> {
+    fn push(&mut self, elt: T) {
+        self.inner.push(elt);
+        self.check("push");
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        let result = self.inner.pop();
+        self.check("pop");
+        result
+    }
+}
+
+impl<T, C: Container<T> + RandomAccess<T>> RandomAccess<T> for Checked<C> {
+    fn first(&mut self) -> Option<&T> {
+        self.inner.first()
+    }
+
+    fn last(&mut self) -> Option<&T> {
+        self.inner.last()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<&T> {
+        self.inner.nth(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+    use crate::contracts::{Checked};
+    use crate::traits::{Container, Stack, ModelAbstraction};
+
+    // the predicates generated for unique and lifo
+    fn unique(c: &mut Vec<u32>) -> bool {
+        let l: Vec<_> = ModelAbstraction::abstraction(&*c).iter().map(|x| *x).collect();
+        l.iter().all(|e1| l.iter().filter(|y2| *y2 == e1).count() == 1)
+    }
+
+    fn lifo(c: &mut Vec<u32>) -> bool {
+        let l: Vec<_> = ModelAbstraction::abstraction(&*c).iter().map(|x| *x).collect();
+        l.iter().all(|e1| Stack::pop({ let d2 = &mut *c; Stack::push(d2, *e1); d2 }).as_ref() == Some(e1))
+    }
+
+    #[test]
+    fn test_checked() {
+        let mut c = Checked::new(Vec::<u32>::new(), "StackCon", "std::vec::Vec", || vec![("lifo", lifo)]);
+        for x in 0..10 {
+            c.push(x);
+        }
+        assert_eq!(c.pop(), Some(9));
+        assert_eq!(c.len(), 9);
+        let violated = panic::catch_unwind(|| {
+            let mut c = Checked::new(Vec::<u32>::new(), "UniqueCon", "std::vec::Vec", || vec![("lifo", lifo), ("unique", unique)]);
+            c.insert(1);
+            c.insert(1);
+        });
+        let message = violated.unwrap_err().downcast::<String>().unwrap();
+        assert_eq!(*message, "UniqueCon: property unique violated by std::vec::Vec after insert");
+    }
+}
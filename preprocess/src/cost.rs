@@ -0,0 +1,242 @@
+// Ranking the library structs matching a container type by the asymptotic
+// costs of their operations, declared in the library files with
+// /*COST* O(log n) *ENDCOST*/ before the /*LIBSPEC* of an operation.
+// A struct switching between representations declares the cost in each of
+// them, e.g. /*COST* O(n) | O(log n) *ENDCOST*/, in the same order for every
+// operation, and is costed in the representation cheapest for the workload.
+use std::collections::BTreeMap;
+
+type ErrorMessage = String;
+type OpName = String;
+type StructName = String;
+
+// The size at which asymptotic costs are compared
+pub const NOMINAL_SIZE: f64 = 1000.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Cost {
+    Const,
+    Log,
+    Linear,
+    LinearLog,
+    Quadratic,
+}
+
+impl Cost {
+    pub fn parse(s: &str) -> Result<Cost, ErrorMessage> {
+        let normalised = s.split_whitespace().collect::<Vec<&str>>().join(" ");
+        match normalised.as_str() {
+            "O(1)" => Ok(Cost::Const),
+            "O(log n)" => Ok(Cost::Log),
+            "O(n)" => Ok(Cost::Linear),
+            "O(n log n)" => Ok(Cost::LinearLog),
+            "O(n^2)" => Ok(Cost::Quadratic),
+            _ => Err("Error, unknown cost: ".to_string() + s + ", expected O(1), O(log n), O(n), O(n log n) or O(n^2)")
+        }
+    }
+
+    // The estimated cost of one operation on a container of n elements
+    pub fn eval(&self, n: f64) -> f64 {
+        let log = n.max(2.0).log2();
+        match self {
+            Cost::Const => 1.0,
+            Cost::Log => log,
+            Cost::Linear => n,
+            Cost::LinearLog => n * log,
+            Cost::Quadratic => n * n,
+        }
+    }
+}
+
+impl std::fmt::Display for Cost {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cost::Const => write!(f, "O(1)"),
+            Cost::Log => write!(f, "O(log n)"),
+            Cost::Linear => write!(f, "O(n)"),
+            Cost::LinearLog => write!(f, "O(n log n)"),
+            Cost::Quadratic => write!(f, "O(n^2)"),
+        }
+    }
+}
+
+// The costs of an operation in each representation of a struct, as declared by
+// /*COST* O(n) | O(log n) *ENDCOST*/; most structs have one
+pub type ReprCosts = Vec<Cost>;
+
+pub fn parse_repr_costs(s: &str) -> Result<ReprCosts, ErrorMessage> {
+    s.split('|').map(|c| Cost::parse(c.trim())).collect()
+}
+
+pub fn fmt_repr_costs(costs: &ReprCosts) -> String {
+    costs.iter().map(|c| c.to_string()).collect::<Vec<String>>().join(" | ")
+}
+
+// The costs of the operations of a struct, None if not declared
+pub type OpCosts = BTreeMap<OpName, Option<ReprCosts>>;
+
+// The number of representations of a struct
+fn reprs(costs: &OpCosts) -> usize {
+    costs.values().flatten().map(|c| c.len()).max().unwrap_or(1)
+}
+
+// The cost of an operation in a representation. An operation with one cost costs the
+// same in every representation, and one without a declared cost is assumed to be linear.
+fn op_cost(costs: &OpCosts, op: &str, repr: usize) -> Cost {
+    match costs.get(op).cloned().flatten() {
+        Some(c) if !c.is_empty() => c[repr.min(c.len() - 1)],
+        _ => Cost::Linear
+    }
+}
+
+fn fmt_costs(costs: &OpCosts, weights: &[(OpName, u64)]) -> String {
+    weights.iter()
+        .map(|(op, _)| match costs.get(op).cloned().flatten() {
+            Some(c) => format!("{} {}", op, fmt_repr_costs(&c)),
+            None => format!("{} {} (assumed)", op, Cost::Linear)
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// The estimated cost of the weighted operations, in the cheapest representation
+fn score(costs: &OpCosts, weights: &[(OpName, u64)], n: f64) -> f64 {
+    (0..reprs(costs))
+        .map(|repr| weights.iter().map(|(op, w)| *w as f64 * op_cost(costs, op, repr).eval(n)).sum())
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn is_std(name: &str) -> bool {
+    name.starts_with("std::")
+}
+
+// Rank the candidates, cheapest first, by the costs of the operations weighted by their
+// frequencies, every operation of the candidates being equally frequent if none are given.
+// The operations of the frequencies are those of the interfaces, checked by the type checker.
+// A struct with several representations is costed in the cheapest for the workload. Of
+// candidates of exactly equal cost, the structs of std come first, then by name, so that a
+// library struct is chosen over a std struct only if it is cheaper. Gives the ranking and
+// why the first was chosen.
+pub fn rank(candidates: &[(StructName, OpCosts)], freqs: &[(OpName, u64)], n: f64) -> (Vec<StructName>, String) {
+    let weights: Vec<(OpName, u64)> = if freqs.is_empty() {
+        let mut ops: Vec<OpName> = candidates.iter().flat_map(|(_, costs)| costs.keys().cloned()).collect();
+        ops.sort();
+        ops.dedup();
+        ops.into_iter().map(|op| (op, 1)).collect()
+    } else {
+        freqs.to_vec()
+    };
+    let mut scores: Vec<(&StructName, &OpCosts, f64)> = candidates.iter()
+        .map(|(name, costs)| (name, costs, score(costs, &weights, n)))
+        .collect();
+    scores.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap()
+        .then_with(|| is_std(b.0).cmp(&is_std(a.0)))
+        .then_with(|| a.0.cmp(b.0)));
+    let ranking = scores.iter().map(|(name, _, _)| name.to_string()).collect();
+    let reason = match scores.as_slice() {
+        [] => String::new(),
+        [(name, costs, _)] => format!("{} is the only matching struct ({})", name, fmt_costs(costs, &weights)),
+        [(name, costs, score), (next, next_costs, next_score), ..] => {
+            let freq = if freqs.is_empty() {
+                "equally frequent operations".to_string()
+            } else {
+                weights.iter().map(|(op, w)| format!("{} x{}", op, w)).collect::<Vec<String>>().join(", ")
+            };
+            let verdict = if score == next_score { "ties with" } else { "is cheaper than" };
+            let tie_break = if score != next_score {
+                ""
+            } else if is_std(name) && !is_std(next) {
+                ", and is chosen as a std struct"
+            } else {
+                ", and is chosen by name"
+            };
+            format!("{} ({}, estimated cost {:.0}) {} {} ({}, estimated cost {:.0}) for {} at n = {}{}",
+                name, fmt_costs(costs, &weights), score, verdict, next, fmt_costs(next_costs, &weights), next_score, freq, n, tie_break)
+        }
+    };
+    (ranking, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cost::{Cost, OpCosts, rank, parse_repr_costs};
+    use crate::lib_spec_processor::{read_lib_file};
+
+    fn costs(ops: &[(&str, Option<Cost>)]) -> OpCosts {
+        ops.iter().map(|(op, c)| (op.to_string(), c.map(|c| vec![c]))).collect()
+    }
+
+    // The costs of the operations of all the interfaces declared in a library file
+    fn lib_costs(filename: &str) -> (String, OpCosts) {
+        let (_, name, _, _, _, _, _, costs, _) = read_lib_file(filename.to_string()).unwrap();
+        (name, costs.values().flat_map(|c| c.clone()).collect())
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Cost::parse("O(n  log n)"), Ok(Cost::LinearLog));
+        assert_eq!(Cost::parse("O(1)").unwrap().to_string(), "O(1)");
+        assert!(Cost::parse("O(2^n)").is_err());
+        assert_eq!(parse_repr_costs("O(n) | O(log n)"), Ok(vec![Cost::Linear, Cost::Log]));
+        assert!(parse_repr_costs("O(n) |").is_err());
+    }
+
+    #[test]
+    fn test_rank_by_frequency() {
+        let candidates = vec![
+            ("LazyUniqueVec".to_string(), costs(&[("insert", Some(Cost::Const)), ("contains", Some(Cost::LinearLog))])),
+            ("BTreeSet".to_string(), costs(&[("insert", Some(Cost::Log)), ("contains", Some(Cost::Log))])),
+        ];
+        let (ranking, reason) = rank(&candidates, &[], 1000.0);
+        assert_eq!(ranking, vec!["BTreeSet", "LazyUniqueVec"]);
+        assert!(reason.starts_with("BTreeSet (contains O(log n), insert O(log n)"));
+        let (ranking, _) = rank(&candidates, &[("insert".to_string(), 100000), ("contains".to_string(), 1)], 1000.0);
+        assert_eq!(ranking, vec!["LazyUniqueVec", "BTreeSet"]);
+    }
+
+    #[test]
+    fn test_rank_ties() {
+        let candidates = vec![
+            ("A".to_string(), costs(&[("len", None)])),
+            ("B".to_string(), costs(&[("len", Some(Cost::Linear))])),
+        ];
+        let (ranking, reason) = rank(&candidates, &[], 1000.0);
+        assert_eq!(ranking, vec!["A", "B"]);
+        assert!(reason.contains("len O(n) (assumed)") && reason.contains("ties with"));
+        assert!(reason.ends_with("chosen by name"));
+        let candidates = vec![
+            ("preprocess::library::adaptive_set::AdaptiveSet".to_string(), costs(&[("nth", Some(Cost::Linear))])),
+            ("std::collections::BTreeSet".to_string(), costs(&[("nth", Some(Cost::Linear))])),
+        ];
+        let (ranking, reason) = rank(&candidates, &[], 1000.0);
+        assert_eq!(ranking, vec!["std::collections::BTreeSet", "preprocess::library::adaptive_set::AdaptiveSet"]);
+        assert!(reason.ends_with("chosen as a std struct"), "{}", reason);
+    }
+
+    #[test]
+    fn test_rank_by_size() {
+        let candidates = vec![
+            ("Vec".to_string(), costs(&[("insert", Some(Cost::Const)), ("contains", Some(Cost::Linear))])),
+            ("BTreeSet".to_string(), costs(&[("insert", Some(Cost::Log)), ("contains", Some(Cost::Log))])),
+        ];
+        let freqs = vec![("insert".to_string(), 100), ("contains".to_string(), 1)];
+        assert_eq!(rank(&candidates, &freqs, 16.0).0, vec!["Vec", "BTreeSet"]);
+        let (ranking, reason) = rank(&candidates, &freqs, 1000000.0);
+        assert_eq!(ranking, vec!["BTreeSet", "Vec"]);
+        assert!(reason.ends_with("for insert x100, contains x1 at n = 1000000"));
+    }
+
+    #[test]
+    fn test_rank_adaptive() {
+        let candidates = vec![lib_costs("./src/library/treeset.rs"), lib_costs("./src/library/adaptive_set.rs")];
+        let freqs = |insert, nth| vec![("insert".to_string(), insert), ("nth".to_string(), nth)];
+        // mostly indexed: the adaptive set is a sorted vector, with O(1) nth
+        let (ranking, reason) = rank(&candidates, &freqs(10, 90), 100000.0);
+        assert_eq!(ranking[0], "preprocess::library::adaptive_set::AdaptiveSet");
+        assert!(reason.contains("nth O(1) | O(n)") && reason.contains("is cheaper than std::collections::BTreeSet"), "{}", reason);
+        // only updated: it is a B-tree, which ties with BTreeSet
+        let (ranking, reason) = rank(&candidates, &freqs(100, 0), 100000.0);
+        assert_eq!(ranking[0], "std::collections::BTreeSet");
+        assert!(reason.ends_with("chosen as a std struct"), "{}", reason);
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::collections::hash_map::Iter;
 
-use crate::parser::{Id};
+use crate::parser::{Id, Workload, Dispatch};
 
 pub type Description = String;
 type ElemTypeName = String;
@@ -12,7 +12,9 @@ type BoundName = String;
 pub enum Tag {
     Prop(Box<Description>), // analysis of a property
     Bound((ConName, ElemTypeName), Box<Vec<Description>>),
-    Con(ElemTypeName, BoundName, Box<Vec<Tag>>) // analysis of a container type with refinements
+    Con(ElemTypeName, BoundName, Box<Vec<Tag>>), // analysis of a container type with refinements
+    Workload(Box<Workload>), // the expected usage of a container type
+    Dispatch(Dispatch) // the dispatch declared for a container type
 }
 
 impl Tag {
@@ -30,6 +32,14 @@ impl Tag {
         }
     }
 
+    pub fn is_workload_tag(&self) -> bool {
+        matches!(self, Tag::Workload(_))
+    }
+
+    pub fn is_dispatch_tag(&self) -> bool {
+        matches!(self, Tag::Dispatch(_))
+    }
+
     pub fn extract_prop_desc(&self) -> Description {
         match self {
             Tag::Prop(desc) => desc.to_string(),
@@ -43,6 +53,20 @@ impl Tag {
             _ => Vec::new()
         }
     }
+
+    pub fn extract_workload(&self) -> Workload {
+        match self {
+            Tag::Workload(workload) => *workload.clone(),
+            _ => Workload::default()
+        }
+    }
+
+    pub fn extract_dispatch(&self) -> Option<Dispatch> {
+        match self {
+            Tag::Dispatch(dispatch) => Some(*dispatch),
+            _ => None
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -0,0 +1,451 @@
+// Stateful differential testing of the library structs against their specifications.
+// The /*LIBSPEC* blocks of a library file are run as an executable model of its struct:
+// random sequences of the specified operations are applied to the struct and to the model
+// in lockstep, comparing their results and states, and checking after every step that the
+// state of the struct satisfies the precondition of every operation.
+use proptest::prelude::*;
+use proptest::collection::vec;
+use proptest::test_runner::{Config, TestCaseError, TestRunner};
+use std::collections::{BTreeSet, HashSet, LinkedList};
+use std::ops::Range;
+
+use crate::traits::{Container, Stack, RandomAccess, ModelAbstraction};
+use crate::lib_spec::{Model, OpSpec};
+use crate::lib_spec::racket::{emit_def};
+use crate::lib_spec_processor::{read_interface_specs};
+use crate::native_matching::interp::{Interp};
+use crate::native_matching::value::{Value};
+use crate::library::eager_sorted_vector::{EagerSortedVec};
+use crate::library::eager_unique_vector::{EagerUniqueVec};
+use crate::library::lazy_sorted_vector::{LazySortedVec};
+use crate::library::lazy_unique_vector::{LazyUniqueVec};
+use crate::library::adaptive_set::{AdaptiveSet};
+use crate::contracts::{Predicate};
+use crate::proptest::strategies::{bound_ops};
+use crate::types::{bound_op_names};
+
+type ErrorMessage = String;
+
+// Few elements, so that sequences insert and remove the same elements
+const ELEMS: Range<i64> = -4..12;
+const INDICES: Range<usize> = 0..8;
+const STEPS: usize = 32; // the maximum length of a sequence
+
+// An operation of the interfaces of the library, with its argument
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Op {
+    Len,
+    Contains(i64),
+    IsEmpty,
+    Insert(i64),
+    Clear,
+    Remove(i64),
+    Push(i64),
+    Pop,
+    First,
+    Last,
+    Nth(usize),
+}
+
+impl Op {
+    // The operation of the specifications of this name
+    fn from_name(name: &str, x: i64, n: usize) -> Option<Op> {
+        match name {
+            "len" => Some(Op::Len),
+            "contains" => Some(Op::Contains(x)),
+            "is-empty" => Some(Op::IsEmpty),
+            "insert" => Some(Op::Insert(x)),
+            "clear" => Some(Op::Clear),
+            "remove" => Some(Op::Remove(x)),
+            "push" => Some(Op::Push(x)),
+            "pop" => Some(Op::Pop),
+            "first" => Some(Op::First),
+            "last" => Some(Op::Last),
+            "nth" => Some(Op::Nth(n)),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Op::Len => "len",
+            Op::Contains(_) => "contains",
+            Op::IsEmpty => "is-empty",
+            Op::Insert(_) => "insert",
+            Op::Clear => "clear",
+            Op::Remove(_) => "remove",
+            Op::Push(_) => "push",
+            Op::Pop => "pop",
+            Op::First => "first",
+            Op::Last => "last",
+            Op::Nth(_) => "nth",
+        }
+    }
+
+    fn arg(&self) -> Option<Value> {
+        match self {
+            Op::Contains(x) | Op::Insert(x) | Op::Remove(x) | Op::Push(x) => Some(Value::Int(*x)),
+            Op::Nth(n) => Some(Value::Int(*n as i64)),
+            _ => None
+        }
+    }
+
+    // The specification gives the resulting list paired with a result, rather than the list
+    fn has_result(&self) -> bool {
+        !matches!(self, Op::Insert(_) | Op::Clear | Op::Push(_))
+    }
+}
+
+impl std::fmt::Display for Op {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.arg() {
+            Some(x) => write!(f, "({} {})", self.name(), x),
+            None => write!(f, "({})", self.name()),
+        }
+    }
+}
+
+fn opt_value(x: Option<&i64>) -> Value {
+    x.map_or(Value::null(), |x| Value::Int(*x))
+}
+
+// The result of an operation of Container as a value of the model, None for the other interfaces
+pub fn apply_container<C: Container<i64> + ?Sized>(c: &mut C, op: &Op) -> Option<Value> {
+    match op {
+        Op::Len => Some(Value::Int(c.len() as i64)),
+        Op::Contains(x) => Some(Value::Bool(c.contains(x))),
+        Op::IsEmpty => Some(Value::Bool(c.is_empty())),
+        Op::Insert(x) => {
+            c.insert(*x);
+            Some(Value::null())
+        },
+        Op::Clear => {
+            c.clear();
+            Some(Value::null())
+        },
+        Op::Remove(x) => Some(opt_value(c.remove(*x).as_ref())),
+        _ => None
+    }
+}
+
+pub fn apply_stack<C: Stack<i64> + ?Sized>(c: &mut C, op: &Op) -> Option<Value> {
+    match op {
+        Op::Push(x) => {
+            c.push(*x);
+            Some(Value::null())
+        },
+        Op::Pop => Some(opt_value(c.pop().as_ref())),
+        _ => None
+    }
+}
+
+pub fn apply_random_access<C: RandomAccess<i64> + ?Sized>(c: &mut C, op: &Op) -> Option<Value> {
+    match op {
+        Op::First => Some(opt_value(c.first())),
+        Op::Last => Some(opt_value(c.last())),
+        Op::Nth(n) => Some(opt_value(c.nth(*n))),
+        _ => None
+    }
+}
+
+// A library struct under test, compared with the model through its abstraction
+pub trait Subject: ModelAbstraction<i64> {
+    // The result of the operation, None if the struct does not implement its interface
+    fn apply(&mut self, op: &Op) -> Option<Value>;
+}
+
+// The executable model of a library struct, given by the specifications of its library file
+pub struct LibModel {
+    interp: Interp,
+    model: Model,
+    ops: Vec<OpSpec>, // of all interfaces
+}
+
+impl LibModel {
+    pub fn from_file(filename: &str) -> Result<LibModel, ErrorMessage> {
+        let (model, abstraction, interfaces) = read_interface_specs(filename)?;
+        if abstraction.is_none() {
+            return Err("Error, no abstraction declared in ".to_string() + filename);
+        }
+        let mut interp = Interp::new(ELEMS.collect());
+        let ops: Vec<OpSpec> = interfaces.into_iter().flat_map(|i| i.ops).collect();
+        for def in ops.iter().flat_map(|s| s.defs()) {
+            interp.load_lib_spec(&emit_def(def))?;
+        }
+        Ok(LibModel { interp, model, ops })
+    }
+
+    pub fn op_names(&self) -> Vec<String> {
+        self.ops.iter().map(|s| s.name.to_string()).collect()
+    }
+
+    fn spec(&self, op: &Op) -> Result<&OpSpec, ErrorMessage> {
+        self.ops.iter().find(|s| s.name == op.name())
+            .ok_or_else(|| "Error, no specification of ".to_string() + op.name())
+    }
+
+    // The state after the operation, with its result if it gives one
+    pub fn step(&self, op: &Op, xs: &Value) -> Result<(Value, Option<Value>), ErrorMessage> {
+        let f = self.interp.lookup(&Vec::new(), &self.spec(op)?.op.name)?;
+        let mut args = vec![xs.clone()];
+        args.extend(op.arg());
+        let result = self.interp.apply(&f, args)?;
+        if op.has_result() {
+            Ok((result.car()?, Some(result.cdr()?)))
+        } else {
+            Ok((result, None))
+        }
+    }
+
+    // The first operation whose precondition does not hold in the state
+    pub fn violated_pre(&self, xs: &Value) -> Result<Option<String>, ErrorMessage> {
+        for spec in self.ops.iter() {
+            let pre = self.interp.lookup(&Vec::new(), &spec.pre.name)?;
+            if !self.interp.apply(&pre, vec![xs.clone()])?.as_bool() {
+                return Ok(Some(spec.pre.name.to_string()));
+            }
+        }
+        Ok(None)
+    }
+
+    // The lists of an unordered model are equal up to their order
+    pub fn same_state(&self, xs: &[i64], ys: &Value) -> Result<bool, ErrorMessage> {
+        let mut ys = ys.as_list()?.iter().map(|y| y.as_int()).collect::<Result<Vec<i64>, ErrorMessage>>()?;
+        let mut xs = xs.to_vec();
+        if self.model.is_unordered() {
+            xs.sort();
+            ys.sort();
+        }
+        Ok(xs == ys)
+    }
+}
+
+// The operations of these names, on few elements
+pub fn op_strategy(names: Vec<String>) -> impl Strategy<Value = Op> {
+    (0..names.len(), ELEMS, INDICES)
+        .prop_map(move |(i, x, n)| Op::from_name(&names[i], x, n).unwrap_or(Op::Len))
+}
+
+fn run_ops<S: Subject>(model: &LibModel, s: &mut S, ops: &[Op]) -> Result<(), ErrorMessage> {
+    let mut xs = Value::null();
+    for (i, op) in ops.iter().enumerate() {
+        let at = || ops[..=i].iter().map(|o| o.to_string()).collect::<Vec<String>>().join(" ");
+        let (next, expected) = model.step(op, &xs)?;
+        let actual = s.apply(op).ok_or_else(|| "Error, the struct does not implement ".to_string() + op.name())?;
+        if let Some(e) = expected {
+            if e != actual {
+                return Err(format!("{} gives {}, the model {}", at(), actual, e));
+            }
+        }
+        xs = next;
+        let state: Vec<i64> = s.abstraction().iter().map(|x| *x).collect();
+        if !model.same_state(&state, &xs)? {
+            return Err(format!("{} leaves {:?}, the model {}", at(), state, xs));
+        }
+        let list = Value::List(state.iter().map(|x| Value::Int(*x)).collect());
+        if let Some(pre) = model.violated_pre(&list)? {
+            return Err(format!("{} leaves {:?}, violating {}", at(), state, pre));
+        }
+    }
+    Ok(())
+}
+
+// Check the struct against the specifications of its library file on random operation
+// sequences, giving the shortest failing sequence found
+pub fn check_lib_struct<S: Subject>(filename: &str, new: impl Fn() -> S, cases: u32) -> Result<(), ErrorMessage> {
+    let model = LibModel::from_file(filename)?;
+    let mut runner = TestRunner::new(Config { cases, ..Config::default() });
+    runner.run(&vec(op_strategy(model.op_names()), 0..STEPS), |ops| {
+        run_ops(&model, &mut new(), &ops).map_err(TestCaseError::fail)
+    }).map_err(|e| e.to_string())
+}
+
+// Check that a container type keeps its properties through random sequences of the operations
+// of its bounds. apply calls them through the bounds on the container type, as generated with its
+// wrappers, and apply_impl on its chosen struct in lockstep: their results are compared, and the
+// properties, translated into predicates over the chosen struct, are checked on it after each
+// step. Run by the tests generated for the container types.
+pub fn check_con_type<C, S>(bounds: &[&str], new: impl Fn() -> C, apply: impl Fn(&mut C, &Op) -> Option<Value>, new_impl: impl Fn() -> S, apply_impl: impl Fn(&mut S, &Op) -> Option<Value>, props: Vec<(&str, Predicate<S>)>, cases: u32) -> Result<(), ErrorMessage> {
+    if bounds.is_empty() {
+        return Err("Error, a container type without bounds has no operations to run".to_string());
+    }
+    if let Some(b) = bounds.iter().find(|b| bound_op_names(b).is_none()) {
+        return Err("Error, unable to run the operations of the bound ".to_string() + b);
+    }
+    let mut runner = TestRunner::new(Config { cases, ..Config::default() });
+    runner.run(&bound_ops(bounds, 0..STEPS), |ops| {
+        let (mut c, mut s) = (new(), new_impl());
+        for (i, op) in ops.iter().enumerate() {
+            let at = || ops[..=i].iter().map(|o| o.to_string()).collect::<Vec<String>>().join(" ");
+            let not_implemented = || TestCaseError::fail("Error, the container type does not implement ".to_string() + op.name());
+            let actual = apply(&mut c, op).ok_or_else(not_implemented)?;
+            let expected = apply_impl(&mut s, op).ok_or_else(not_implemented)?;
+            if actual != expected {
+                return Err(TestCaseError::fail(format!("{} gives {}, the chosen struct {}", at(), actual, expected)));
+            }
+            if let Some((name, _)) = props.iter().find(|(_, holds)| !holds(&mut s)) {
+                return Err(TestCaseError::fail(format!("{} violates {}", at(), name)));
+            }
+        }
+        Ok(())
+    }).map_err(|e| e.to_string())
+}
+
+impl Subject for Vec<i64> {
+    fn apply(&mut self, op: &Op) -> Option<Value> {
+        apply_container(self, op).or_else(|| apply_stack(self, op)).or_else(|| apply_random_access(self, op))
+    }
+}
+
+impl Subject for LinkedList<i64> {
+    fn apply(&mut self, op: &Op) -> Option<Value> {
+        apply_container(self, op).or_else(|| apply_stack(self, op)).or_else(|| apply_random_access(self, op))
+    }
+}
+
+impl Subject for BTreeSet<i64> {
+    fn apply(&mut self, op: &Op) -> Option<Value> {
+        apply_container(self, op).or_else(|| apply_random_access(self, op))
+    }
+}
+
+impl Subject for HashSet<i64> {
+    fn apply(&mut self, op: &Op) -> Option<Value> {
+        apply_container(self, op)
+    }
+}
+
+impl Subject for EagerSortedVec<i64> {
+    fn apply(&mut self, op: &Op) -> Option<Value> {
+        apply_container(self, op).or_else(|| apply_random_access(self, op))
+    }
+}
+
+impl Subject for EagerUniqueVec<i64> {
+    fn apply(&mut self, op: &Op) -> Option<Value> {
+        apply_container(self, op).or_else(|| apply_random_access(self, op))
+    }
+}
+
+impl Subject for LazySortedVec<i64> {
+    fn apply(&mut self, op: &Op) -> Option<Value> {
+        apply_container(self, op).or_else(|| apply_random_access(self, op))
+    }
+}
+
+impl Subject for LazyUniqueVec<i64> {
+    fn apply(&mut self, op: &Op) -> Option<Value> {
+        apply_container(self, op).or_else(|| apply_random_access(self, op))
+    }
+}
+
+impl Subject for AdaptiveSet<i64> {
+    fn apply(&mut self, op: &Op) -> Option<Value> {
+        apply_container(self, op).or_else(|| apply_random_access(self, op))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::collections::{BTreeSet, HashSet, LinkedList};
+    use im::conslist::{ConsList};
+    use crate::differential::{Op, Subject, check_lib_struct, check_con_type, apply_container, apply_random_access};
+    use crate::traits::{Container, RandomAccess, ModelAbstraction};
+    use crate::native_matching::value::{Value};
+    use crate::library::eager_sorted_vector::{EagerSortedVec};
+    use crate::library::eager_unique_vector::{EagerUniqueVec};
+    use crate::library::lazy_sorted_vector::{LazySortedVec};
+    use crate::library::lazy_unique_vector::{LazyUniqueVec};
+    use crate::library::adaptive_set::{AdaptiveSet};
+
+    type ErrorMessage = String;
+
+    const CASES: u32 = 64;
+
+    const LIBDIR: &str = "./src/library/";
+
+    // The differential check of the struct of each library file, None for a file not listed
+    fn check_lib_file(file: &str) -> Option<Result<(), ErrorMessage>> {
+        let filename = LIBDIR.to_string() + file;
+        Some(match file {
+            "vector.rs" => check_lib_struct(&filename, Vec::<i64>::new, CASES),
+            "list.rs" => check_lib_struct(&filename, LinkedList::<i64>::new, CASES),
+            "treeset.rs" => check_lib_struct(&filename, BTreeSet::<i64>::new, CASES),
+            "hashset.rs" => check_lib_struct(&filename, HashSet::<i64>::new, CASES),
+            "eager_sorted_vector.rs" => check_lib_struct(&filename, EagerSortedVec::<i64>::new, CASES),
+            "lazy_sorted_vector.rs" => check_lib_struct(&filename, LazySortedVec::<i64>::new, CASES),
+            "eager_unique_vector.rs" => check_lib_struct(&filename, EagerUniqueVec::<i64>::new, CASES),
+            "lazy_unique_vector.rs" => check_lib_struct(&filename, LazyUniqueVec::<i64>::new, CASES),
+            // low thresholds, so that the sequences migrate it
+            "adaptive_set.rs" => check_lib_struct(&filename, || AdaptiveSet::<i64>::with_thresholds(2, 4, 2), CASES),
+            _ => return None
+        })
+    }
+
+    // Every library file with a specification is checked, so a new library struct
+    // fails here until it implements Subject and is listed in check_lib_file
+    #[test]
+    fn test_lib_structs() {
+        let mut files: Vec<String> = fs::read_dir(LIBDIR).unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|file| fs::read_to_string(LIBDIR.to_string() + file).unwrap().contains("/*LIBSPEC*"))
+            .collect();
+        files.sort();
+        assert!(!files.is_empty());
+        for file in files {
+            match check_lib_file(&file) {
+                Some(result) => assert_eq!(result, Ok(()), "{}", file),
+                None => panic!("No differential test of the struct of {}: implement Subject for it and add it to check_lib_file", file)
+            }
+        }
+    }
+
+    // A vector whose insert drops the element, against the specification of Vec
+    struct Lossy(Vec<i64>);
+
+    impl Subject for Lossy {
+        fn apply(&mut self, op: &Op) -> Option<Value> {
+            match op {
+                Op::Insert(x) if *x == 3 => Some(Value::null()),
+                _ => self.0.apply(op)
+            }
+        }
+    }
+
+    impl ModelAbstraction<i64> for Lossy {
+        fn abstraction(&self) -> ConsList<i64> {
+            self.0.abstraction()
+        }
+    }
+
+    #[test]
+    fn test_drift_is_found() {
+        let e = check_lib_struct("./src/library/vector.rs", || Lossy(Vec::new()), 256).unwrap_err();
+        assert!(e.contains("(insert 3)"), "{}", e);
+    }
+
+    // the predicate generated for ascending
+    fn ascending<C: ModelAbstraction<i64>>(c: &mut C) -> bool {
+        let l: Vec<_> = c.abstraction().iter().map(|x| *x).collect();
+        l.windows(2).all(|w1| { let (e2, e3) = (&w1[0], &w1[1]); e2 <= e3 })
+    }
+
+    // the trait of a generated container type with these bounds
+    trait SortedSetTrait: Container<i64> + RandomAccess<i64> {}
+    impl SortedSetTrait for BTreeSet<i64> {}
+
+    #[test]
+    fn test_check_con_type() {
+        let apply = |c: &mut BTreeSet<i64>, op: &Op| apply_container(c, op).or_else(|| apply_random_access(c, op));
+        let new_boxed = || Box::new(BTreeSet::new()) as Box<dyn SortedSetTrait>;
+        let apply_boxed = |c: &mut Box<dyn SortedSetTrait>, op: &Op| apply_container(&mut **c, op).or_else(|| apply_random_access(&mut **c, op));
+        assert_eq!(check_con_type(&["Container", "RandomAccess"], new_boxed, apply_boxed, BTreeSet::new, apply, vec![("ascending", ascending)], CASES), Ok(()));
+        // a container type which does not forward to its chosen struct
+        let e = check_con_type(&["Container"], Vec::new, apply_container, BTreeSet::new, apply_container, vec![], CASES).unwrap_err();
+        assert!(e.contains("the chosen struct"), "{}", e);
+        let e = check_con_type(&["Container"], Vec::new, apply_container, Vec::new, apply_container, vec![("ascending", ascending)], CASES).unwrap_err();
+        assert!(e.contains("violates ascending"), "{}", e);
+        assert!(check_con_type(&["Queue"], Vec::new, apply_container, Vec::new, apply_container, vec![("ascending", ascending)], CASES).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+// Errors of processing a source file, carrying the position of the
+// offending declaration or token in the original source where known.
+use std::fmt;
+
+use crate::parser::{Id};
+
+const SPEC: &str = "/*SPEC*";
+const SPECEND: &str = "*ENDSPEC*/";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeclKind {
+    Property,
+    ConType,
+}
+
+impl fmt::Display for DeclKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeclKind::Property => write!(f, "property"),
+            DeclKind::ConType => write!(f, "container type"),
+        }
+    }
+}
+
+// A position in a source file, with the text of its line
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub file: String,
+    pub line: usize,   // from 1
+    pub column: usize, // from 1
+    pub text: String,
+}
+
+// An error of compiling the generated program, with the container type whose code it is in,
+// or the position in the source of the code it is in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompileError {
+    pub message: String,
+    pub con: Option<Id>,
+    pub span: Option<Span>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SpecError {
+    Io(String),
+    Parse { span: Span, expected: Vec<String> },
+    Type { decl: Option<(DeclKind, Id)>, message: String, span: Option<Span> },
+    Analysis { decl: Option<(DeclKind, Id)>, message: String, span: Option<Span> },
+    Library(String), // processing library specifications or matching
+    Compile(Vec<CompileError>), // compiling the generated program
+}
+
+impl SpecError {
+    pub fn type_error(kind: DeclKind, id: &str, message: String) -> SpecError {
+        SpecError::Type { decl: Some((kind, id.to_string())), message, span: None }
+    }
+
+    pub fn analysis_error(kind: DeclKind, id: &str, message: String) -> SpecError {
+        SpecError::Analysis { decl: Some((kind, id.to_string())), message, span: None }
+    }
+
+    pub fn span(&self) -> Option<&Span> {
+        match self {
+            SpecError::Parse { span, .. } => Some(span),
+            SpecError::Type { span, .. } | SpecError::Analysis { span, .. } => span.as_ref(),
+            _ => None
+        }
+    }
+
+    // Locate the declaration the error is about in the source
+    pub fn locate(self, src: &SourceMap) -> SpecError {
+        match self {
+            SpecError::Type { decl: Some((kind, id)), message, span: None } => {
+                let span = src.decl_span(kind, &id);
+                SpecError::Type { decl: Some((kind, id)), message, span }
+            },
+            SpecError::Analysis { decl: Some((kind, id)), message, span: None } => {
+                let span = src.decl_span(kind, &id);
+                SpecError::Analysis { decl: Some((kind, id)), message, span }
+            },
+            e => e
+        }
+    }
+}
+
+fn fmt_decl(f: &mut fmt::Formatter<'_>, decl: &Option<(DeclKind, Id)>) -> fmt::Result {
+    match decl {
+        Some((kind, id)) => write!(f, " in {} {}", kind, id),
+        None => Ok(())
+    }
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecError::Io(message) | SpecError::Library(message) => write!(f, "{}", message)?,
+            SpecError::Parse { expected, .. } => {
+                write!(f, "Error, invalid source code: expected one of {}", expected.join(", "))?
+            },
+            SpecError::Type { decl, message, .. } => {
+                write!(f, "Type error")?;
+                fmt_decl(f, decl)?;
+                write!(f, ": {}", message)?
+            },
+            SpecError::Analysis { decl, message, .. } => {
+                write!(f, "Error")?;
+                fmt_decl(f, decl)?;
+                write!(f, ": {}", message)?
+            },
+            SpecError::Compile(errors) => {
+                write!(f, "Error, the generated code does not compile")?;
+                for e in errors.iter() {
+                    match &e.con {
+                        Some(con) => write!(f, "\nin the code generated for container type {}: {}", con, e.message)?,
+                        None => write!(f, "\n{}", e.message)?
+                    }
+                    if let Some(span) = &e.span {
+                        fmt_span(f, span)?;
+                    }
+                }
+            },
+        }
+        if let Some(span) = self.span() {
+            fmt_span(f, span)?;
+        }
+        Ok(())
+    }
+}
+
+fn fmt_span(f: &mut fmt::Formatter<'_>, span: &Span) -> fmt::Result {
+    let margin = " ".repeat(span.line.to_string().len());
+    write!(f, "\n{}--> {}:{}:{}", margin, span.file, span.line, span.column)?;
+    write!(f, "\n{} |\n{} | {}", margin, span.line, span.text)?;
+    write!(f, "\n{} | {}^", margin, " ".repeat(span.column - 1))
+}
+
+fn is_id_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '?'
+}
+
+// Maps positions in the marked source (see generator::mark_src_blocks) back to the original file
+pub struct SourceMap {
+    file: String,
+    src: String,
+    anchors: Vec<(usize, usize)>, // (offset in the marked source, offset in the original)
+}
+
+impl SourceMap {
+    pub fn new(file: &str, src: &str, anchors: Vec<(usize, usize)>) -> SourceMap {
+        SourceMap {
+            file: file.to_string(),
+            src: src.to_string(),
+            anchors
+        }
+    }
+
+    fn span_at(&self, offset: usize) -> Span {
+        let offset = offset.min(self.src.len());
+        let before = &self.src[..offset];
+        let line_start = before.rfind('\n').map(|n| n + 1).unwrap_or(0);
+        let line_end = self.src[offset..].find('\n').map(|n| n + offset).unwrap_or(self.src.len());
+        Span {
+            file: self.file.to_string(),
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            text: self.src[line_start..line_end].to_string()
+        }
+    }
+
+    // The span of an offset in the marked source
+    pub fn marked_span(&self, offset: usize) -> Span {
+        let original = match self.anchors.iter().rev().find(|(marked, _)| *marked <= offset) {
+            Some((marked, original)) => original + (offset - marked),
+            None => 0
+        };
+        self.span_at(original)
+    }
+
+    // The span of a declaration within the spec blocks
+    pub fn decl_span(&self, kind: DeclKind, id: &str) -> Option<Span> {
+        let keyword = match kind {
+            DeclKind::Property => "property",
+            DeclKind::ConType => "type",
+        };
+        let mut rest = 0;
+        while let Some(start) = self.src[rest..].find(SPEC).map(|n| n + rest) {
+            let end = self.src[start..].find(SPECEND).map(|n| n + start).unwrap_or(self.src.len());
+            let block = &self.src[start..end];
+            for (n, _) in block.match_indices(keyword) {
+                let before_ok = !block[..n].chars().last().map(is_id_char).unwrap_or(false);
+                let after = &block[n + keyword.len()..];
+                let name = after.trim_start();
+                let name_ok = name.len() < after.len() && name.starts_with(id)
+                    && !name[id.len()..].chars().next().map(is_id_char).unwrap_or(false);
+                if before_ok && name_ok {
+                    return Some(self.span_at(start + n));
+                }
+            }
+            rest = end;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{SourceMap, SpecError, DeclKind};
+
+    const SRC: &str = "/*SPEC*\nproperty unique<T> {\n  \\c -> c\n}\ntype UniqueCon<T> = {c impl (Container) | unique c}\n*ENDSPEC*/\nfn main() {}\n";
+
+    #[test]
+    fn test_decl_span() {
+        let map = SourceMap::new("a.rs", SRC, vec![(0, 0)]);
+        let span = map.decl_span(DeclKind::ConType, "UniqueCon").unwrap();
+        assert_eq!((span.line, span.column), (5, 1));
+        assert!(map.decl_span(DeclKind::Property, "uniq").is_none());
+    }
+
+    #[test]
+    fn test_marked_span() {
+        // the marked source starts with the spec block, followed by "/*CODE*/" and the code
+        let code = SRC.find("fn main").unwrap();
+        let marked = code + 8;
+        let map = SourceMap::new("a.rs", SRC, vec![(0, 0), (marked, code)]);
+        let span = map.marked_span(marked + 3);
+        assert_eq!((span.line, span.column, span.text.as_str()), (7, 4, "fn main() {}"));
+    }
+
+    #[test]
+    fn test_display() {
+        let map = SourceMap::new("a.rs", SRC, vec![(0, 0)]);
+        let e = SpecError::type_error(DeclKind::Property, "unique", "types do not unify".to_string()).locate(&map);
+        assert_eq!(e.to_string(), "Type error in property unique: types do not unify\n --> a.rs:2:1\n  |\n2 | property unique<T> {\n  | ^");
+    }
+}
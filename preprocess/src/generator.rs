@@ -1,16 +1,30 @@
+use std::collections::BTreeSet;
 use std::env;
 use std::fs;
 use std::io::{Write, BufReader, BufRead, Error, ErrorKind};
-use indicatif::{ProgressBar, ProgressStyle};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 
-use crate::parser::{Block, Spec, spec};
+use crate::parser::{Prog, Block, Decl, Dispatch, Spec, Workload, spec};
 use crate::type_check::{TypeChecker};
+use crate::error::{SpecError, SourceMap};
 
 use crate::analysis::{Analyser};
 use crate::description::{Tag, Description, InforMap};
 use crate::lib_spec_processor::{process_lib_specs};
-use crate::spec_map::{PropSpecs, MatchSetup, ProvidedOps};
-use crate::run_matching::{LANGDECL, initialise_match_setup, gen_match_script, run_matching, cleanup_script, setup_dirs};
+use crate::spec_map::{PropSpecs, PropTerms, MatchSetup, ProvidedOps, LibSpecs};
+use crate::lib_spec::{Model};
+use crate::run_matching::{LANGDECL, WORKDIR, LIBDIR, PROPDIR, MATCHDIR, SETUPS, initialise_match_setup, job_dir, setup_job, gen_match_script, gen_order_script, run_matching, cleanup_jobs, setup_dirs};
+use crate::native_matching::{MODELSIZE, CHECKERVERSION, check_match};
+use crate::native_matching::counterexample::{Counterexample};
+use crate::match_cache::{CACHEFILE, MatchCache, CacheEntry, MatchResult, cache_key};
+use crate::cost::{OpCosts, NOMINAL_SIZE, rank};
+use crate::profiler::{read_profile};
+use crate::benchmark::{gen_benchmark, summarise, snake_case};
+use crate::compile_check::{Origin, Origins, check_output};
+use crate::predicates::{gen_predicate};
 
 const CODEGEN: &str = "/*CODEGEN*/\n";
 const CODEGENEND: &str = "/*ENDCODEGEN*/\n";
@@ -22,15 +36,80 @@ const SPEC: &str = "/*SPEC*";
 const SPECEND: &str = "*ENDSPEC*/";
 
 const LIB: &str = "./src/library/";
-const MATCHSCRIPT: &str = "./racket_specs/gen_match/match-script.rkt";
 
 const IMPORT: &str = "use preprocess::traits::container_constructor::ContainerConstructor;\n";
 const TRAITCRATE: &str = "preprocess::traits::";
-
-const OPS: &str = "./racket_specs/gen_lib_spec/ops.rkt";
+const TESTELEM: &str = "i64"; // the element type of the generated tests
+const TESTCASES: u32 = 128;  // the number of operation sequences run by each generated test
 
 type ErrorMessage = String;
 
+// Parts of the generated program, each with where it comes from
+type Parts = Vec<(Origin, String)>;
+
+// Each container type with its matching library structs, cheapest first, and why the first was chosen
+pub type ConMatches = Vec<(String, Vec<String>, String)>;
+
+// The backend deciding whether a library struct matches a property
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Matcher {
+    Rosette, // run the generated match scripts with racket
+    Native,  // the in-process bounded model checker
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchOptions {
+    pub matcher: Matcher,
+    pub model_size: usize, // the length bound of the list model
+    pub jobs: usize,       // the number of checks run in parallel
+    pub lib_dir: String,   // the directory of the library files with specifications
+    pub work_dir: String,  // the directory of the generated specifications and the match cache
+    pub record: bool,      // wrap the chosen structs in a profiler::Recorder
+    pub contracts: bool,   // wrap the chosen structs in a contracts::Checked
+    pub tests: bool,       // generate a property-based test module for each container type
+    pub profile_dir: Option<String>, // select the structs by the profiles recorded there
+    pub dispatch: Dispatch, // of the container types not declaring their own
+    pub quiet: bool,        // print no progress, e.g. in a build script or a macro
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        MatchOptions {
+            matcher: Matcher::Native,
+            model_size: MODELSIZE,
+            jobs: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+            lib_dir: LIB.to_string(),
+            work_dir: WORKDIR.to_string(),
+            record: false,
+            contracts: false,
+            tests: false,
+            profile_dir: None,
+            dispatch: Dispatch::Dynamic,
+            quiet: false
+        }
+    }
+}
+
+// Why a library struct was rejected for a container type
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatchDiagnostic {
+    pub con: String,
+    pub lib_struct: String,
+    pub property: String,
+    pub bound: String,
+    pub counterexample: Option<Counterexample>, // None if the matcher provides no witness
+}
+
+impl std::fmt::Display for MatchDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} does not satisfy {} as {}", self.con, self.lib_struct, self.property, self.bound)?;
+        match &self.counterexample {
+            Some(c) => write!(f, ", {}", c),
+            None => Ok(())
+        }
+    }
+}
+
 pub fn readfile(filename : String) -> String {
     let contents = fs::read_to_string(filename)
         .expect("Something went wrong reading the file");
@@ -61,27 +140,48 @@ fn process_bound_elem_ty(t: &str, elem_ty: &str) -> String {
     return TRAITCRATE.to_string() + t + "<" + elem_ty + ">";
 }
 
-pub fn process_bound_decl(ctx: &InforMap) -> Result<String, ErrorMessage> {
-    let mut code = String::new();
-    let match_setup = initialise_match_setup();
-    for (id, tag) in ctx.iter() {
+// The dispatch declared for a container type, or else the one of the options
+fn con_dispatch(ctx: &InforMap, con: &str, options: &MatchOptions) -> Dispatch {
+    match ctx.get_id(con.to_string()) {
+        Some(Tag::Con(_, _, tags)) => tags.iter().find(|t| t.is_dispatch_tag()).and_then(|t| t.extract_dispatch()).unwrap_or(options.dispatch),
+        _ => options.dispatch
+    }
+}
+
+pub fn process_bound_decl(ctx: &InforMap, options: &MatchOptions) -> Result<String, ErrorMessage> {
+    Ok(concat_parts(gen_bound_decls(ctx, options)))
+}
+
+// The traits of the container types, each with the container type it is generated for
+fn gen_bound_decls(ctx: &InforMap, options: &MatchOptions) -> Parts {
+    let mut parts = Parts::new();
+    // in the order of their names, so that the generated code does not change between runs
+    let mut tags: Vec<(&String, &Tag)> = ctx.iter().collect();
+    tags.sort_by_key(|(id, _)| *id);
+    for (id, tag) in tags.into_iter() {
         match tag {
             Tag::Bound((c, t), decs) => {
                 let traits = decs.iter().map(|name| process_bound_elem_ty(name, t)).collect::<Vec<String>>().join(" + ");
-                code = code + &gen_trait_code(id, c, t, &traits);
+                parts.push((Origin::Con(c.to_string()), gen_trait_code(id, c, t, &traits, con_dispatch(ctx, c, options), options.contracts)));
             },
             _ => continue
         }
     }
-    Ok(code)
+    parts
+}
+
+fn concat_parts(parts: Parts) -> String {
+    parts.into_iter().map(|(_, code)| code).collect()
 }
 
-pub fn process_con_decl(ctx: &InforMap, prop_specs: &PropSpecs) -> Result<String, ErrorMessage> {
-    let mut code = String::new();
+// The library structs matching each container type declared in the context
+pub fn match_con_decls(ctx: &InforMap, prop_specs: &PropSpecs, prop_terms: &PropTerms, options: &MatchOptions) -> Result<(ConMatches, Vec<MatchDiagnostic>), ErrorMessage> {
+    let mut matches = ConMatches::new();
+    let mut diagnostics = Vec::<MatchDiagnostic>::new();
     let match_setup = initialise_match_setup();
     for (id, tag) in ctx.iter() {
         match tag {
-            Tag::Con(elem_ty, i_name, tags) => {
+            Tag::Con(_, _, tags) => {
                 let prop_descs: Vec<Description> = 
                     tags.iter()
                     .filter(| t | t.is_prop_tag())
@@ -92,42 +192,190 @@ pub fn process_con_decl(ctx: &InforMap, prop_specs: &PropSpecs) -> Result<String
                     .filter(| t | t.is_bound_tag())
                     .flat_map(| t | t.extract_bound_descs())
                     .collect();
-                let lookup_result = library_spec_lookup(id.to_string(), prop_descs, bounds, prop_specs, &match_setup);
-                match lookup_result {
-                    Ok(struct_choices) => {
-                        if struct_choices.is_empty() {
-                            return Err("Unable to find a struct which matches the specification in the library".to_string());
-                        } else {
-                            let opt = struct_choices.join(", ");
-                            code = code + &gen_output_code(id, elem_ty, &struct_choices[0], i_name, &opt)
-                        }
-                    },
-                    Err(e) => {
-                        return Err(e);
-                    }
+                let mut workload = tags.iter().find(|t| t.is_workload_tag()).map(|t| t.extract_workload()).unwrap_or_default();
+                // a recorded profile takes the place of the declared workload
+                let profile = match &options.profile_dir {
+                    Some(dir) => read_profile(dir, id)?,
+                    None => None
+                };
+                if let Some((recorded, _)) = &profile {
+                    workload = recorded.clone();
                 }
+                let (candidates, mut diags) = library_spec_lookup(id.to_string(), prop_descs, bounds, prop_specs, prop_terms, &match_setup, options)?;
+                diagnostics.append(&mut diags);
+                // no struct to rank, reported by the diagnostics
+                if candidates.is_empty() {
+                    matches.push((id.to_string(), Vec::new(), String::new()));
+                    continue;
+                }
+                let size = workload.size.map(|n| n as f64).unwrap_or(NOMINAL_SIZE);
+                let (struct_choices, mut reason) = rank(&candidates, &workload.ops, size);
+                if let Some((_, instances)) = profile {
+                    reason = reason + &format!(", as profiled from {} container(s)", instances);
+                }
+                matches.push((id.to_string(), struct_choices, reason));
             },
             _ => continue
         }
     }
-    Ok(code)
+    matches.sort();
+    diagnostics.sort_by(|a, b| a.con.cmp(&b.con));
+    Ok((matches, diagnostics))
+}
+
+pub fn process_con_decl(ctx: &InforMap, prop_specs: &PropSpecs, prop_terms: &PropTerms, options: &MatchOptions) -> Result<(String, Vec<MatchDiagnostic>), ErrorMessage> {
+    let (parts, diagnostics) = gen_con_decls(ctx, prop_specs, prop_terms, options)?;
+    Ok((concat_parts(parts), diagnostics))
+}
+
+// The code of the container types, each with the container type it is generated for
+fn gen_con_decls(ctx: &InforMap, prop_specs: &PropSpecs, prop_terms: &PropTerms, options: &MatchOptions) -> Result<(Parts, Vec<MatchDiagnostic>), ErrorMessage> {
+    let mut parts = Parts::new();
+    let mut static_bounds = BTreeSet::<String>::new();
+    let (matches, diagnostics) = match_con_decls(ctx, prop_specs, prop_terms, options)?;
+    let lib_specs = process_lib_specs(options.lib_dir.to_string(), &options.work_dir)?; // for the abstractions of the chosen structs
+    for (id, struct_choices, reason) in matches.iter() {
+        if let Some(Tag::Con(elem_ty, i_name, tags)) = ctx.get_id(id.to_string()) {
+            if struct_choices.is_empty() {
+                let reasons = diagnostics.iter().filter(|d| &d.con == id).map(|d| d.to_string()).collect::<Vec<String>>().join("\n");
+                return Err("Unable to find a struct which matches the specification in the library\n".to_string() + &reasons);
+            } else {
+                let opt = struct_choices.join(", ");
+                let chosen = &struct_choices[0];
+                let bounds: Vec<String> = tags.iter().filter(|t| t.is_bound_tag()).flat_map(|t| t.extract_bound_descs()).collect();
+                // a property outside the fragment translated into Rust is left unchecked
+                let contracts = if options.contracts {
+                    Some(gen_contracts(id, elem_ty, chosen, tags, &lib_specs, prop_terms))
+                } else {
+                    None
+                };
+                let wrappers = Wrappers { record: options.record, contracts };
+                let code = match con_dispatch(ctx, id, options) {
+                    Dispatch::Dynamic => gen_output_code(id, elem_ty, chosen, i_name, &opt, reason, &wrappers),
+                    Dispatch::Static => {
+                        static_bounds.extend(bounds.iter().cloned());
+                        gen_static_code(id, elem_ty, chosen, &bounds, &opt, reason, &wrappers)?
+                    }
+                };
+                // a property outside the fragment translated into Rust leaves the container type untested
+                let test = if !options.tests {
+                    String::new()
+                } else {
+                    let predicates: Result<Vec<(String, String)>, ErrorMessage> = gen_contracts(id, TESTELEM, chosen, tags, &lib_specs, prop_terms)
+                        .into_iter().map(|(name, p)| p.map(|p| (name, p))).collect();
+                    match predicates {
+                        Ok(predicates) => gen_con_test(id, chosen, &bounds, con_dispatch(ctx, id, options), &predicates),
+                        Err(e) => format!("\n// No test generated for {}: {}\n", id, e)
+                    }
+                };
+                parts.push((Origin::Con(id.to_string()), code + &test));
+            }
+        }
+    }
+    // the methods of the bounds are called on the static container types without naming them
+    if !static_bounds.is_empty() {
+        let imports = static_bounds.iter().map(|b| b.to_string() + " as _").collect::<Vec<String>>().join(", ");
+        parts.insert(0, (Origin::Generated, format!("use {TRAITCRATE}{{{imports}}};\n")));
+    }
+    Ok((parts, diagnostics))
+}
+
+// The predicates checking the properties of a container type, by name, over its chosen struct,
+// which has to declare its list abstraction; for each property, why it cannot be translated
+// if it is outside the fragment translated into Rust
+fn gen_contracts(con: &str, elem_type: &str, chosen: &str, tags: &[Tag], lib_specs: &LibSpecs, prop_terms: &PropTerms) -> Vec<(String, Result<String, ErrorMessage>)> {
+    let abstraction = match lib_specs.get(chosen) {
+        Some((_, _, _, _, _, Some(_))) => Ok(()),
+        _ => Err("Error, ".to_string() + chosen + " declares no abstraction to check the properties of " + con + " against")
+    };
+    let impl_ty = format!("{chosen}<{elem_type}>");
+    tags.iter().filter(|t| t.is_prop_tag()).map(|t| {
+        let name = t.extract_prop_desc();
+        let predicate = abstraction.clone()
+            .and_then(|_| prop_terms.get(&name).ok_or_else(|| "Error, undefined property ".to_string() + &name + " of " + con))
+            .and_then(|term| gen_predicate(term, &impl_ty, prop_terms).map_err(|e| e + " in the property " + &name + " of " + con));
+        (name, predicate)
+    }).collect()
 }
 
-fn write_provided_ops(provided_ops: &ProvidedOps) -> Result<(), Error>  {
-    let ops_path = OPS;
+// The contents of a specification file the result of a check depends on
+fn read_spec(path: &str) -> String {
+    fs::read_to_string(path).unwrap_or_default()
+}
+
+// The operations provided by a library struct, required by the property specifications
+fn gen_provided_ops(provided_ops: &ProvidedOps) -> String {
     let (code, ops) = provided_ops;
-    let mut output = fs::File::create(ops_path.to_owned())?;
-    write!(output, "{}", LANGDECL.to_string())?;
-    for i in 0..code.len() {
-        write!(output, "{}", code[i])?;
-    }
-    let ops_string = ops.join(" ");
-    let provide = "\n(provide ".to_string() + &ops_string + ")";
-    write!(output, "{}", provide)?;
-    Ok(())
+    LANGDECL.to_string() + &code.concat() + "\n(provide " + &ops.join(" ") + ")"
 }
 
-fn library_spec_lookup(id: String, properties: Vec<Description>, bounds: Vec<Description>, prop_specs: &PropSpecs, match_setup: &MatchSetup) -> Result<Vec<String>, ErrorMessage> {
+// A check of one library struct against one property for one bound
+struct MatchJob<'a> {
+    lib_struct: &'a str,
+    property: &'a str,
+    bound: &'a str,
+    lib_spec_dir: &'a str,
+    interface_spec: &'a str,
+    ops: &'a str, // the operations required by the property specification, for Rosette
+    model: Model,
+    key: String,
+}
+
+// Run the jobs on up to `workers` threads, giving the results in the order of the jobs
+// whichever finishes first; f is given the index of the worker running the job
+fn run_parallel<J: Sync, R: Send>(jobs: &[J], workers: usize, f: impl Fn(usize, &J) -> R + Sync) -> Vec<R> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..jobs.len()).map(|_| None).collect::<Vec<Option<R>>>());
+    thread::scope(|scope| {
+        for worker in 0..workers.max(1).min(jobs.len()) {
+            let (next, results, f) = (&next, &results, &f);
+            scope.spawn(move || {
+                loop {
+                    let n = next.fetch_add(1, Ordering::SeqCst);
+                    if n >= jobs.len() {
+                        break;
+                    }
+                    let r = f(worker, &jobs[n]);
+                    results.lock().unwrap()[n] = Some(r);
+                }
+            });
+        }
+    });
+    results.into_inner().unwrap().into_iter().map(|r| r.unwrap()).collect()
+}
+
+fn run_job(job: &MatchJob, worker: usize, prop_specs: &PropSpecs, prop_terms: &PropTerms, match_setup: &MatchSetup, options: &MatchOptions) -> Result<MatchResult, ErrorMessage> {
+    match options.matcher {
+        Matcher::Rosette => {
+            let dir = job_dir(&options.work_dir, worker);
+            let (prop_file, symbolics) = prop_specs.get(job.property).expect(&("Error: No property specification found for: ".to_string() + job.property));
+            if setup_job(&options.work_dir, &dir, prop_file, job.ops).is_err() {
+                return Err("Error, cannot obtain provided operations from the library specifiction".to_string());
+            }
+            if job.model.is_unordered() {
+                match gen_order_script(&dir, job.property.to_string(), prop_file.to_string(), job.lib_spec_dir.to_string(), job.interface_spec.to_string()) {
+                    Ok(script) => {
+                        let result = run_matching(script)?;
+                        if result.is_some() {
+                            return Ok(result);
+                        }
+                    },
+                    Err(e) => return Err(e.to_string())
+                }
+            }
+            match gen_match_script(&dir, job.property.to_string(), match_setup.get(job.bound).unwrap().to_string(), prop_file.to_string(), job.lib_spec_dir.to_string(), job.interface_spec.to_string(), symbolics) {
+                Ok(script) => run_matching(script),
+                Err(e) => Err(e.to_string())
+            }
+        },
+        Matcher::Native => {
+            check_match(job.property.to_string(), job.bound.to_string(), prop_terms, options.work_dir.to_owned() + LIBDIR + job.lib_spec_dir, job.interface_spec.to_string(), options.model_size, job.model)
+                .map(|r| r.map(Some))
+        }
+    }
+}
+
+fn library_spec_lookup(id: String, properties: Vec<Description>, bounds: Vec<Description>, prop_specs: &PropSpecs, prop_terms: &PropTerms, match_setup: &MatchSetup, options: &MatchOptions) -> Result<(Vec<(String, OpCosts)>, Vec<MatchDiagnostic>), ErrorMessage> {
     let pb = ProgressBar::new_spinner();
     pb.enable_steady_tick(200);
     pb.set_style(
@@ -145,122 +393,323 @@ fn library_spec_lookup(id: String, properties: Vec<Description>, bounds: Vec<Des
             ])
             .template("{spinner:.magenta} {msg}"),
     );
+    if options.quiet {
+        pb.set_draw_target(ProgressDrawTarget::hidden());
+    }
     pb.set_message("Finding library implementations for ".to_owned() + &id + "...");
-    let lib_spec = process_lib_specs(LIB.to_string()).expect("Error: Unable to process library files"); // The specifications of library structs
+    let lib_spec = process_lib_specs(options.lib_dir.to_string(), &options.work_dir)?; // The specifications of library structs
     let mut structs = Vec::new();
-    // select library structs implement bounds decl in contype
-    let mut lib_spec_impls = lib_spec.clone();
-    for (name, (_, impls, _)) in lib_spec.iter() {
-        if (!bounds.iter().all(|i| impls.keys().cloned().collect::<String>().contains(i))) {
-            lib_spec_impls.remove(name);
-        }
-    }
-    for (name, (lib_spec_dir, bound_ctx, provided_ops)) in lib_spec_impls.iter() {
-        match write_provided_ops(provided_ops) {
-            Ok(_) => { },
-            Err(_) => {
-                return Err("Error, cannot obtain provided operations from the library specifiction".to_string());
-            }
-        }
-        let mut is_match = false;
+    let mut diagnostics = Vec::<MatchDiagnostic>::new();
+    let mut cache = MatchCache::load(&(options.work_dir.to_owned() + CACHEFILE))?;
+    // select library structs implement bounds decl in contype, in a fixed order
+    let mut lib_spec_impls: Vec<_> = lib_spec.iter()
+        .filter(|(_, (_, impls, _, _, _, _))| bounds.iter().all(|i| impls.keys().cloned().collect::<String>().contains(i)))
+        .collect();
+    lib_spec_impls.sort_by(|a, b| a.0.cmp(b.0));
+    let ops: Vec<String> = lib_spec_impls.iter().map(|(_, (_, _, provided_ops, _, _, _))| gen_provided_ops(provided_ops)).collect();
+    // one job per struct, property and bound
+    let mut jobs = Vec::<MatchJob>::new();
+    for ((name, (lib_spec_dir, bound_ctx, _, model, _, _)), ops) in lib_spec_impls.iter().zip(ops.iter()) {
         for p in &properties {
-            let mut is_partial_match = false;
             for i in &bounds {
-                let (prop_file, symbolics) = prop_specs.get(p).expect(&("Error: No property specification found for: ".to_string() + &p));
-                match gen_match_script(p.to_string(), match_setup.get(i).unwrap().to_string(), prop_file.to_string(), lib_spec_dir.to_string(), bound_ctx.get(i).unwrap().to_string(), symbolics) {
-                    Ok(_) => {
-                        let result = run_matching(MATCHSCRIPT.to_string());
-                        match result {
-                            Ok(r) => { // true - match; false - not match
-                                if (r) {
-                                    is_partial_match = true;
-                                } else {
-                                    is_partial_match = false;
-                                    break;
-                                }
-                            },
-                            Err(e) => {
-                                return Err(e);
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        return Err(e.to_string());
-                    }
-                }
+                let (prop_file, _) = prop_specs.get(p).expect(&("Error: No property specification found for: ".to_string() + &p));
+                // the setups and the checker are in the key, so that changing them invalidates the results
+                let key = cache_key(&[
+                    &read_spec(&(options.work_dir.to_owned() + PROPDIR + prop_file)),
+                    &read_spec(&(options.work_dir.to_owned() + LIBDIR + lib_spec_dir)),
+                    &read_spec(&(options.work_dir.to_owned() + MATCHDIR + match_setup.get(i).unwrap())),
+                    &SETUPS.iter().map(|(_, contents)| *contents).collect::<String>(),
+                    ops,
+                    p, i, bound_ctx.get(i).unwrap(),
+                    &format!("{:?}-{}-{}-{}", options.matcher, options.model_size, model, CHECKERVERSION)
+                ]);
+                jobs.push(MatchJob {
+                    lib_struct: name,
+                    property: p,
+                    bound: i,
+                    lib_spec_dir,
+                    interface_spec: bound_ctx.get(i).unwrap(),
+                    ops,
+                    model: *model,
+                    key
+                });
             }
-            is_match = is_partial_match;
-            if (!is_match) {
+        }
+    }
+    // only the jobs without a cached result are run
+    let pending: Vec<&MatchJob> = jobs.iter().filter(|job| cache.get(&job.key).is_none()).collect();
+    let results = run_parallel(&pending, options.jobs, |worker, job| {
+        run_job(job, worker, prop_specs, prop_terms, match_setup, options)
+    });
+    for (job, result) in pending.iter().zip(results.into_iter()) {
+        cache.insert(job.key.to_string(), CacheEntry {
+            lib_struct: job.lib_struct.to_string(),
+            property: job.property.to_string(),
+            bound: job.bound.to_string(),
+            result: result?
+        });
+    }
+    // a struct matches if it passes all of its checks; it is reported by its first failing one
+    for (name, (_, _, _, _, costs, _)) in lib_spec_impls.iter() {
+        let mut checks = jobs.iter().filter(|job| job.lib_struct == name.as_str()).peekable();
+        let mut is_match = checks.peek().is_some();
+        for job in checks {
+            if let Some(counterexample) = &cache.get(&job.key).unwrap().result {
+                diagnostics.push(MatchDiagnostic {
+                    con: id.to_string(),
+                    lib_struct: name.to_string(),
+                    property: job.property.to_string(),
+                    bound: job.bound.to_string(),
+                    counterexample: counterexample.clone()
+                });
+                is_match = false;
                 break;
             }
         }
         if (is_match) {
-            structs.push(name.to_string());
+            // the costs of the operations of the bounds
+            let op_costs: OpCosts = bounds.iter().flat_map(|i| costs.get(i).cloned().unwrap_or_default()).collect();
+            structs.push((name.to_string(), op_costs));
         }
     }
     pb.finish_with_message("Done. ".to_owned() + &structs.len().to_string() + " implementation(s) for " + &id + " found.");
-    cleanup_script();
-    Ok(structs)
+    cleanup_jobs(&options.work_dir);
+    if cache.save().is_err() {
+        return Err("Error, unable to write the match cache".to_string());
+    }
+    Ok((structs, diagnostics))
+}
+
+pub fn process_src(filename : String) -> Result<String, SpecError> {
+    process_src_with(filename, &MatchOptions::default())
 }
 
-pub fn process_src(filename : String) -> Result<String, ErrorMessage> {
-    setup_dirs();
-    println!("{}", "Ready...");
-    let f = readfile(filename);
+fn read_src(filename: &str) -> Result<String, SpecError> {
+    fs::read_to_string(filename).map_err(|_| SpecError::Io("Error, unable to read the source file: ".to_string() + filename))
+}
+
+fn parse_src(filename: String) -> Result<(Prog, SourceMap), SpecError> {
+    let src = read_src(&filename)?;
+    parse_text(&filename, &src)
+}
+
+fn parse_text(filename: &str, src: &str) -> Result<(Prog, SourceMap), SpecError> {
+    let (f, anchors) = mark_src_blocks_with_anchors(src);
+    let src_map = SourceMap::new(filename, src, anchors);
     match spec::prog(&f) {
         Ok(blocks) => {
             let mut tc = TypeChecker::new();
-            match tc.check_prog(blocks.clone()) {// type checking
-                Ok(_) => {
-                    // type checking ok
-                    // run analyser
-                    let mut analyser = Analyser::new();
-                    match analyser.analyse_prog(blocks.clone()) {
-                        Ok(_) => {
-                            let mut result = String::new();
-                            // generate con types according to the information in con decl
-                            match process_bound_decl(analyser.get_ctx()) {
-                                Ok(code) => {
-                                    result = result + &code;
-                                    match process_con_decl(analyser.get_ctx(), analyser.get_prop_specs()) {
-                                        Ok(code) => {
-                                            result = CODEGEN.to_string() + IMPORT + &result + &code + CODEGENEND;
-                                            // generate rust source code
-                                            let code_blocks: Vec<&Block> = 
-                                                blocks.iter()
-                                                .filter(| block | block.is_code_block())
-                                                .collect();
-                                            for block in code_blocks.iter() {
-                                                result = result + &process_block(block.to_owned());
-                                            }
-                                            Ok(result)
-                                        },
-                                        Err(e) => Err(e)
-                                    }
-                                },
-                                Err(e) => Err(e)
-                            }
-                        },
-                        Err(e) => Err(e)
-                    }
-                },
-                Err(e) => Err(e)
+            match tc.check_prog(blocks.clone()) {
+                Ok(_) => Ok((blocks, src_map)),
+                Err(e) => Err(e.locate(&src_map))
             }
         },
-        _ => Err("Error, invalid source code.".to_string())
+        Err(e) => {
+            let mut expected: Vec<String> = e.expected.tokens().map(|t| t.to_string()).collect();
+            expected.sort();
+            Err(SpecError::Parse { span: src_map.marked_span(e.location.offset), expected })
+        }
     }
 }
 
+// Parse and type check a source file
+pub fn check_src(filename: String) -> Result<Prog, SpecError> {
+    parse_src(filename).map(|(blocks, _)| blocks)
+}
+
+fn analyse_src(filename: String, options: &MatchOptions) -> Result<(Prog, Analyser), SpecError> {
+    let src = read_src(&filename)?;
+    analyse_text(&filename, &src, options).map(|(blocks, analyser, _)| (blocks, analyser))
+}
+
+fn analyse_text(filename: &str, src: &str, options: &MatchOptions) -> Result<(Prog, Analyser, SourceMap), SpecError> {
+    let (blocks, src_map) = parse_text(filename, src)?;
+    setup_dirs(&options.work_dir).map_err(|_| SpecError::Io("Error, unable to set up the working directory ".to_string() + &options.work_dir))?;
+    let mut analyser = Analyser::new();
+    analyser.set_model_size(options.model_size);
+    analyser.set_work_dir(&options.work_dir);
+    match analyser.analyse_prog(blocks.clone()) {
+        Ok(_) => Ok((blocks, analyser, src_map)),
+        Err(e) => Err(e.locate(&src_map))
+    }
+}
+
+// The library structs matching each container type of a source file
+pub fn match_src(filename: String, options: &MatchOptions) -> Result<(ConMatches, Vec<MatchDiagnostic>), SpecError> {
+    let (_, analyser) = analyse_src(filename, options)?;
+    match_con_decls(analyser.get_ctx(), analyser.get_prop_specs(), analyser.get_prop_terms(), options)
+        .map_err(SpecError::Library)
+}
+
+// The container types declared in a program, with their bounds and workloads
+fn con_type_decls(blocks: &Prog) -> Vec<(String, Vec<String>, Workload)> {
+    blocks.iter()
+        .flat_map(|block| block.extract_spec())
+        .filter_map(|decl| match &decl {
+            Decl::ConTypeDecl(_, (_, ins, _), workload, _) => Some((decl.get_name(), ins.iter().cloned().collect(), *workload.clone())),
+            _ => None
+        })
+        .collect()
+}
+
+// A criterion benchmark of the matching structs of each container type of a source file
+pub fn bench_src(filename: String, options: &MatchOptions) -> Result<Vec<(String, String)>, SpecError> {
+    let (blocks, analyser) = analyse_src(filename, options)?;
+    let (matches, _) = match_con_decls(analyser.get_ctx(), analyser.get_prop_specs(), analyser.get_prop_terms(), options)
+        .map_err(SpecError::Library)?;
+    let decls = con_type_decls(&blocks);
+    let mut benches = Vec::<(String, String)>::new();
+    for (con, structs, _) in matches.iter().filter(|(_, structs, _)| !structs.is_empty()) {
+        if let Some((_, bounds, _)) = decls.iter().find(|(name, _, _)| name == con) {
+            benches.push((con.to_string(), gen_benchmark(con, structs, bounds)));
+        }
+    }
+    Ok(benches)
+}
+
+// The fastest struct of each container type of a source file for its workload, from the results of its benchmark
+pub fn summarise_src(filename: String, criterion_dir: &str) -> Result<Vec<String>, SpecError> {
+    let (blocks, _) = parse_src(filename)?;
+    con_type_decls(&blocks).iter()
+        .map(|(con, bounds, workload)| summarise(criterion_dir, con, bounds, workload))
+        .collect::<Result<Vec<String>, ErrorMessage>>()
+        .map_err(SpecError::Library)
+}
+
+pub fn process_src_with(filename : String, options: &MatchOptions) -> Result<String, SpecError> {
+    generate_src(filename, options).map(|(code, _, _)| code)
+}
+
+fn generate_src(filename : String, options: &MatchOptions) -> Result<(String, Origins, SourceMap), SpecError> {
+    let src = read_src(&filename)?;
+    generate_text(&filename, &src, options)
+}
+
+// The generated program, with where each of its parts comes from
+fn generate_text(filename: &str, src: &str, options: &MatchOptions) -> Result<(String, Origins, SourceMap), SpecError> {
+    let (blocks, analyser, src_map) = analyse_text(filename, src, options)?;
+    // generate con types according to the information in con decl
+    let mut parts = vec![(Origin::Generated, CODEGEN.to_string() + IMPORT)];
+    parts.append(&mut gen_bound_decls(analyser.get_ctx(), options));
+    let (mut con_parts, diagnostics) = gen_con_decls(analyser.get_ctx(), analyser.get_prop_specs(), analyser.get_prop_terms(), options)
+        .map_err(SpecError::Library)?;
+    for d in diagnostics.iter().filter(|_| !options.quiet) {
+        println!("{}", d);
+    }
+    parts.append(&mut con_parts);
+    parts.push((Origin::Generated, CODEGENEND.to_string()));
+    // generate rust source code
+    for block in blocks.iter().filter(| block | block.is_code_block()) {
+        if let Block::CodeBlock(_, n) = block {
+            parts.push((Origin::Code(n + CODE.len()), process_block(block)));
+        }
+    }
+    let mut result = String::new();
+    let mut origins = Origins::new();
+    for (origin, code) in parts.into_iter() {
+        origins.push((result.len(), origin));
+        result += &code;
+    }
+    Ok((result, origins, src_map))
+}
+
+// Generate the program and check that it compiles, see compile_check
+pub fn process_and_check_src(filename : String, options: &MatchOptions) -> Result<String, SpecError> {
+    let (code, origins, src_map) = generate_src(filename, options)?;
+    check_output(&code, &origins, &src_map)?;
+    Ok(code)
+}
+
+// The /*CODEGEN*/ ... /*ENDCODEGEN*/ region of a generated program, without the code of the source
+fn codegen_of(code: &str) -> &str {
+    match code.find(CODEGENEND) {
+        Some(n) => &code[..n + CODEGENEND.len()],
+        None => code
+    }
+}
+
+// The container types generated from the spec blocks of a source file, without its code
+pub fn process_specs(filename: String, options: &MatchOptions) -> Result<String, SpecError> {
+    generate_src(filename, options).map(|(code, _, _)| codegen_of(&code).to_string())
+}
+
+// Parse and type check the declarations of a spec block, named name in the errors
+pub fn check_spec_text(name: &str, decls: &str) -> Result<Prog, SpecError> {
+    parse_text(name, &(SPEC.to_string() + "\n" + decls + "\n" + SPECEND)).map(|(blocks, _)| blocks)
+}
+
+// The container types generated from the declarations of a spec block, named name in the errors
+pub fn process_spec_text(name: &str, decls: &str, options: &MatchOptions) -> Result<String, SpecError> {
+    let src = SPEC.to_string() + "\n" + decls + "\n" + SPECEND;
+    generate_text(name, &src, options).map(|(code, _, _)| codegen_of(&code).to_string())
+}
+
+// The /*CODEGEN*/ ... /*ENDCODEGEN*/ region of a source file, with the line break after it
+fn codegen_region(src: &str) -> Result<Option<(usize, usize)>, SpecError> {
+    let (begin, end) = (CODEGEN.trim_end(), CODEGENEND.trim_end());
+    let start = match src.find(begin) {
+        Some(n) => n,
+        None => return Ok(None)
+    };
+    match src[start..].find(end) {
+        Some(n) => {
+            let stop = start + n + end.len();
+            Ok(Some((start, if src[stop..].starts_with('\n') { stop + 1 } else { stop })))
+        },
+        None => Err(SpecError::Io("Error, ".to_string() + begin + " without " + end))
+    }
+}
+
+// Regenerate the /*CODEGEN*/ region of a source file from its spec blocks, leaving the rest
+// of the file untouched. A file without the region gets it after its last spec block.
+// Gives whether the file changed, so rerunning on an unchanged file does not write it.
+pub fn regenerate_in_place(filename: String, options: &MatchOptions, verify: bool) -> Result<bool, SpecError> {
+    let src = read_src(&filename)?;
+    let (start, end) = match codegen_region(&src)? {
+        Some(region) => region,
+        None => {
+            let n = src.rfind(SPECEND).map(|n| n + SPECEND.len()).unwrap_or(0);
+            let n = if src[n..].starts_with('\n') { n + 1 } else { n };
+            (n, n)
+        }
+    };
+    // the old region is blanked, keeping the lines of the rest of the file for the errors
+    let blanked = src[..start].to_string() + &"\n".repeat(src[start..end].matches('\n').count()) + &src[end..];
+    let (code, origins, src_map) = generate_text(&filename, &blanked, options)?;
+    if verify {
+        check_output(&code, &origins, &src_map)?;
+    }
+    let regenerated = src[..start].to_string() + codegen_of(&code) + &src[end..];
+    if regenerated == src {
+        return Ok(false);
+    }
+    fs::write(&filename, regenerated).map_err(|_| SpecError::Io("Error, unable to write the source file: ".to_string() + &filename))?;
+    Ok(true)
+}
+
+// Generate the program, check that it compiles and write it
 pub fn run(input: String, output_file: String) -> Result<(), Error> {
-    match process_src(input) {
+    match process_and_check_src(input, &MatchOptions::default()) {
         Ok(code) => writefile(output_file, code),
         Err(e) => Err(Error::new(ErrorKind::Other, e.to_string()))
     }
 }
 
 fn mark_src_blocks(src : String) -> String {
+    mark_src_blocks_with_anchors(&src).0
+}
+
+// The offset of a slice of src within src
+fn offset_in(src: &str, slice: &str) -> usize {
+    slice.as_ptr() as usize - src.as_ptr() as usize
+}
+
+// Mark the code blocks of the source, recording for each block its offset
+// in the marked source and in the original source
+fn mark_src_blocks_with_anchors(src : &str) -> (String, Vec<(usize, usize)>) {
     let mut trimed_src = src.trim();
     let mut result = String::new();
+    let mut anchors = Vec::<(usize, usize)>::new();
     while trimed_src.len() > 0 {
         match trimed_src.find(SPEC) {
             Some(n) => {
@@ -268,48 +717,199 @@ fn mark_src_blocks(src : String) -> String {
                     Some(m) => {
                         if (n > 0) {
                             let code = &trimed_src[..n];
+                            anchors.push((result.len() + CODE.len(), offset_in(src, code)));
                             result = result + CODE + &code + CODEEND;
                         }
                         let spec = &trimed_src[n..(m+SPECEND.len())];
+                        anchors.push((result.len(), offset_in(src, spec)));
                         trimed_src = &trimed_src[(m+SPECEND.len())..].trim();
                         result = result + &spec;
                     },
                     None => {
+                        anchors.push((result.len() + CODE.len(), offset_in(src, trimed_src)));
                         result = result + CODE + trimed_src + CODEEND;
                         break;
                     }
                 }
             },
             None => {
+                anchors.push((result.len() + CODE.len(), offset_in(src, trimed_src)));
                 result = result + CODE + trimed_src + CODEEND;
                 break;
             }
         }
     }
-    result
+    (result, anchors)
 }
 
-pub fn gen_output_code(s: &str, elem_type: &str, chosen: &str, trait_name: &str, choices: &str) -> String {
+// What the chosen struct of a container type is wrapped in
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Wrappers {
+    pub record: bool,                              // a profiler::Recorder, profiling the container type
+    pub contracts: Option<Vec<(String, Result<String, ErrorMessage>)>>,  // a contracts::Checked, with the predicates of the properties by name, or why one is not checked
+}
+
+// The bounds of the element type, which is cloned to check the properties of the container
+fn elem_bounds(contracts: bool) -> &'static str {
+    if contracts {
+        "'static + Ord + std::hash::Hash + Clone"
+    } else {
+        "'static + Ord + std::hash::Hash"
+    }
+}
+
+// The type of the implementation of a container type and its construction, the chosen struct
+// in its wrappers
+fn gen_impl(s: &str, elem_type: &str, chosen: &str, wrappers: &Wrappers) -> (String, String) {
+    let mut impl_ty = format!("{chosen}<{elem_type}>");
+    let mut new_impl = format!("<{chosen}<{elem_type}>>::new()");
+    if let Some(predicates) = &wrappers.contracts {
+        let props: String = predicates.iter().map(|(name, p)| match p {
+            Ok(p) => format!("\n            (\"{name}\", {p}),"),
+            Err(e) => format!("\n            // No contract for {name}: {}", e.replace('\n', " ")),
+        }).collect();
+        new_impl = format!("preprocess::contracts::Checked::new({new_impl}, \"{s}\", \"{chosen}\", || vec![{props}\n        ])");
+        impl_ty = format!("preprocess::contracts::Checked<{impl_ty}>");
+    }
+    if wrappers.record {
+        new_impl = format!("preprocess::profiler::Recorder::new({new_impl}, \"{s}\", \"{chosen}\")");
+        impl_ty = format!("preprocess::profiler::Recorder<{impl_ty}>");
+    }
+    (impl_ty, new_impl)
+}
+
+pub fn gen_output_code(s: &str, elem_type: &str, chosen: &str, trait_name: &str, choices: &str, reason: &str, wrappers: &Wrappers) -> String {
+    let (impl_ty, new_impl) = gen_impl(s, elem_type, chosen, wrappers);
+    let bounds = elem_bounds(wrappers.contracts.is_some());
     format!(
 r#"struct {s}<{elem_type}> {{
     elem_t: core::marker::PhantomData<{elem_type}>,
 }}
 
-impl<{elem_type}: 'static + Ord + std::hash::Hash> ContainerConstructor for {s}<{elem_type}> {{
-    type Impl = {chosen}<{elem_type}>; // All possible choices: {choices}
-    type Bound = dyn {trait_name}<{elem_type}>;
-    fn new() -> Box<Self::Bound> {{
-        Box::new(Self::Impl::new())
+impl<{elem_type}: {bounds}> ContainerConstructor for {s}<{elem_type}> {{
+    // Chosen: {reason}
+    type Impl = {impl_ty}; // All possible choices: {choices}
+    type Interface = dyn {trait_name}<{elem_type}>;
+    fn new() -> Box<Self::Interface> {{
+        Box::new({new_impl})
     }}
 }}
 "#)
 }
 
-pub fn gen_trait_code(trait_name: &str, s: &str, elem_type: &str, traits: &str) -> String {
+// The methods of a bound trait, with their parameters, result (none if empty) and arguments,
+// where {T} stands for the element type
+fn bound_methods(bound: &str) -> Option<Vec<(&'static str, &'static str, &'static str, &'static str)>> {
+    match bound {
+        "Container" => Some(vec![
+            ("len", "", "usize", ""),
+            ("contains", ", x: &{T}", "bool", ", x"),
+            ("is_empty", "", "bool", ""),
+            ("insert", ", elt: {T}", "", ", elt"),
+            ("clear", "", "", ""),
+            ("remove", ", elt: {T}", "Option<{T}>", ", elt"),
+        ]),
+        "Stack" => Some(vec![
+            ("push", ", elt: {T}", "", ", elt"),
+            ("pop", "", "Option<{T}>", ""),
+        ]),
+        "RandomAccess" => Some(vec![
+            ("first", "", "Option<&{T}>", ""),
+            ("last", "", "Option<&{T}>", ""),
+            ("nth", ", n: usize", "Option<&{T}>", ", n"),
+        ]),
+        _ => None
+    }
+}
+
+// The elem_type in place of the placeholder {T} of the element type in a signature
+fn with_elem_type(sig: &str, elem_type: &str) -> String {
+    sig.replace("{T}", elem_type)
+}
+
+// A wrapper holding the chosen struct by value, forwarding the bound traits to it
+// without a box or a vtable, so that the calls can be inlined
+pub fn gen_static_code(s: &str, elem_type: &str, chosen: &str, bounds: &[String], choices: &str, reason: &str, wrappers: &Wrappers) -> Result<String, ErrorMessage> {
+    let (impl_ty, new_impl) = gen_impl(s, elem_type, chosen, wrappers);
+    let elem_bounds = elem_bounds(wrappers.contracts.is_some());
+    let mut code = format!(
+r#"struct {s}<{elem_type}> {{
+    c: {impl_ty},
+}}
+
+impl<{elem_type}: {elem_bounds}> {s}<{elem_type}> {{
+    // Chosen: {reason}
+    // All possible choices: {choices}
+    fn new() -> {s}<{elem_type}> {{
+        {s} {{ c: {new_impl} }}
+    }}
+}}
+"#);
+    for bound in bounds.iter() {
+        let methods = bound_methods(bound).ok_or_else(|| "Error, unable to forward the bound statically: ".to_string() + bound)?;
+        code = code + &format!("\nimpl<{elem_type}: {elem_bounds}> {TRAITCRATE}{bound}<{elem_type}> for {s}<{elem_type}> {{\n");
+        for (name, params, result, args) in methods.iter() {
+            let params = with_elem_type(params, elem_type);
+            let result = if result.is_empty() { String::new() } else { " -> ".to_string() + &with_elem_type(result, elem_type) };
+            code = code + &format!(
+r#"    #[inline]
+    fn {name}(&mut self{params}){result} {{
+        {TRAITCRATE}{bound}::<{elem_type}>::{name}(&mut self.c{args})
+    }}
+"#);
+        }
+        code += "}\n";
+    }
+    Ok(code)
+}
+
+// A test of a container type, running random sequences of the operations of its bounds on it,
+// built by its generated constructor with its wrappers, and on its chosen struct in lockstep,
+// comparing their results and checking the properties of the container type after each of them
+pub fn gen_con_test(s: &str, chosen: &str, bounds: &[String], dispatch: Dispatch, predicates: &[(String, String)]) -> String {
+    let name = snake_case(s);
+    let impl_ty = format!("{chosen}<{TESTELEM}>");
+    let (con_ty, new_con, con) = match dispatch {
+        Dispatch::Dynamic => (format!("Box<<{s}<{TESTELEM}> as ContainerConstructor>::Interface>"), format!("<{s}<{TESTELEM}> as ContainerConstructor>::new"), "&mut **c"),
+        Dispatch::Static => (format!("{s}<{TESTELEM}>"), format!("{s}::<{TESTELEM}>::new"), "c"),
+    };
+    let props: String = predicates.iter().map(|(prop, p)| format!("\n            (\"{prop}\", {p}),")).collect();
+    let bound_names = bounds.iter().map(|b| format!("\"{b}\"")).collect::<Vec<String>>().join(", ");
+    let apply = |c: &str| bounds.iter().map(|b| format!("preprocess::differential::apply_{}({c}, op)", snake_case(b)))
+        .reduce(|calls, call| calls + ".or_else(|| " + &call + ")")
+        .unwrap_or_else(|| "None".to_string());
+    let (apply_con, apply_impl) = (apply(con), apply("c"));
+    format!(
+r#"
+#[cfg(test)]
+mod {name}_tests {{
+    use super::*;
+
+    // {s} keeps its properties through random sequences of its operations, and gives the results of {chosen}
+    #[test]
+    fn test_{name}() {{
+        let props: Vec<(&str, preprocess::contracts::Predicate<{impl_ty}>)> = vec![{props}
+        ];
+        let apply = |c: &mut {con_ty}, op: &preprocess::differential::Op| {apply_con};
+        let apply_impl = |c: &mut {impl_ty}, op: &preprocess::differential::Op| {apply_impl};
+        if let Err(e) = preprocess::differential::check_con_type(&[{bound_names}], {new_con}, apply, <{impl_ty}>::new, apply_impl, props, {TESTCASES}) {{
+            panic!("{{}}", e);
+        }}
+    }}
+}}
+"#)
+}
+
+pub fn gen_trait_code(trait_name: &str, s: &str, elem_type: &str, traits: &str, dispatch: Dispatch, contracts: bool) -> String {
+    let bounds = elem_bounds(contracts);
+    let target = match dispatch {
+        Dispatch::Dynamic => format!("<{s}<{elem_type}> as ContainerConstructor>::Impl"),
+        Dispatch::Static => format!("{s}<{elem_type}>")
+    };
     format!(
 r#"
 trait {trait_name}<{elem_type}> : {traits} {{}}
-impl<{elem_type}: 'static + Ord + std::hash::Hash> {trait_name}<{elem_type}> for <{s}<{elem_type}> as ContainerConstructor>::Impl {{}}
+impl<{elem_type}: {bounds}> {trait_name}<{elem_type}> for {target} {{}}
 "#)
 }
 
@@ -331,4 +931,72 @@ impl<{elem_type}: 'static + Ord + std::hash::Hash> {trait_name}<{elem_type}> for
 //         assert!(spec::prog(&f).is_ok())
 //     }
 
-// }
\ No newline at end of file
+// }
+#[cfg(test)]
+mod region_tests {
+    use crate::generator::{codegen_region};
+
+    #[test]
+    fn test_codegen_region() {
+        let src = "/*SPEC*\n*ENDSPEC*/\n/*CODEGEN*/\nstruct A;\n/*ENDCODEGEN*/\nfn main() {}\n";
+        let (start, end) = codegen_region(src).unwrap().unwrap();
+        assert_eq!(&src[start..end], "/*CODEGEN*/\nstruct A;\n/*ENDCODEGEN*/\n");
+        assert_eq!(codegen_region("fn main() {}").unwrap(), None);
+        assert!(codegen_region("/*CODEGEN*/\nstruct A;\n").is_err());
+    }
+}
+
+#[cfg(test)]
+mod match_tests {
+    use std::fs;
+    use crate::generator::{MatchOptions, analyse_text, match_con_decls, process_spec_text};
+
+    const DECLS: &str = r"property descending<T> {
+    \c -> ((for-all-consecutive-pairs c) geq?)
+}
+type DescendingCon<T> = {c impl (Container) | (descending c)} workload { insert: 60, contains: 40 }";
+
+    #[test]
+    fn test_workload_without_match() {
+        let work_dir = std::env::temp_dir().join("preprocess-match-test");
+        let options = MatchOptions { work_dir: work_dir.to_string_lossy().to_string() + "/", quiet: true, ..MatchOptions::default() };
+        let src = "/*SPEC*\n".to_string() + DECLS + "\n*ENDSPEC*/\n";
+        let (_, analyser, _) = analyse_text("descending.rs", &src, &options).unwrap();
+        let (matches, diagnostics) = match_con_decls(analyser.get_ctx(), analyser.get_prop_specs(), analyser.get_prop_terms(), &options).unwrap();
+        assert_eq!(matches, vec![("DescendingCon".to_string(), Vec::new(), String::new())]);
+        assert!(!diagnostics.is_empty() && diagnostics.iter().all(|d| d.counterexample.is_some()));
+        let e = process_spec_text("descending.rs", DECLS, &options).unwrap_err().to_string();
+        assert!(e.contains("Unable to find a struct") && e.contains("std::vec::Vec does not satisfy descending"));
+        let e = process_spec_text("descending.rs", &DECLS.replace("contains", "push"), &options).unwrap_err().to_string();
+        assert!(e.contains("The workload names an operation of none of its interfaces: push"));
+        fs::remove_dir_all(&work_dir).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod wrapper_tests {
+    use crate::generator::{Wrappers, gen_output_code, gen_static_code};
+
+    #[test]
+    fn test_untranslated_contract() {
+        let contracts = vec![
+            ("unique".to_string(), Ok("unique_predicate".to_string())),
+            ("small".to_string(), Err("Error, unable to translate the literal x into Rust".to_string())),
+        ];
+        let wrappers = Wrappers { record: false, contracts: Some(contracts) };
+        let code = gen_output_code("SetCon", "T", "std::collections::BTreeSet", "SetConTrait", "none", "the only matching struct", &wrappers);
+        assert!(code.contains("preprocess::contracts::Checked::new(<std::collections::BTreeSet<T>>::new(), \"SetCon\", \"std::collections::BTreeSet\", || vec!["));
+        assert!(code.contains("(\"unique\", unique_predicate),"));
+        assert!(code.contains("// No contract for small: Error, unable to translate the literal x into Rust"));
+    }
+
+    #[test]
+    fn test_static_signatures() {
+        let bounds = vec!["Container".to_string(), "RandomAccess".to_string()];
+        let code = gen_static_code("SetCon", "Elt", "std::collections::BTreeSet", &bounds, "none", "the only matching struct", &Wrappers::default()).unwrap();
+        assert!(code.contains("fn contains(&mut self, x: &Elt) -> bool {"));
+        assert!(code.contains("fn remove(&mut self, elt: Elt) -> Option<Elt> {"));
+        assert!(code.contains("fn nth(&mut self, n: usize) -> Option<&Elt> {"));
+        assert!(code.contains("preprocess::traits::RandomAccess::<Elt>::nth(&mut self.c, n)"));
+    }
+}
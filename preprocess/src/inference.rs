@@ -53,9 +53,13 @@ impl TypeEnv {
         // Get types of operations defined in traits
         let bounded_ops = generate_bounded_ops();
         let (s, t) = (match term {
-            // Infer literal: currently only boolean
+            // Infer literal: boolean or integer
             Term::LitTerm(_) => {
-                Ok((Subst::new(), Type::Bool()))
+                if term.is_int_literal() {
+                    Ok((Subst::new(), Type::Int()))
+                } else {
+                    Ok((Subst::new(), Type::Bool()))
+                }
             }
             // Infer variable
             Term::VarTerm(v) => {
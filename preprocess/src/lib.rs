@@ -6,14 +6,26 @@
 pub mod parser;
 pub mod generator;
 pub mod type_check;
+pub mod error;
 pub mod description;
 pub mod types;
 pub mod analysis;
 pub mod inference;
 pub mod lib_spec_processor;
+pub mod lib_spec;
 pub mod spec_map;
 pub mod run_matching;
+pub mod native_matching;
+pub mod match_cache;
 pub mod bounded_ops;
+pub mod cost;
+pub mod profiler;
+pub mod benchmark;
+pub mod compile_check;
+pub mod build_script;
+pub mod differential;
+pub mod predicates;
+pub mod contracts;
 
 pub mod library;
 pub mod traits;
@@ -0,0 +1,264 @@
+// A typed intermediate representation of the operation specifications
+// declared in /*LIBSPEC* blocks of library files.
+pub mod sexpr;
+pub mod type_check;
+pub mod racket;
+
+use crate::parser::{Id};
+use crate::lib_spec::sexpr::{SExpr, read_sexprs};
+
+type ErrorMessage = String;
+
+// The abstract state of a library struct, declared by the /*MODEL* pragma of its library file.
+// The specifications always work on lists; for an unordered model the order of a list is arbitrary.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Model {
+    List, // a sequence, the default
+    Bag,  // a multiset
+    Set,
+}
+
+impl Model {
+    pub fn parse(s: &str) -> Result<Model, ErrorMessage> {
+        match s {
+            "list" => Ok(Model::List),
+            "bag" => Ok(Model::Bag),
+            "set" => Ok(Model::Set),
+            _ => Err("Error, unknown model: ".to_string() + s + ", expected list, bag or set")
+        }
+    }
+
+    pub fn is_unordered(&self) -> bool {
+        *self != Model::List
+    }
+}
+
+impl std::fmt::Display for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Model::List => write!(f, "list"),
+            Model::Bag => write!(f, "bag"),
+            Model::Set => write!(f, "set"),
+        }
+    }
+}
+
+// Types of the list model
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Ty {
+    Bool,
+    Int,
+    Elem,                     // an element of the container
+    List(Box<Ty>),
+    Pair(Box<Ty>, Box<Ty>),
+    Opt(Box<Ty>),             // a value or null
+}
+
+impl std::fmt::Display for Ty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Ty::Bool => write!(f, "Bool"),
+            Ty::Int => write!(f, "Int"),
+            Ty::Elem => write!(f, "T"),
+            Ty::List(t) => write!(f, "List<{}>", t),
+            Ty::Pair(a, d) => write!(f, "({}, {})", a, d),
+            Ty::Opt(t) => write!(f, "Option<{}>", t),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Bool(bool),
+    Int(i64),
+    Null,
+    Var(Id),
+    App(Id, Vec<Expr>),
+    If(Box<Expr>, Box<Expr>, Box<Expr>),
+    Cond(Vec<(Expr, Expr)>, Option<Box<Expr>>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+// (define (name params ...) body)
+#[derive(Clone, Debug, PartialEq)]
+pub struct Def {
+    pub name: Id,
+    pub params: Vec<Id>,
+    pub body: Expr,
+}
+
+// The specification of one operation: its model, precondition and postcondition
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpSpec {
+    pub name: Id,
+    pub op: Def,
+    pub pre: Def,
+    pub post: Def,
+}
+
+impl OpSpec {
+    pub fn defs(&self) -> Vec<&Def> {
+        vec![&self.op, &self.pre, &self.post]
+    }
+}
+
+// The specifications of the operations of one interface, e.g. Container
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterfaceSpec {
+    pub interface: Id,
+    pub ops: Vec<OpSpec>,
+}
+
+fn parse_expr(e: &SExpr) -> Result<Expr, ErrorMessage> {
+    match e {
+        SExpr::Atom(a) => {
+            match a.as_str() {
+                "#t" | "#true" => Ok(Expr::Bool(true)),
+                "#f" | "#false" => Ok(Expr::Bool(false)),
+                "null" | "empty" => Ok(Expr::Null),
+                _ => match a.parse::<i64>() {
+                    Ok(n) => Ok(Expr::Int(n)),
+                    Err(_) => Ok(Expr::Var(a.to_string()))
+                }
+            }
+        },
+        SExpr::List(l) => {
+            let head = match l.first().and_then(|h| h.as_atom()) {
+                Some(h) => h,
+                None => return Err("Error, expected an operator in: ".to_string() + &e.to_string())
+            };
+            let args = |from: usize| -> Result<Vec<Expr>, ErrorMessage> {
+                l[from..].iter().map(parse_expr).collect()
+            };
+            match head {
+                "quote" => match l.get(1) {
+                    Some(SExpr::List(q)) if q.is_empty() => Ok(Expr::Null),
+                    _ => Err("Error, unsupported quoted expression: ".to_string() + &e.to_string())
+                },
+                "if" => {
+                    let mut a = args(1)?;
+                    if a.len() != 3 {
+                        return Err("Error, invalid if expression: ".to_string() + &e.to_string());
+                    }
+                    let e3 = a.pop().unwrap();
+                    let e2 = a.pop().unwrap();
+                    let e1 = a.pop().unwrap();
+                    Ok(Expr::If(Box::new(e1), Box::new(e2), Box::new(e3)))
+                },
+                "cond" => {
+                    let mut clauses = Vec::<(Expr, Expr)>::new();
+                    let mut otherwise = None;
+                    for c in l[1..].iter() {
+                        match c {
+                            SExpr::List(c) if c.len() == 2 => {
+                                if c[0].as_atom() == Some("else") {
+                                    otherwise = Some(Box::new(parse_expr(&c[1])?));
+                                } else {
+                                    clauses.push((parse_expr(&c[0])?, parse_expr(&c[1])?));
+                                }
+                            },
+                            _ => return Err("Error, invalid cond clause: ".to_string() + &c.to_string())
+                        }
+                    }
+                    Ok(Expr::Cond(clauses, otherwise))
+                },
+                "and" => Ok(Expr::And(args(1)?)),
+                "or" => Ok(Expr::Or(args(1)?)),
+                "define" | "lambda" | "let" => Err("Error, nested definitions are not supported: ".to_string() + &e.to_string()),
+                _ => Ok(Expr::App(head.to_string(), args(1)?))
+            }
+        }
+    }
+}
+
+pub fn parse_def(e: &SExpr) -> Result<Def, ErrorMessage> {
+    if let SExpr::List(l) = e {
+        if l.len() == 3 && l[0].as_atom() == Some("define") {
+            if let SExpr::List(sig) = &l[1] {
+                let names: Option<Vec<Id>> = sig.iter().map(|s| s.as_atom().map(|a| a.to_string())).collect();
+                if let Some(mut names) = names {
+                    if !names.is_empty() {
+                        let params = names.split_off(1);
+                        return Ok(Def {
+                            name: names.pop().unwrap(),
+                            params,
+                            body: parse_expr(&l[2])?
+                        });
+                    }
+                }
+            }
+        }
+    }
+    Err("Error, expected a definition of the form (define (f x ...) body): ".to_string() + &e.to_string())
+}
+
+// Parse the code of a /*LIBSPEC* block given the names declared in its /*OPNAME* pragma
+pub fn parse_op_spec(name: &str, op: &str, pre: &str, post: &str, code: &str) -> Result<OpSpec, ErrorMessage> {
+    let defs = read_sexprs(code)?
+        .iter()
+        .map(parse_def)
+        .collect::<Result<Vec<Def>, ErrorMessage>>()?;
+    let find = |n: &str| -> Result<Def, ErrorMessage> {
+        match defs.iter().find(|d| d.name == n) {
+            Some(d) => Ok(d.clone()),
+            None => Err("Error, no definition of ".to_string() + n + " in the specification of " + name)
+        }
+    };
+    if defs.len() != 3 {
+        return Err("Error, the specification of ".to_string() + name + " should define exactly the operation, its pre- and postcondition");
+    }
+    Ok(OpSpec {
+        name: name.to_string(),
+        op: find(op)?,
+        pre: find(pre)?,
+        post: find(post)?
+    })
+}
+
+impl Expr {
+    pub fn to_sexpr(&self) -> SExpr {
+        let atom = |s: &str| SExpr::Atom(s.to_string());
+        match self {
+            Expr::Bool(true) => atom("#t"),
+            Expr::Bool(false) => atom("#f"),
+            Expr::Int(n) => atom(&n.to_string()),
+            Expr::Null => atom("null"),
+            Expr::Var(id) => atom(id),
+            Expr::App(f, args) => {
+                let mut l = vec![atom(f)];
+                l.extend(args.iter().map(|a| a.to_sexpr()));
+                SExpr::List(l)
+            },
+            Expr::If(e1, e2, e3) => SExpr::List(vec![atom("if"), e1.to_sexpr(), e2.to_sexpr(), e3.to_sexpr()]),
+            Expr::Cond(clauses, otherwise) => {
+                let mut l = vec![atom("cond")];
+                for (c, e) in clauses.iter() {
+                    l.push(SExpr::List(vec![c.to_sexpr(), e.to_sexpr()]));
+                }
+                if let Some(e) = otherwise {
+                    l.push(SExpr::List(vec![atom("else"), e.to_sexpr()]));
+                }
+                SExpr::List(l)
+            },
+            Expr::And(es) => {
+                let mut l = vec![atom("and")];
+                l.extend(es.iter().map(|a| a.to_sexpr()));
+                SExpr::List(l)
+            },
+            Expr::Or(es) => {
+                let mut l = vec![atom("or")];
+                l.extend(es.iter().map(|a| a.to_sexpr()));
+                SExpr::List(l)
+            },
+        }
+    }
+}
+
+impl Def {
+    pub fn to_sexpr(&self) -> SExpr {
+        let mut sig = vec![SExpr::Atom(self.name.to_string())];
+        sig.extend(self.params.iter().map(|p| SExpr::Atom(p.to_string())));
+        SExpr::List(vec![SExpr::Atom("define".to_string()), SExpr::List(sig), self.body.to_sexpr()])
+    }
+}
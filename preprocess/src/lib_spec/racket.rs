@@ -0,0 +1,11 @@
+use crate::lib_spec::{Def, OpSpec};
+
+// Emit a definition as Racket code
+pub fn emit_def(def: &Def) -> String {
+    def.to_sexpr().to_string() + "\n"
+}
+
+// Emit the code of an operation specification, in the order of its /*OPNAME* pragma
+pub fn emit_op_spec(spec: &OpSpec) -> String {
+    spec.defs().into_iter().map(emit_def).collect()
+}
@@ -0,0 +1,112 @@
+pub type ReadError = String;
+
+// S-expressions of the Racket subset used by library specifications
+#[derive(Clone, Debug, PartialEq)]
+pub enum SExpr {
+    Atom(String),
+    List(Vec<SExpr>),
+}
+
+impl SExpr {
+    pub fn as_atom(&self) -> Option<&str> {
+        match self {
+            SExpr::Atom(a) => Some(a),
+            _ => None
+        }
+    }
+}
+
+impl std::fmt::Display for SExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SExpr::Atom(a) => write!(f, "{}", a),
+            SExpr::List(l) => {
+                let elems: Vec<String> = l.iter().map(|e| e.to_string()).collect();
+                write!(f, "({})", elems.join(" "))
+            }
+        }
+    }
+}
+
+fn tokenize(src: &str) -> Vec<String> {
+    let mut tokens = Vec::<String>::new();
+    let mut current = String::new();
+    let mut in_comment = false;
+    for c in src.chars() {
+        if in_comment {
+            if c == '\n' {
+                in_comment = false;
+            }
+            continue;
+        }
+        match c {
+            ';' => {
+                in_comment = true;
+            },
+            '(' | ')' | '[' | ']' | '\'' => {
+                if !current.is_empty() {
+                    tokens.push(current.clone());
+                    current.clear();
+                }
+                tokens.push(c.to_string());
+            },
+            c if c.is_whitespace() => {
+                if !current.is_empty() {
+                    tokens.push(current.clone());
+                    current.clear();
+                }
+            },
+            _ => current.push(c)
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn parse_expr(tokens: &[String], pos: &mut usize) -> Result<SExpr, ReadError> {
+    match tokens.get(*pos) {
+        Some(t) if t == "(" || t == "[" => {
+            let close = if t == "(" { ")" } else { "]" };
+            *pos += 1;
+            let mut elems = Vec::<SExpr>::new();
+            loop {
+                match tokens.get(*pos) {
+                    Some(t) if t == close => {
+                        *pos += 1;
+                        return Ok(SExpr::List(elems));
+                    },
+                    Some(_) => elems.push(parse_expr(tokens, pos)?),
+                    None => return Err("Unbalanced parentheses in specification".to_string())
+                }
+            }
+        },
+        Some(t) if t == "'" => {
+            *pos += 1;
+            let quoted = parse_expr(tokens, pos)?;
+            Ok(SExpr::List(vec![SExpr::Atom("quote".to_string()), quoted]))
+        },
+        Some(t) if t == ")" || t == "]" => Err("Unexpected closing parenthesis in specification".to_string()),
+        Some(t) => {
+            *pos += 1;
+            Ok(SExpr::Atom(t.to_string()))
+        },
+        None => Err("Unexpected end of specification".to_string())
+    }
+}
+
+// Read all top level forms of a Racket source, skipping the #lang line
+pub fn read_sexprs(src: &str) -> Result<Vec<SExpr>, ReadError> {
+    let body: String = src.lines()
+        .filter(|l| !l.trim_start().starts_with("#lang"))
+        .collect::<Vec<&str>>()
+        .join("\n");
+    let tokens = tokenize(&body);
+    let mut pos = 0;
+    let mut exprs = Vec::<SExpr>::new();
+    while pos < tokens.len() {
+        exprs.push(parse_expr(&tokens, &mut pos)?);
+    }
+    Ok(exprs)
+}
@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+
+use crate::parser::{Id};
+use crate::lib_spec::{Ty, Expr, Def, OpSpec, InterfaceSpec};
+
+type TypeError = String;
+
+// (types of the arguments following the list, type of the result)
+type Signature = (Vec<Ty>, Ty);
+
+fn list() -> Ty {
+    Ty::List(Box::new(Ty::Elem))
+}
+
+fn with_list(t: Ty) -> Ty {
+    Ty::Pair(Box::new(list()), Box::new(t))
+}
+
+// The list model of each operation of the traits in crate::traits
+pub fn op_signature(interface: &str, op: &str) -> Option<Signature> {
+    let elem = Ty::Opt(Box::new(Ty::Elem));
+    let sig = match (interface, op) {
+        ("Container", "len") => (vec![], with_list(Ty::Int)),
+        ("Container", "contains") => (vec![Ty::Elem], with_list(Ty::Bool)),
+        ("Container", "is-empty") => (vec![], with_list(Ty::Bool)),
+        ("Container", "clear") => (vec![], list()),
+        ("Container", "insert") => (vec![Ty::Elem], list()),
+        ("Container", "remove") => (vec![Ty::Elem], with_list(elem)),
+        ("Stack", "push") => (vec![Ty::Elem], list()),
+        ("Stack", "pop") => (vec![], with_list(elem)),
+        ("RandomAccess", "first") => (vec![], with_list(elem)),
+        ("RandomAccess", "last") => (vec![], with_list(elem)),
+        ("RandomAccess", "nth") => (vec![Ty::Int], with_list(elem)),
+        _ => return None
+    };
+    Some(sig)
+}
+
+pub fn interface_ops(interface: &str) -> Option<Vec<&'static str>> {
+    match interface {
+        "Container" => Some(vec!["clear", "contains", "insert", "is-empty", "len", "remove"]),
+        "Stack" => Some(vec!["pop", "push"]),
+        "RandomAccess" => Some(vec!["first", "last", "nth"]),
+        _ => None
+    }
+}
+
+fn is_comparison(f: &str) -> bool {
+    matches!(f, "<" | ">" | "<=" | ">=" | "=" | "leq?" | "geq?")
+}
+
+struct Ctx<'a> {
+    vars: Vec<(Id, Ty)>,
+    defs: &'a HashMap<Id, Signature>,
+}
+
+impl<'a> Ctx<'a> {
+    fn lookup(&self, id: &str) -> Result<Ty, TypeError> {
+        match self.vars.iter().rev().find(|(v, _)| v == id) {
+            Some((_, t)) => Ok(t.clone()),
+            None => Err("unbound variable ".to_string() + id)
+        }
+    }
+
+    fn synth_list(&self, e: &Expr) -> Result<Ty, TypeError> {
+        match self.synth(e)? {
+            Ty::List(t) => Ok(*t),
+            t => Err(format!("expected a list, found {} in {}", t, e.to_sexpr()))
+        }
+    }
+
+    fn arity(&self, f: &str, args: &[Expr], n: usize) -> Result<(), TypeError> {
+        if args.len() == n {
+            Ok(())
+        } else {
+            Err(format!("{} expects {} argument(s), given {}", f, n, args.len()))
+        }
+    }
+
+    fn synth(&self, e: &Expr) -> Result<Ty, TypeError> {
+        match e {
+            Expr::Bool(_) => Ok(Ty::Bool),
+            Expr::Int(_) => Ok(Ty::Int),
+            Expr::Null => Err("cannot infer the type of null here".to_string()),
+            Expr::Var(id) => self.lookup(id),
+            Expr::If(c, e1, e2) => {
+                self.check(c, &Ty::Bool)?;
+                self.synth_branches(&[e1.as_ref(), e2.as_ref()])
+            },
+            Expr::Cond(clauses, otherwise) => {
+                let mut branches = Vec::<&Expr>::new();
+                for (c, b) in clauses.iter() {
+                    self.check(c, &Ty::Bool)?;
+                    branches.push(b);
+                }
+                if let Some(b) = otherwise {
+                    branches.push(b);
+                }
+                self.synth_branches(&branches)
+            },
+            Expr::And(es) | Expr::Or(es) => {
+                for e in es.iter() {
+                    self.check(e, &Ty::Bool)?;
+                }
+                Ok(Ty::Bool)
+            },
+            Expr::App(f, args) => self.synth_app(f, args)
+        }
+    }
+
+    fn synth_branches(&self, branches: &[&Expr]) -> Result<Ty, TypeError> {
+        match branches.iter().find(|b| **b != &Expr::Null) {
+            Some(b) => {
+                let t = self.synth(b)?;
+                for b in branches.iter() {
+                    self.check(b, &t)?;
+                }
+                Ok(t)
+            },
+            None => Err("cannot infer the type of null here".to_string())
+        }
+    }
+
+    fn synth_app(&self, f: &str, args: &[Expr]) -> Result<Ty, TypeError> {
+        if let Some((params, result)) = self.defs.get(f) {
+            // a call to another definition of the specification, taking the list first
+            self.arity(f, args, params.len() + 1)?;
+            self.check(&args[0], &list())?;
+            for (a, t) in args[1..].iter().zip(params.iter()) {
+                self.check(a, t)?;
+            }
+            return Ok(result.clone());
+        }
+        match f {
+            "length" => {
+                self.arity(f, args, 1)?;
+                self.synth_list(&args[0])?;
+                Ok(Ty::Int)
+            },
+            "null?" | "empty?" | "list?" => {
+                self.arity(f, args, 1)?;
+                if args[0] != Expr::Null {
+                    self.synth(&args[0])?;
+                }
+                Ok(Ty::Bool)
+            },
+            "member" | "remove" => {
+                self.arity(f, args, 2)?;
+                let t = self.synth_list(&args[1])?;
+                self.check(&args[0], &t)?;
+                if f == "member" {
+                    Ok(Ty::Opt(Box::new(Ty::List(Box::new(t)))))
+                } else {
+                    Ok(Ty::List(Box::new(t)))
+                }
+            },
+            "remove-duplicates" | "reverse" => {
+                self.arity(f, args, 1)?;
+                Ok(Ty::List(Box::new(self.synth_list(&args[0])?)))
+            },
+            "sort" => {
+                self.arity(f, args, 2)?;
+                let t = self.synth_list(&args[0])?;
+                match &args[1] {
+                    Expr::Var(cmp) if is_comparison(cmp) => Ok(Ty::List(Box::new(t))),
+                    a => Err(format!("sort expects a comparison operator, found {}", a.to_sexpr()))
+                }
+            },
+            "append" => {
+                if args.is_empty() {
+                    return Err("append expects at least one argument".to_string());
+                }
+                let t = Ty::List(Box::new(self.synth_list(&args[0])?));
+                for a in args[1..].iter() {
+                    self.check(a, &t)?;
+                }
+                Ok(t)
+            },
+            "list" => {
+                if args.is_empty() {
+                    return Err("cannot infer the type of an empty list".to_string());
+                }
+                let t = self.synth(&args[0])?;
+                for a in args[1..].iter() {
+                    self.check(a, &t)?;
+                }
+                Ok(Ty::List(Box::new(t)))
+            },
+            "take" | "drop" => {
+                self.arity(f, args, 2)?;
+                let t = self.synth_list(&args[0])?;
+                self.check(&args[1], &Ty::Int)?;
+                Ok(Ty::List(Box::new(t)))
+            },
+            "first" | "last" => {
+                self.arity(f, args, 1)?;
+                self.synth_list(&args[0])
+            },
+            "list-ref" => {
+                self.arity(f, args, 2)?;
+                let t = self.synth_list(&args[0])?;
+                self.check(&args[1], &Ty::Int)?;
+                Ok(t)
+            },
+            "+" | "-" | "*" => {
+                for a in args.iter() {
+                    self.check(a, &Ty::Int)?;
+                }
+                Ok(Ty::Int)
+            },
+            f if is_comparison(f) => {
+                if args.len() < 2 {
+                    return Err(format!("{} expects at least two arguments", f));
+                }
+                let t = self.synth(&args[0])?;
+                if t != Ty::Int && t != Ty::Elem {
+                    return Err(format!("{} compares numbers, found {}", f, t));
+                }
+                for a in args[1..].iter() {
+                    self.check(a, &t)?;
+                }
+                Ok(Ty::Bool)
+            },
+            "not" => {
+                self.arity(f, args, 1)?;
+                self.check(&args[0], &Ty::Bool)?;
+                Ok(Ty::Bool)
+            },
+            "equal?" | "not-equal?" => {
+                self.arity(f, args, 2)?;
+                let (known, other) = if args[0] == Expr::Null { (&args[1], &args[0]) } else { (&args[0], &args[1]) };
+                let t = self.synth(known)?;
+                self.check(other, &t)?;
+                Ok(Ty::Bool)
+            },
+            "cons" => {
+                self.arity(f, args, 2)?;
+                let a = self.synth(&args[0])?;
+                if args[1] == Expr::Null {
+                    return Ok(Ty::List(Box::new(a)));
+                }
+                let d = self.synth(&args[1])?;
+                if d == Ty::List(Box::new(a.clone())) {
+                    Ok(d)
+                } else {
+                    Ok(Ty::Pair(Box::new(a), Box::new(d)))
+                }
+            },
+            "car" | "cdr" => {
+                self.arity(f, args, 1)?;
+                match self.synth(&args[0])? {
+                    Ty::Pair(a, _) if f == "car" => Ok(*a),
+                    Ty::Pair(_, d) => Ok(*d),
+                    Ty::List(t) if f == "car" => Ok(*t),
+                    Ty::List(t) => Ok(Ty::List(t)),
+                    t => Err(format!("{} expects a pair, found {}", f, t))
+                }
+            },
+            _ => Err("unknown operator ".to_string() + f)
+        }
+    }
+
+    fn check(&self, e: &Expr, expected: &Ty) -> Result<(), TypeError> {
+        match (e, expected) {
+            (Expr::Null, Ty::List(_)) | (Expr::Null, Ty::Opt(_)) => Ok(()),
+            (Expr::If(c, e1, e2), _) => {
+                self.check(c, &Ty::Bool)?;
+                self.check(e1, expected)?;
+                self.check(e2, expected)
+            },
+            (Expr::Cond(clauses, otherwise), _) => {
+                for (c, b) in clauses.iter() {
+                    self.check(c, &Ty::Bool)?;
+                    self.check(b, expected)?;
+                }
+                match otherwise {
+                    Some(b) => self.check(b, expected),
+                    None => Ok(())
+                }
+            },
+            (Expr::App(f, args), Ty::Pair(a, d)) if f == "cons" && !self.defs.contains_key(f) && args.len() == 2 => {
+                self.check(&args[0], a)?;
+                self.check(&args[1], d)
+            },
+            (Expr::App(f, args), Ty::List(t)) if f == "cons" && !self.defs.contains_key(f) && args.len() == 2 => {
+                self.check(&args[0], t)?;
+                self.check(&args[1], expected)
+            },
+            _ => {
+                let actual = self.synth(e)?;
+                let compatible = match expected {
+                    Ty::Opt(t) => actual == **t || actual == *expected,
+                    _ => actual == *expected
+                };
+                if compatible {
+                    Ok(())
+                } else {
+                    Err(format!("expected {}, found {} in {}", expected, actual, e.to_sexpr()))
+                }
+            }
+        }
+    }
+}
+
+fn check_def(def: &Def, params: &[Ty], result: &Ty, defs: &HashMap<Id, Signature>) -> Result<(), TypeError> {
+    if def.params.len() != params.len() {
+        return Err(format!("{} should take {} parameter(s), found {}", def.name, params.len(), def.params.len()));
+    }
+    let ctx = Ctx {
+        vars: def.params.iter().cloned().zip(params.iter().cloned()).collect(),
+        defs
+    };
+    ctx.check(&def.body, result).map_err(|e| def.name.to_string() + ": " + &e)
+}
+
+fn check_op_spec(interface: &str, op: &OpSpec, defs: &HashMap<Id, Signature>) -> Result<(), TypeError> {
+    let (args, result) = match op_signature(interface, &op.name) {
+        Some(sig) => sig,
+        None => return Err(format!("{} is not an operation of {}", op.name, interface))
+    };
+    let mut op_params = vec![list()];
+    op_params.extend(args.iter().cloned());
+    check_def(&op.op, &op_params, &result, defs)?;
+    check_def(&op.pre, &[list()], &Ty::Bool, defs)?;
+    let mut post_params = op_params.clone();
+    post_params.push(result);
+    check_def(&op.post, &post_params, &Ty::Bool, defs)
+}
+
+// Type check the specifications of an interface against the signatures of the trait
+pub fn check_interface(spec: &InterfaceSpec) -> Result<(), TypeError> {
+    let expected = match interface_ops(&spec.interface) {
+        Some(ops) => ops,
+        None => return Err("Error, unknown interface: ".to_string() + &spec.interface)
+    };
+    let mut provided: Vec<&str> = spec.ops.iter().map(|op| op.name.as_str()).collect();
+    provided.sort_unstable();
+    if provided != expected {
+        return Err(format!("Error, the specification of {} should provide exactly the operations: {}", spec.interface, expected.join(", ")));
+    }
+    // the definitions which can be called from other definitions of this interface
+    let mut defs = HashMap::<Id, Signature>::new();
+    for op in spec.ops.iter() {
+        if let Some((args, result)) = op_signature(&spec.interface, &op.name) {
+            defs.insert(op.op.name.to_string(), (args, result));
+            defs.insert(op.pre.name.to_string(), (vec![], Ty::Bool));
+        }
+    }
+    for op in spec.ops.iter() {
+        if let Err(e) = check_op_spec(&spec.interface, op, &defs) {
+            return Err("Error, ill-typed specification of ".to_string() + &spec.interface + "::" + &op.name + ", " + &e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lib_spec::{InterfaceSpec, parse_op_spec};
+    use crate::lib_spec::type_check::{check_interface};
+
+    fn stack(pop_code: &str) -> InterfaceSpec {
+        let push = parse_op_spec("push", "op-push", "pre-push", "post-push",
+            "(define (op-push xs x) (append xs (list x)))
+             (define (pre-push xs) #t)
+             (define (post-push xs x ys) (equal? ys (op-push xs x)))").unwrap();
+        let pop = parse_op_spec("pop", "op-pop", "pre-pop", "post-pop", pop_code).unwrap();
+        InterfaceSpec {
+            interface: "Stack".to_string(),
+            ops: vec![push, pop]
+        }
+    }
+
+    #[test]
+    fn test_well_typed_stack() {
+        let spec = stack(
+            "(define (op-pop xs) (cond ((null? xs) (cons xs null)) (else (cons (take xs (- (length xs) 1)) (last xs)))))
+             (define (pre-pop xs) #t)
+             (define (post-pop xs r) (equal? r (op-pop xs)))");
+        assert!(check_interface(&spec).is_ok());
+    }
+
+    #[test]
+    fn test_pop_must_return_the_list() {
+        let spec = stack(
+            "(define (op-pop xs) (last xs))
+             (define (pre-pop xs) #t)
+             (define (post-pop xs r) (equal? r (op-pop xs)))");
+        assert!(check_interface(&spec).is_err());
+    }
+
+    #[test]
+    fn test_post_arity() {
+        let spec = stack(
+            "(define (op-pop xs) (cons xs null))
+             (define (pre-pop xs) #t)
+             (define (post-pop r xs ys) (equal? r (op-pop xs)))");
+        assert!(check_interface(&spec).is_err());
+    }
+}
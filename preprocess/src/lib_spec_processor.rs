@@ -6,20 +6,31 @@ use std::collections::btree_map::Iter;
 //use std::collections::hash_map::Iter;
 use std::collections::HashMap;
 
-use crate::spec_map::{LibSpecs, Bounds, ProvidedOps};
+use crate::spec_map::{LibSpecs, Bounds, ProvidedOps, Costs, Abstraction};
+use crate::lib_spec::{Model, OpSpec, InterfaceSpec, parse_op_spec};
+use crate::lib_spec::type_check::{check_interface};
+use crate::lib_spec::racket::{emit_op_spec};
+use crate::cost::{ReprCosts, OpCosts, parse_repr_costs};
+use crate::run_matching::{LIBDIR};
 
 const LIBSPECNAME: &str = "/*LIBSPEC-NAME*";
 const LIBSPECNAMEEND: &str = "*ENDLIBSPEC-NAME*/";
 const LIBSPEC: &str = "/*LIBSPEC*";
 const LIBSPECEND: &str = "*ENDLIBSPEC*/";
 const LANGDECL: &str = "#lang rosette\n";
-const GENPATH: &str = "./racket_specs/gen_lib_spec/";
 const OPNAME: &str = "/*OPNAME*";
 const OPNAMEEND: &str = "*ENDOPNAME*/";
 const IMPL: &str = "/*IMPL*";
 const IMPLEND: &str = "*ENDIMPL*/";
+const MODEL: &str = "/*MODEL*";
+const MODELEND: &str = "*ENDMODEL*/";
+const COST: &str = "/*COST*";
+const COSTEND: &str = "*ENDCOST*/";
+const ABSTRACTIONTRAIT: &str = "ModelAbstraction";
 
 type ErrorMessage = String;
+// The generated specification file and the struct name of a library file, with what it declares
+type ProcessedLibSpec = (String, String, Bounds, ProvidedOps, Model, Costs, Abstraction);
 
 fn is_next_pragma_impl(src: &String) -> bool {
     match src.find(IMPL) {
@@ -43,7 +54,61 @@ fn has_pragma_spec(src: &String) -> bool {
     src.contains(LIBSPEC)
 }
 
-pub fn read_lib_file(filename : String) -> Result<(String, String, Vec<String>, String, Bounds, ProvidedOps), ErrorMessage> {
+// The model declared by /*MODEL* list | bag | set *ENDMODEL*/, a list if not declared
+fn read_model(src: &str) -> Result<Model, ErrorMessage> {
+    match src.find(MODEL) {
+        Some(start) => {
+            let rest = &src[start + MODEL.len()..];
+            match rest.find(MODELEND) {
+                Some(end) => Model::parse(rest[..end].trim()),
+                None => Err("Error, invalid declaration of the model.".to_string())
+            }
+        },
+        None => Ok(Model::List)
+    }
+}
+
+// The abstraction of the struct, found by its implementation of ModelAbstraction in the
+// library file, e.g. impl<T: Clone> ModelAbstraction<T> for Vec<T>, None if it has none
+fn read_abstraction(src: &str, struct_name: &str) -> Abstraction {
+    let name = struct_name.rsplit("::").next().unwrap_or(struct_name);
+    let implemented = src.match_indices(ABSTRACTIONTRAIT).any(|(n, _)| {
+        let line = &src[src[..n].rfind('\n').map_or(0, |l| l + 1)..n];
+        let rest = &src[n + ABSTRACTIONTRAIT.len()..];
+        let header = &rest[..rest.find('{').unwrap_or(rest.len())];
+        // the type after for, without its parameters and path
+        let target = header.split(" for ").nth(1)
+            .and_then(|t| t.split(|c: char| c == '<' || c.is_whitespace()).find(|t| !t.is_empty()))
+            .map(|t| t.rsplit("::").next().unwrap_or(t));
+        line.trim_start().starts_with("impl") && target == Some(name)
+    });
+    if implemented { Some(ABSTRACTIONTRAIT.to_string()) } else { None }
+}
+
+// The struct name declared by /*LIBSPEC-NAME* <spec name> <struct name> *ENDLIBSPEC-NAME*/
+fn read_struct_name(src: &str) -> Result<String, ErrorMessage> {
+    let start = src.find(LIBSPECNAME).ok_or_else(|| "Error, no library specification name declared.".to_string())?;
+    let rest = &src[start + LIBSPECNAME.len()..];
+    let end = rest.find(LIBSPECNAMEEND).ok_or_else(|| "Error, invalid declaration of library specification name.".to_string())?;
+    rest[..end].split_whitespace().nth(1).map(|n| n.to_string())
+        .ok_or_else(|| "Error, invalid declaration of library specification name.".to_string())
+}
+
+// The costs declared by /*COST* ... *ENDCOST*/ in the text before a specification, if any
+fn read_cost(src: &str) -> Result<Option<ReprCosts>, ErrorMessage> {
+    match src.rfind(COST) {
+        Some(start) => {
+            let rest = &src[start + COST.len()..];
+            match rest.find(COSTEND) {
+                Some(end) => parse_repr_costs(rest[..end].trim()).map(Some),
+                None => Err("Error, invalid declaration of the cost.".to_string())
+            }
+        },
+        None => Ok(None)
+    }
+}
+
+pub fn read_lib_file(filename : String) -> Result<(String, String, Vec<String>, String, Bounds, ProvidedOps, Model, Costs, Abstraction), ErrorMessage> {
     let contents = fs::read_to_string(filename)
         .expect("Something went wrong reading the file");
     let trimed_contents = contents.trim().to_string();
@@ -65,6 +130,8 @@ pub fn read_lib_file(filename : String) -> Result<(String, String, Vec<String>,
         let v3: Vec<&str> = s3.split(" ").collect();
         let spec_name = v3.get(0).unwrap().trim().to_string();
         let struct_name = v3.get(1).unwrap().trim().to_string();
+        let model = read_model(&trimed_contents)?;
+        let abstraction = read_abstraction(&trimed_contents, &struct_name);
         let s1 = v1.get(0).expect("Unexpected error.");
         let s2 = v2.get(1).expect("Unexpected error.");
         // process interface blocks
@@ -75,9 +142,10 @@ pub fn read_lib_file(filename : String) -> Result<(String, String, Vec<String>,
             return Err("Specification without declared interface is not allowed".to_string());
         } else {
             let mut interfaces = Vec::<String>::new();
-            let mut interface_info = HashMap::<String, BTreeMap<String, (String, String, String)>>::new();
+            let mut interface_info = BTreeMap::<String, BTreeMap<String, (String, String, String)>>::new();
             let mut code = Vec::<String>::new();
             let mut provided_ops = Vec::<String>::new();
+            let mut costs = Costs::new();
             while (has_pragma_impl(&trimed_contents)) {
                 let v4: Vec<&str> = trimed_contents.splitn(2, IMPL).collect();
                 let s4 = v4.get(1).expect("Error, invalid interface declaration.");
@@ -86,10 +154,15 @@ pub fn read_lib_file(filename : String) -> Result<(String, String, Vec<String>,
                 trimed_contents = v5.get(1).unwrap().trim().to_string();
                 let lib_specs = extract_lib_specs(trimed_contents);
                 match lib_specs {
-                    Ok((rest, mut v, infos, mut ops)) => {
+                    Ok((rest, mut v, infos, mut ops, op_specs, op_costs)) => {
+                        check_interface(&InterfaceSpec {
+                            interface: interface_name.clone(),
+                            ops: op_specs
+                        })?;
                         code.append(&mut v);
                         interface_info.insert(interface_name.clone(), infos);
                         interfaces.push(interface_name.clone());
+                        costs.insert(interface_name.clone(), op_costs);
                         provided_ops.append(&mut ops);
                         trimed_contents = rest;
                     },
@@ -99,12 +172,30 @@ pub fn read_lib_file(filename : String) -> Result<(String, String, Vec<String>,
                 }
             }
             let (provide, interface_provide_map) = generate_provide(interface_info);
-            Ok((spec_name, struct_name, code.clone(), provide, interface_provide_map, (code, provided_ops)))
+            Ok((spec_name, struct_name, code.clone(), provide, interface_provide_map, (code, provided_ops), model, costs, abstraction))
         }
     }
 }
 
-pub fn generate_provide(interface_info: HashMap<String, BTreeMap<String, (String, String, String)>>) -> (String, Bounds) {
+// The model, the abstraction and the operation specifications of each interface declared in a library file
+pub fn read_interface_specs(filename: &str) -> Result<(Model, Abstraction, Vec<InterfaceSpec>), ErrorMessage> {
+    let contents = fs::read_to_string(filename)
+        .map_err(|_| "Error, unable to read the library file: ".to_string() + filename)?;
+    let model = read_model(&contents)?;
+    let abstraction = read_abstraction(&contents, &read_struct_name(&contents)?);
+    let mut interfaces = Vec::<InterfaceSpec>::new();
+    let mut rest = contents.as_str();
+    while let Some(start) = rest.find(IMPL) {
+        let after = &rest[start + IMPL.len()..];
+        let end = after.find(IMPLEND).ok_or_else(|| "Error, invalid interface declaration.".to_string())?;
+        let (_, _, _, _, ops, _) = extract_lib_specs(after[end + IMPLEND.len()..].to_string())?;
+        interfaces.push(InterfaceSpec { interface: after[..end].trim().to_string(), ops });
+        rest = &after[end..];
+    }
+    Ok((model, abstraction, interfaces))
+}
+
+pub fn generate_provide(interface_info: BTreeMap<String, BTreeMap<String, (String, String, String)>>) -> (String, Bounds) {
     let mut interfaces = Vec::<String>::new();
     let mut provide = String::new();
     let mut interface_provide_map = Bounds::new();
@@ -131,29 +222,34 @@ pub fn generate_provide(interface_info: HashMap<String, BTreeMap<String, (String
     (provide, interface_provide_map)
 }
 
-pub fn extract_lib_specs(src: String) -> Result<(String, Vec<String>, BTreeMap<String, (String, String, String)>, Vec<String>), ErrorMessage> {
+pub fn extract_lib_specs(src: String) -> Result<(String, Vec<String>, BTreeMap<String, (String, String, String)>, Vec<String>, Vec<OpSpec>, OpCosts), ErrorMessage> {
     let mut result = Vec::<String>::new();
+    let mut op_specs = Vec::<OpSpec>::new();
     let mut contents = src.trim();
     let mut op_infos = BTreeMap::<String, (String, String, String)>::new();
     let mut provided_ops = Vec::<String>::new();
+    let mut costs = OpCosts::new();
     while (contents.len() > 0 && !is_next_pragma_impl(&contents.to_string())) {
         if (contents.contains(LIBSPEC) && contents.contains(LIBSPECEND)) {
             let v1: Vec<&str> = contents.splitn(2, LIBSPEC).collect();
             let s = v1.get(1).expect("Error, invalid specification.");
             let v2: Vec<&str> = s.splitn(2, LIBSPECEND).collect();
             let spec = v2.get(0).unwrap().trim().to_string();
-            let info = extract_op_info(spec.clone()).unwrap();
+            let info = extract_op_info(spec.clone())?;
+            costs.insert(info.0.clone(), read_cost(v1.first().unwrap())?);
+            let v3: Vec<&str> = spec.splitn(2, OPNAMEEND).collect();
+            let code = v3.get(1).unwrap();
+            let op_spec = parse_op_spec(&info.0, &info.1, &info.2, &info.3, code)?;
+            result.push(emit_op_spec(&op_spec));
+            op_specs.push(op_spec);
             op_infos.insert(info.0, (info.1.clone(), info.2, info.3));
             provided_ops.push(info.1);
-            let v3: Vec<&str> = spec.splitn(2, OPNAMEEND).collect();
-            let code = v3.get(1).unwrap().trim_matches(|c| c == '\t' || c == ' ').to_string();
-            result.push(code);
             contents = v2.get(1).unwrap().trim();
         } else {
             break;
         }
     }
-    Ok((contents.to_string(), result, op_infos, provided_ops))
+    Ok((contents.to_string(), result, op_infos, provided_ops, op_specs, costs))
 }
 
 pub fn extract_op_info(spec: String) -> Result<(String, String, String, String), ErrorMessage> {
@@ -179,10 +275,10 @@ pub fn extract_op_info(spec: String) -> Result<(String, String, String, String),
     }
 }
 
-pub fn write_lib_file(filename : String, contents: Vec<String>, provide: String) -> Result<(), Error> {
-    let path = GENPATH;
+pub fn write_lib_file(work_dir: &str, filename : String, contents: Vec<String>, provide: String) -> Result<(), Error> {
+    let path = work_dir.to_owned() + LIBDIR;
 
-    let mut output = fs::File::create(path.to_owned() + &filename)?;
+    let mut output = fs::File::create(path + &filename)?;
     write!(output, "{}", LANGDECL.to_string())?;
     for i in 0..contents.len() {
         write!(output, "{}", contents[i])?;
@@ -191,32 +287,36 @@ pub fn write_lib_file(filename : String, contents: Vec<String>, provide: String)
     Ok(())
 }
 
-pub fn process_lib_spec(filename: String) -> Result<(String, String, Bounds, ProvidedOps), ErrorMessage> {
+pub fn process_lib_spec(filename: String, work_dir: &str) -> Result<ProcessedLibSpec, ErrorMessage> {
     let result = read_lib_file(filename);
     match result {
-        Ok((name, struct_name, specs, provide, interface_provide_map, provided_ops)) => {
+        Ok((name, struct_name, specs, provide, interface_provide_map, provided_ops, model, costs, abstraction)) => {
             let spec_name = name + ".rkt";
-            let state = write_lib_file(spec_name.clone(), specs, provide);
+            let state = write_lib_file(work_dir, spec_name.clone(), specs, provide);
             if (!state.is_ok()) {
                 return Err("Unable to create lib specification file".to_string());
             }
-            Ok((spec_name, struct_name, interface_provide_map, provided_ops))
+            Ok((spec_name, struct_name, interface_provide_map, provided_ops, model, costs, abstraction))
         },
         Err(e) => Err(e)
     }
 }
 
-pub fn process_lib_specs(dirname: String) -> Result<LibSpecs, ErrorMessage> {
-    let paths = fs::read_dir(dirname).unwrap();
+// Process the library files of the directory, writing their specifications under the working directory
+pub fn process_lib_specs(dirname: String, work_dir: &str) -> Result<LibSpecs, ErrorMessage> {
+    let paths = match fs::read_dir(&dirname) {
+        Ok(paths) => paths,
+        Err(_) => return Err("Error, unable to read the library directory: ".to_string() + &dirname)
+    };
     let files : Vec<String> = paths.into_iter()
                                         .map(|path| path.unwrap().path().to_str().unwrap().to_string())
                                         .filter(|path| !path.contains("/mod.rs"))
                                         .collect();
     let mut lib_specs = LibSpecs::new();
     for path in files {
-        match process_lib_spec(path) {
-            Ok((spec_name, struct_name, interface_provide_map, provided_ops)) => {
-                lib_specs.insert(struct_name, (spec_name, interface_provide_map, provided_ops));
+        match process_lib_spec(path, work_dir) {
+            Ok((spec_name, struct_name, interface_provide_map, provided_ops, model, costs, abstraction)) => {
+                lib_specs.insert(struct_name, (spec_name, interface_provide_map, provided_ops, model, costs, abstraction));
             },
             Err(e) => {
                 return Err(e);
@@ -226,6 +326,22 @@ pub fn process_lib_specs(dirname: String) -> Result<LibSpecs, ErrorMessage> {
     Ok(lib_specs)
 }
 
+#[cfg(test)]
+mod abstraction_tests {
+    use crate::lib_spec_processor::{read_abstraction, read_interface_specs};
+
+    #[test]
+    fn test_read_abstraction() {
+        assert_eq!(read_abstraction("impl<T: Clone> ModelAbstraction<T> for Vec<T> {}", "std::vec::Vec"), Some("ModelAbstraction".to_string()));
+        assert_eq!(read_abstraction("impl<T> ModelAbstraction<T> for Vec<T> where T: Clone {}", "std::vec::Vec"), Some("ModelAbstraction".to_string()));
+        assert_eq!(read_abstraction("impl<T: Clone> ModelAbstraction<T> for VecDeque<T> {}", "std::vec::Vec"), None);
+        assert_eq!(read_abstraction("use crate::traits::{Container, ModelAbstraction};\nimpl<T> Container<T> for Vec<T> {}", "std::vec::Vec"), None);
+        let (_, abstraction, interfaces) = read_interface_specs("./src/library/list.rs").unwrap();
+        assert_eq!(abstraction, Some("ModelAbstraction".to_string()));
+        assert_eq!(interfaces.iter().map(|i| i.interface.as_str()).collect::<Vec<&str>>(), vec!["Container", "Stack", "RandomAccess"]);
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use crate::lib_spec_processor::{read_lib_file};
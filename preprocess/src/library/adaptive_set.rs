@@ -0,0 +1,482 @@
+/*LIBSPEC-NAME*
+rust-adaptive-set-spec preprocess::library::adaptive_set::AdaptiveSet
+*ENDLIBSPEC-NAME*/
+
+use std::collections::BTreeSet;
+use std::iter::FromIterator;
+use std::mem;
+use crate::traits::{Container, RandomAccess, ModelAbstraction};
+use crate::proptest::*;
+
+use proptest::prelude::*;
+use crate::proptest::strategies::{adaptive_set};
+
+use im::conslist::{ConsList};
+use im::conslist;
+use std::sync::Arc;
+
+// An ordered set switching between two representations of the same sorted unique list:
+// a sorted vector, with O(1) nth but O(n) insert and remove, while it is small or mostly
+// indexed, and a B-tree, with O(log n) insert and remove but O(n) nth, once it is large
+// and mostly updated. The operation mix is checked every window operations; migrating
+// costs O(n), and the gap between the small and large sizes keeps it from thrashing.
+// The costs are declared in both representations, sorted vector | B-tree, so that the
+// ranking costs it in the one a workload settles it in.
+const SMALL: usize = 512;
+const LARGE: usize = 2048;
+const WINDOW: usize = 256;
+
+#[derive(Debug, Clone)]
+enum Repr<T> {
+    Sorted(Vec<T>),
+    Tree(BTreeSet<T>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Update,
+    Index,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct AdaptiveSet<T> {
+    repr: Repr<T>,
+    small: usize,   // at most this size the sorted vector is used
+    large: usize,   // from this size the B-tree is used, unless mostly indexed
+    window: usize,
+    ops: usize,     // the number of operations in the current window
+    updates: usize, // inserts and removes in the current window
+    indexes: usize, // first, last and nth in the current window
+}
+
+impl<T: Ord> AdaptiveSet<T> {
+    pub fn new() -> AdaptiveSet<T> {
+        AdaptiveSet::with_thresholds(SMALL, LARGE, WINDOW)
+    }
+
+    pub fn with_thresholds(small: usize, large: usize, window: usize) -> AdaptiveSet<T> {
+        assert!(small < large && window > 0, "the small size must be below the large size");
+        AdaptiveSet { repr: Repr::Sorted(Vec::new()), small, large, window, ops: 0, updates: 0, indexes: 0 }
+    }
+
+    pub fn from_vec(v: Vec<T>) -> AdaptiveSet<T> {
+        let mut s = AdaptiveSet::new();
+        for x in v {
+            s.insert(x);
+        }
+        s
+    }
+
+    // Whether the elements are currently held in the B-tree
+    pub fn is_tree(&self) -> bool {
+        matches!(self.repr, Repr::Tree(_))
+    }
+
+    fn size(&self) -> usize {
+        match &self.repr {
+            Repr::Sorted(v) => v.len(),
+            Repr::Tree(t) => t.len(),
+        }
+    }
+
+    // Count the operation, and at the end of a window migrate if the size or the
+    // operation mix has crossed a threshold
+    fn adapt(&mut self, op: Op) {
+        match op {
+            Op::Update => self.updates += 1,
+            Op::Index => self.indexes += 1,
+            Op::Other => {}
+        }
+        self.ops += 1;
+        if self.ops < self.window {
+            return;
+        }
+        let size = self.size();
+        let to_tree = match self.repr {
+            Repr::Sorted(_) => size >= self.large && self.updates >= self.indexes,
+            Repr::Tree(_) => !(size <= self.small || self.indexes > 2 * self.updates),
+        };
+        if to_tree != self.is_tree() {
+            self.repr = match mem::replace(&mut self.repr, Repr::Sorted(Vec::new())) {
+                Repr::Sorted(v) => Repr::Tree(v.into_iter().collect()),
+                Repr::Tree(t) => Repr::Sorted(t.into_iter().collect()),
+            };
+        }
+        self.ops = 0;
+        self.updates = 0;
+        self.indexes = 0;
+    }
+
+    pub fn len(&mut self) -> usize {
+        self.adapt(Op::Other);
+        self.size()
+    }
+
+    pub fn contains(&mut self, x: &T) -> bool {
+        self.adapt(Op::Other);
+        match &self.repr {
+            Repr::Sorted(v) => v.binary_search(x).is_ok(),
+            Repr::Tree(t) => t.contains(x),
+        }
+    }
+
+    pub fn is_empty(&mut self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn insert(&mut self, x: T) {
+        self.adapt(Op::Update);
+        match &mut self.repr {
+            Repr::Sorted(v) => {
+                if let Err(i) = v.binary_search(&x) {
+                    v.insert(i, x);
+                }
+            },
+            Repr::Tree(t) => {
+                t.insert(x);
+            },
+        }
+    }
+
+    pub fn remove(&mut self, x: T) -> Option<T> {
+        self.adapt(Op::Update);
+        match &mut self.repr {
+            Repr::Sorted(v) => v.binary_search(&x).ok().map(|i| v.remove(i)),
+            Repr::Tree(t) => t.take(&x),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.repr = Repr::Sorted(Vec::new());
+        self.ops = 0;
+        self.updates = 0;
+        self.indexes = 0;
+    }
+
+    pub fn first(&mut self) -> Option<&T> {
+        self.adapt(Op::Index);
+        match &self.repr {
+            Repr::Sorted(v) => v.as_slice().first(),
+            Repr::Tree(t) => t.first(),
+        }
+    }
+
+    pub fn last(&mut self) -> Option<&T> {
+        self.adapt(Op::Index);
+        match &self.repr {
+            Repr::Sorted(v) => v.as_slice().last(),
+            Repr::Tree(t) => t.last(),
+        }
+    }
+
+    pub fn nth(&mut self, n: usize) -> Option<&T> {
+        self.adapt(Op::Index);
+        match &self.repr {
+            Repr::Sorted(v) => v.get(n),
+            Repr::Tree(t) => t.iter().nth(n),
+        }
+    }
+
+    pub fn to_vec(self) -> Vec<T> {
+        match self.repr {
+            Repr::Sorted(v) => v,
+            Repr::Tree(t) => t.into_iter().collect(),
+        }
+    }
+}
+
+impl<T: Ord> Default for AdaptiveSet<T> {
+    fn default() -> Self {
+        AdaptiveSet::new()
+    }
+}
+
+/*IMPL*
+Container
+*ENDIMPL*/
+impl<T: Ord> Container<T> for AdaptiveSet<T> {
+
+    /*COST* O(1) *ENDCOST*/
+    /*LIBSPEC*
+    /*OPNAME*
+    len op-len pre-len post-len
+    *ENDOPNAME*/
+    (define (op-len xs) (cons xs (length xs)))
+    (define (pre-len xs) (equal? xs (remove-duplicates (sort xs <))))
+    (define (post-len xs r) (equal? r (op-len xs)))
+    *ENDLIBSPEC*/
+    fn len(&mut self) -> usize {
+        AdaptiveSet::len(self)
+    }
+
+    /*COST* O(log n) *ENDCOST*/
+    /*LIBSPEC*
+    /*OPNAME*
+    contains op-contains pre-contains post-contains
+    *ENDOPNAME*/
+    (define (op-contains xs x)
+      (cond
+        [(list? (member x xs)) (cons xs #t)]
+        [else (cons xs #f)]))
+    (define (pre-contains xs) (equal? xs (remove-duplicates (sort xs <))))
+    (define (post-contains xs x r) (equal? r (op-contains xs x)))
+    *ENDLIBSPEC*/
+    fn contains(&mut self, x: &T) -> bool {
+        AdaptiveSet::contains(self, x)
+    }
+
+    /*COST* O(1) *ENDCOST*/
+    /*LIBSPEC*
+    /*OPNAME*
+    is-empty op-is-empty pre-is-empty post-is-empty
+    *ENDOPNAME*/
+    (define (op-is-empty xs) (cons xs (null? xs)))
+    (define (pre-is-empty xs) (equal? xs (remove-duplicates (sort xs <))))
+    (define (post-is-empty xs r) (equal? r (op-is-empty xs)))
+    *ENDLIBSPEC*/
+    fn is_empty(&mut self) -> bool {
+        AdaptiveSet::is_empty(self)
+    }
+
+    /*COST* O(n) *ENDCOST*/
+    /*LIBSPEC*
+    /*OPNAME*
+    clear op-clear pre-clear post-clear 
+    *ENDOPNAME*/
+    (define (op-clear xs) null)
+    (define (pre-clear xs) (equal? xs (remove-duplicates (sort xs <))))
+    (define (post-clear xs r) (equal? r (op-clear xs)))
+    *ENDLIBSPEC*/
+    fn clear(&mut self) {
+        AdaptiveSet::clear(self);
+    }
+
+    /*COST* O(n) | O(log n) *ENDCOST*/
+    /*LIBSPEC*
+    /*OPNAME*
+    insert op-insert pre-insert post-insert
+    *ENDOPNAME*/
+    (define (op-insert xs x) (remove-duplicates (sort (append xs (list x)) <)))
+    (define (pre-insert xs) (equal? xs (remove-duplicates (sort xs <))))
+    (define (post-insert xs x ys) (equal? ys (op-insert xs x)))
+    *ENDLIBSPEC*/
+    fn insert(&mut self, elt: T) {
+        AdaptiveSet::insert(self, elt);
+    }
+
+    /*COST* O(n) | O(log n) *ENDCOST*/
+    /*LIBSPEC*
+    /*OPNAME*
+    remove op-remove pre-remove post-remove
+    *ENDOPNAME*/
+    (define (op-remove xs x)
+      (cond
+        [(list? (member x xs)) (cons (remove x xs) x)]
+        [else (cons xs null)]))
+    (define (pre-remove xs) (equal? xs (remove-duplicates (sort xs <))))
+    (define (post-remove xs x r) (equal? r (op-remove xs x)))
+    *ENDLIBSPEC*/
+    fn remove(&mut self, elt: T) -> Option<T> {
+        AdaptiveSet::remove(self, elt)
+    }
+}
+
+/*IMPL*
+RandomAccess
+*ENDIMPL*/
+impl<T: Ord> RandomAccess<T> for AdaptiveSet<T> {
+    /*COST* O(1) | O(log n) *ENDCOST*/
+    /*LIBSPEC*
+    /*OPNAME*
+    first op-first pre-first post-first
+    *ENDOPNAME*/
+    (define (op-first xs)
+      (cond
+        [(null? xs) (cons xs null)]
+        [else (cons xs (first xs))]))
+    (define (pre-first xs) (equal? xs (remove-duplicates (sort xs <))))
+    (define (post-first xs r) (equal? r (op-first xs)))
+    *ENDLIBSPEC*/
+    fn first(&mut self) -> Option<&T> {
+        AdaptiveSet::first(self)
+    }
+
+    /*COST* O(1) | O(log n) *ENDCOST*/
+    /*LIBSPEC*
+    /*OPNAME*
+    last op-last pre-last post-last
+    *ENDOPNAME*/
+    (define (op-last xs)
+      (cond
+        [(null? xs) (cons xs null)]
+        [else (cons xs (last xs))]))
+    (define (pre-last xs) (equal? xs (remove-duplicates (sort xs <))))
+    (define (post-last xs r) (equal? r (op-last xs)))
+    *ENDLIBSPEC*/
+    fn last(&mut self) -> Option<&T> {
+        AdaptiveSet::last(self)
+    }
+
+    /*COST* O(1) | O(n) *ENDCOST*/
+    /*LIBSPEC*
+    /*OPNAME*
+    nth op-nth pre-nth post-nth
+    *ENDOPNAME*/
+    (define (op-nth xs n)
+      (cond
+        [(>= n (length xs)) (cons xs null)]
+        [(< n 0) (cons xs null)]
+        [else (cons xs (list-ref xs n))]))
+    (define (pre-nth xs) (equal? xs (remove-duplicates (sort xs <))))
+    (define (post-nth xs n r) (equal? r (op-nth xs n)))
+    *ENDLIBSPEC*/
+    fn nth(&mut self, n: usize) -> Option<&T> {
+        AdaptiveSet::nth(self, n)
+    }
+}
+
+impl<T: Ord + Clone> ModelAbstraction<T> for AdaptiveSet<T> {
+    fn abstraction(&self) -> ConsList<T> {
+        ConsList::from_iter(self.clone().to_vec())
+    }
+}
+
+proptest!{
+    #![proptest_config(ProptestConfig {
+        cases: 100, .. ProptestConfig::default()
+      })]
+    #[test]
+    fn test_adaptive_set_len(ref mut s in adaptive_set(".*", 0..100)) {
+        let abs_list = s.abstraction();
+        // pre: our list model is a sorted and unique list
+        assert_eq!(abs_list, unique(&abs_list.sort()));
+        //post
+        assert_eq!(Container::<String>::len(s), abs_list.len());
+        assert_eq!(s.abstraction(), abs_list);
+    }
+
+    #[test]
+    fn test_adaptive_set_contains(ref mut s in adaptive_set(".*", 0..100), a in ".*") {
+        let abs_list = s.abstraction();
+        //pre
+        assert_eq!(abs_list, unique(&abs_list.sort()));
+        //post
+        assert_eq!(Container::<String>::contains(s, &a), contains(&abs_list, &a));
+        assert_eq!(s.abstraction(), abs_list);
+    }
+
+    #[test]
+    fn test_adaptive_set_is_empty(ref mut s in adaptive_set(".*", 0..100)) {
+        let abs_list = s.abstraction();
+        //pre
+        assert_eq!(abs_list, unique(&abs_list.sort()));
+        //post
+        assert_eq!(Container::<String>::is_empty(s), abs_list.is_empty());
+        assert_eq!(s.abstraction(), abs_list);
+    }
+
+    #[test]
+    fn test_adaptive_set_insert(ref mut s in adaptive_set(".*", 0..100), a in ".*") {
+        let abs_list = s.abstraction();
+        //pre
+        assert_eq!(abs_list, unique(&abs_list.sort()));
+        //post
+        let after_list = unique(&abs_list.append(conslist![a.clone()]).sort());
+        Container::<String>::insert(s, a.clone());
+        assert_eq!(s.abstraction(), after_list);
+    }
+
+    #[test]
+    fn test_adaptive_set_clear(ref mut s in adaptive_set(".*", 0..100)) {
+        let abs_list = s.abstraction();
+        //pre
+        assert_eq!(abs_list, unique(&abs_list.sort()));
+        //post
+        let after_list = clear(&abs_list);
+        Container::<String>::clear(s);
+        assert_eq!(s.abstraction(), after_list);
+    }
+
+    #[test]
+    fn test_adaptive_set_remove(ref mut s in adaptive_set(".*", 0..100), a in ".*") {
+        let abs_list = s.abstraction();
+        //pre
+        assert_eq!(abs_list, unique(&abs_list.sort()));
+        //post
+        let (after_list, abs_elem) = remove(&abs_list, a.clone());
+        let elem = Container::<String>::remove(s, a.clone());
+        assert_eq!(s.abstraction(), after_list);
+        assert_eq!(elem, abs_elem);
+    }
+
+    #[test]
+    fn test_adaptive_set_first(ref mut s in adaptive_set(".*", 0..100)) {
+        let abs_list = s.abstraction();
+        //pre
+        assert_eq!(abs_list, unique(&abs_list.sort()));
+        //post
+        let elem = RandomAccess::<String>::first(s);
+        let abs_first = first(&abs_list);
+        assert_eq!(elem, abs_first);
+        assert_eq!(s.abstraction(), abs_list);
+    }
+
+    #[test]
+    fn test_adaptive_set_last(ref mut s in adaptive_set(".*", 0..100)) {
+        let abs_list = s.abstraction();
+        //pre
+        assert_eq!(abs_list, unique(&abs_list.sort()));
+        //post
+        let elem = RandomAccess::<String>::last(s);
+        let abs_last = last(&abs_list);
+        assert_eq!(elem, abs_last);
+        assert_eq!(s.abstraction(), abs_list);
+    }
+
+    #[test]
+    fn test_adaptive_set_nth(ref mut s in adaptive_set(".*", 0..100), n in 0usize..100) {
+        let abs_list = s.abstraction();
+        //pre
+        assert_eq!(abs_list, unique(&abs_list.sort()));
+        //post
+        let elem = RandomAccess::<String>::nth(s, n);
+        let abs_nth = nth(&abs_list, n);
+        assert_eq!(elem, abs_nth);
+        assert_eq!(s.abstraction(), abs_list);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::library::adaptive_set::{AdaptiveSet};
+    use crate::traits::{Container, RandomAccess};
+
+    #[test]
+    fn test_adaptive_set_migrates() {
+        let mut s = AdaptiveSet::<u32>::with_thresholds(8, 32, 4);
+        for x in (0..100).rev() {
+            s.insert(x);
+        }
+        // large and mostly updated
+        assert!(s.is_tree());
+        for n in 0..8 {
+            assert_eq!(s.nth(n), Some(&(n as u32)));
+        }
+        // mostly indexed
+        assert!(!s.is_tree());
+        for x in 0..100 {
+            s.insert(x + 100);
+        }
+        assert!(s.is_tree());
+        for x in 0..195 {
+            assert_eq!(s.remove(x), Some(x));
+        }
+        // small again, by the end of the window
+        for _ in 0..4 {
+            assert_eq!(s.len(), 5);
+        }
+        assert!(!s.is_tree());
+        assert_eq!(s.first(), Some(&195));
+    }
+}
@@ -5,7 +5,7 @@ rust-eager-sorted-vec-spec preprocess::library::eager_sorted_vector::EagerSorted
 use std::vec::Vec;
 use std::slice::Iter;
 use std::ops::Deref;
-use crate::traits::{Container, Stack, RandomAccess};
+use crate::traits::{Container, Stack, RandomAccess, ModelAbstraction};
 use std::iter::FromIterator;
 
 use proptest::prelude::*;
@@ -88,6 +88,7 @@ Container
 *ENDIMPL*/
 impl<T: Ord> Container<T> for EagerSortedVec<T> {
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     len op-len pre-len post-len
@@ -100,6 +101,7 @@ impl<T: Ord> Container<T> for EagerSortedVec<T> {
         EagerSortedVec::len(self)
     }
 
+    /*COST* O(log n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     contains op-contains pre-contains post-contains
@@ -115,6 +117,7 @@ impl<T: Ord> Container<T> for EagerSortedVec<T> {
         EagerSortedVec::contains(self, x)
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     is-empty op-is-empty pre-is-empty post-is-empty
@@ -127,6 +130,7 @@ impl<T: Ord> Container<T> for EagerSortedVec<T> {
         EagerSortedVec::is_empty(self)
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     clear op-clear pre-clear post-clear 
@@ -139,6 +143,7 @@ impl<T: Ord> Container<T> for EagerSortedVec<T> {
         EagerSortedVec::clear(self);
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     insert op-insert pre-insert post-insert
@@ -151,6 +156,7 @@ impl<T: Ord> Container<T> for EagerSortedVec<T> {
         EagerSortedVec::push(self, elt);
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     remove op-remove pre-remove post-remove
@@ -160,7 +166,7 @@ impl<T: Ord> Container<T> for EagerSortedVec<T> {
         [(list? (member x xs)) (cons (remove x xs) x)]
         [else (cons xs null)]))
     (define (pre-remove xs) (equal? xs (sort xs <)))
-    (define (post-remove xs r) (equal? r (op-remove xs)))
+    (define (post-remove xs x r) (equal? r (op-remove xs x)))
     *ENDLIBSPEC*/
     fn remove(&mut self, elt: T) -> Option<T> {
         match self.iter().position(|x| *x == elt) {
@@ -176,6 +182,7 @@ impl<T: Ord> Container<T> for EagerSortedVec<T> {
 RandomAccess
 *ENDIMPL*/
 impl<T: Ord> RandomAccess<T> for EagerSortedVec<T> {
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     first op-first pre-first post-first
@@ -191,6 +198,7 @@ impl<T: Ord> RandomAccess<T> for EagerSortedVec<T> {
         EagerSortedVec::first(self)
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     last op-last pre-last post-last
@@ -206,6 +214,7 @@ impl<T: Ord> RandomAccess<T> for EagerSortedVec<T> {
         EagerSortedVec::last(self)
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     nth op-nth pre-nth post-nth
@@ -223,11 +232,10 @@ impl<T: Ord> RandomAccess<T> for EagerSortedVec<T> {
     }                                      
 }
 
-fn abstraction<T>(v: EagerSortedVec<T>) -> ConsList<T>
-where T: Ord
-{
-    let list: ConsList<T> = ConsList::from(v.to_vec());
-    list
+impl<T: Ord + Clone> ModelAbstraction<T> for EagerSortedVec<T> {
+    fn abstraction(&self) -> ConsList<T> {
+        ConsList::from(self.clone().to_vec())
+    }
 }
 
 proptest! {
@@ -237,101 +245,101 @@ proptest! {
 
     #[test]
     fn test_eager_sorted_vec_len(ref mut v in eager_sorted_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, abs_list.sort());
         //post
         assert_eq!(Container::<String>::len(v), abs_list.len());
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_eager_sorted_vec_contains(ref mut v in eager_sorted_vec(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, abs_list.sort());
         //post
         assert_eq!(Container::<String>::contains(v, &a), contains(&abs_list, &a));
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_eager_sorted_vec_is_empty(ref mut v in eager_sorted_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, abs_list.sort());
         //post
         assert_eq!(Container::<String>::is_empty(v), abs_list.is_empty());
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_eager_sorted_vec_insert(ref mut v in eager_sorted_vec(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, abs_list.sort());
         //post
         let after_list = abs_list.append(conslist![a.clone()]).sort();
         Container::<String>::insert(v, a.clone());
-        assert_eq!(abstraction(v.clone()), after_list);
+        assert_eq!(v.abstraction(), after_list);
     }
 
     #[test]
     fn test_eager_sorted_vec_clear(ref mut v in eager_sorted_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, abs_list.sort());
         //post
         let after_list = clear(&abs_list);
         Container::<String>::clear(v);
-        assert_eq!(abstraction(v.clone()), after_list);
+        assert_eq!(v.abstraction(), after_list);
     }
 
     #[test]
     fn test_eager_sorted_vec_remove(ref mut v in eager_sorted_vec(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, abs_list.sort());
         //post
         let (after_list, abs_elem) = remove(&abs_list, a.clone());
         let elem = Container::<String>::remove(v, a.clone());
-        assert_eq!(abstraction(v.clone()), after_list);
+        assert_eq!(v.abstraction(), after_list);
         assert_eq!(elem, abs_elem);
     }
 
     #[test]
     fn test_eager_sorted_vec_first(ref mut v in eager_sorted_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, abs_list.sort());
         //post
         let elem = RandomAccess::<String>::first(v);
         let abs_first = first(&abs_list);
         assert_eq!(elem, abs_first);
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_eager_sorted_vec_last(ref mut v in eager_sorted_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, abs_list.sort());
         //post
         let elem = RandomAccess::<String>::last(v);
         let abs_last = last(&abs_list);
         assert_eq!(elem, abs_last);
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_eager_sorted_vec_nth(ref mut v in eager_sorted_vec(".*", 0..100), n in 0usize..100) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, abs_list.sort());
         //post
         let elem = RandomAccess::<String>::nth(v, n.clone());
         let abs_nth = nth(&abs_list, n);
         assert_eq!(elem, abs_nth);
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 }
\ No newline at end of file
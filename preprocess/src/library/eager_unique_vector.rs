@@ -5,7 +5,7 @@ rust-eager-unique-vec-spec preprocess::library::eager_unique_vector::EagerUnique
 use std::vec::Vec;
 use std::slice::Iter;
 use std::ops::Deref;
-use crate::traits::{Container, Stack, RandomAccess};
+use crate::traits::{Container, Stack, RandomAccess, ModelAbstraction};
 use std::iter::FromIterator;
 
 use proptest::prelude::*;
@@ -90,6 +90,7 @@ Container
 *ENDIMPL*/
 impl<T: PartialEq> Container<T> for EagerUniqueVec<T> {
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     len op-len pre-len post-len
@@ -102,6 +103,7 @@ impl<T: PartialEq> Container<T> for EagerUniqueVec<T> {
         EagerUniqueVec::len(self)
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     contains op-contains pre-contains post-contains
@@ -117,6 +119,7 @@ impl<T: PartialEq> Container<T> for EagerUniqueVec<T> {
         EagerUniqueVec::contains(self, x) // use fully qualified syntax to avoid function name collision
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     is-empty op-is-empty pre-is-empty post-is-empty
@@ -129,6 +132,7 @@ impl<T: PartialEq> Container<T> for EagerUniqueVec<T> {
         EagerUniqueVec::is_empty(self)
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     clear op-clear pre-clear post-clear 
@@ -141,6 +145,7 @@ impl<T: PartialEq> Container<T> for EagerUniqueVec<T> {
         EagerUniqueVec::clear(self);
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     insert op-insert pre-insert post-insert
@@ -153,6 +158,7 @@ impl<T: PartialEq> Container<T> for EagerUniqueVec<T> {
         EagerUniqueVec::push(self, elt);
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     remove op-remove pre-remove post-remove
@@ -162,7 +168,7 @@ impl<T: PartialEq> Container<T> for EagerUniqueVec<T> {
         [(list? (member x xs)) (cons (remove x xs) x)]
         [else (cons xs null)]))
     (define (pre-remove xs) (equal? xs (remove-duplicates xs)))
-    (define (post-remove xs r) (equal? r (op-remove xs)))
+    (define (post-remove xs x r) (equal? r (op-remove xs x)))
     *ENDLIBSPEC*/
     fn remove(&mut self, elt: T) -> Option<T> {
         match self.iter().position(|x| *x == elt) {
@@ -178,6 +184,7 @@ impl<T: PartialEq> Container<T> for EagerUniqueVec<T> {
 RandomAccess
 *ENDIMPL*/
 impl<T: PartialEq> RandomAccess<T> for EagerUniqueVec<T> {
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     first op-first pre-first post-first
@@ -193,6 +200,7 @@ impl<T: PartialEq> RandomAccess<T> for EagerUniqueVec<T> {
         EagerUniqueVec::first(self)
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     last op-last pre-last post-last
@@ -208,6 +216,7 @@ impl<T: PartialEq> RandomAccess<T> for EagerUniqueVec<T> {
         EagerUniqueVec::last(self)
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     nth op-nth pre-nth post-nth
@@ -225,11 +234,10 @@ impl<T: PartialEq> RandomAccess<T> for EagerUniqueVec<T> {
     }                                      
 }
 
-fn abstraction<T>(v: EagerUniqueVec<T>) -> ConsList<T>
-where T: PartialEq
-{
-    let list: ConsList<T> = ConsList::from(v.to_vec());
-    list
+impl<T: PartialEq + Clone> ModelAbstraction<T> for EagerUniqueVec<T> {
+    fn abstraction(&self) -> ConsList<T> {
+        ConsList::from(self.clone().to_vec())
+    }
 }
 
 proptest! {
@@ -239,102 +247,102 @@ proptest! {
     
     #[test]
     fn test_eager_unique_vec_len(ref mut v in eager_unique_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list));
         //post
         assert_eq!(Container::<String>::len(v), abs_list.len());
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_eager_unique_vec_contains(ref mut v in eager_unique_vec(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list));
         //post
         assert_eq!(Container::<String>::contains(v, &a), contains(&abs_list, &a));
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_eager_unique_vec_is_empty(ref mut v in eager_unique_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list));
         //post
         assert_eq!(Container::<String>::is_empty(v), abs_list.is_empty());
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_eager_unique_vec_insert(ref mut v in eager_unique_vec(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list));
         //post
         let after_list = unique(&abs_list.append(conslist![a.clone()]));
         Container::<String>::insert(v, a.clone());
-        assert_eq!(abstraction(v.clone()), after_list);
+        assert_eq!(v.abstraction(), after_list);
     }
 
     #[test]
     fn test_eager_unique_vec_clear(ref mut v in eager_unique_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list));
         //post
         let after_list = clear(&abs_list);
         Container::<String>::clear(v);
-        assert_eq!(abstraction(v.clone()), after_list);
+        assert_eq!(v.abstraction(), after_list);
     }
 
     #[test]
     fn test_eager_unique_vec_remove(ref mut v in eager_unique_vec(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list));
         //post
         let (after_list, abs_elem) = remove(&abs_list, a.clone());
         let elem = Container::<String>::remove(v, a.clone());
-        assert_eq!(abstraction(v.clone()), after_list);
+        assert_eq!(v.abstraction(), after_list);
         assert_eq!(elem, abs_elem);
     }
 
     #[test]
     fn test_eager_unique_vec_first(ref mut v in eager_unique_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list));
         //post
         let elem = RandomAccess::<String>::first(v);
         let abs_first = first(&abs_list);
         assert_eq!(elem, abs_first);
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_eager_unique_vec_last(ref mut v in eager_unique_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list));
         //post
         let elem = RandomAccess::<String>::last(v);
         let abs_last = last(&abs_list);
         assert_eq!(elem, abs_last);
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_eager_unique_vec_nth(ref mut v in eager_unique_vec(".*", 0..100), n in 0usize..100) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list));
         //post
         let elem = RandomAccess::<String>::nth(v, n.clone());
         let abs_nth = nth(&abs_list, n);
         assert_eq!(elem, abs_nth);
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 }
 
@@ -5,7 +5,7 @@ rust-lazy-unique-vec-spec preprocess::library::lazy_unique_vector::LazyUniqueVec
 use std::vec::Vec;
 use std::slice::Iter;
 use std::ops::Deref;
-use crate::traits::{Container, Stack, RandomAccess};
+use crate::traits::{Container, Stack, RandomAccess, ModelAbstraction};
 
 use proptest::prelude::*;
 use crate::proptest::strategies::{lazy_unique_vec};
@@ -128,6 +128,7 @@ Container
 *ENDIMPL*/
 impl<T: Ord> Container<T> for LazyUniqueVec<T> {
 
+    /*COST* O(n log n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     len op-len pre-len post-len
@@ -140,6 +141,7 @@ impl<T: Ord> Container<T> for LazyUniqueVec<T> {
         LazyUniqueVec::len(self)
     }
 
+    /*COST* O(n log n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     contains op-contains pre-contains post-contains
@@ -155,6 +157,7 @@ impl<T: Ord> Container<T> for LazyUniqueVec<T> {
         LazyUniqueVec::contains(self, x) // use fully qualified syntax to avoid function name collision
     }
 
+    /*COST* O(n log n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     is-empty op-is-empty pre-is-empty post-is-empty
@@ -167,6 +170,7 @@ impl<T: Ord> Container<T> for LazyUniqueVec<T> {
         LazyUniqueVec::is_empty(self)
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     clear op-clear pre-clear post-clear 
@@ -179,6 +183,7 @@ impl<T: Ord> Container<T> for LazyUniqueVec<T> {
         LazyUniqueVec::clear(self);
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     insert op-insert pre-insert post-insert
@@ -191,6 +196,7 @@ impl<T: Ord> Container<T> for LazyUniqueVec<T> {
         LazyUniqueVec::push(self, elt);
     }
 
+    /*COST* O(n log n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     remove op-remove pre-remove post-remove
@@ -200,7 +206,7 @@ impl<T: Ord> Container<T> for LazyUniqueVec<T> {
         [(list? (member x xs)) (cons (remove x xs) x)]
         [else (cons xs null)]))
     (define (pre-remove xs) (equal? xs (remove-duplicates (sort xs <))))
-    (define (post-remove xs r) (equal? r (op-remove xs)))
+    (define (post-remove xs x r) (equal? r (op-remove xs x)))
     *ENDLIBSPEC*/
     fn remove(&mut self, elt: T) -> Option<T> {
         match self.iter().position(|x| *x == elt) {
@@ -216,6 +222,7 @@ impl<T: Ord> Container<T> for LazyUniqueVec<T> {
 RandomAccess
 *ENDIMPL*/
 impl<T: Ord> RandomAccess<T> for LazyUniqueVec<T> {
+    /*COST* O(n log n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     first op-first pre-first post-first
@@ -231,6 +238,7 @@ impl<T: Ord> RandomAccess<T> for LazyUniqueVec<T> {
         LazyUniqueVec::first(self)
     }
 
+    /*COST* O(n log n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     last op-last pre-last post-last
@@ -246,6 +254,7 @@ impl<T: Ord> RandomAccess<T> for LazyUniqueVec<T> {
         LazyUniqueVec::last(self)
     }
 
+    /*COST* O(n log n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     nth op-nth pre-nth post-nth
@@ -256,18 +265,17 @@ impl<T: Ord> RandomAccess<T> for LazyUniqueVec<T> {
         [(< n 0) (cons xs null)]
         [else (cons xs (list-ref xs n))]))
     (define (pre-nth xs) (equal? xs (remove-duplicates (sort xs <))))
-    (define (post-nth n xs r) (equal? r (op-nth xs n)))
+    (define (post-nth xs n r) (equal? r (op-nth xs n)))
     *ENDLIBSPEC*/
     fn nth(&mut self, n: usize) -> Option<&T> {
         LazyUniqueVec::iter(self).nth(n)
     }                                      
 }
 
-fn abstraction<T>(v: LazyUniqueVec<T>) -> ConsList<T>
-where T: Ord
-{
-    let list: ConsList<T> = ConsList::from(v.to_vec());
-    unique(&list.sort())
+impl<T: Ord + Clone> ModelAbstraction<T> for LazyUniqueVec<T> {
+    fn abstraction(&self) -> ConsList<T> {
+        unique(&ConsList::from(self.clone().to_vec()).sort())
+    }
 }
 
 proptest! {
@@ -277,91 +285,91 @@ proptest! {
     
     #[test]
     fn test_lazy_unique_vec_len(ref mut v in lazy_unique_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         assert_eq!(Container::<String>::len(v), abs_list.len());
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_lazy_unique_vec_contains(ref mut v in lazy_unique_vec(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         assert_eq!(Container::<String>::contains(v, &a), contains(&abs_list, &a));
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_lazy_unique_vec_insert(ref mut v in lazy_unique_vec(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         let after_list = unique(&abs_list.append(conslist![a.clone()]).sort());
         Container::<String>::insert(v, a.clone());
-        assert_eq!(abstraction(v.clone()), after_list);
+        assert_eq!(v.abstraction(), after_list);
     }
 
     #[test]
     fn test_lazy_vec_is_empty(ref mut v in lazy_unique_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         assert_eq!(Container::<String>::is_empty(v), abs_list.is_empty());
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_lazy_unique_vec_remove(ref mut v in lazy_unique_vec(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         let (after_list, abs_elem) = remove(&abs_list, a.clone());
         let elem = Container::<String>::remove(v, a.clone());
-        assert_eq!(abstraction(v.clone()), after_list);
+        assert_eq!(v.abstraction(), after_list);
         assert_eq!(elem, abs_elem);
     }
 
     #[test]
     fn test_lazy_unique_vec_first(ref mut v in lazy_unique_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         let elem = RandomAccess::<String>::first(v);
         let abs_first = first(&abs_list);
         assert_eq!(elem, abs_first);
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_lazy_unique_vec_last(ref mut v in lazy_unique_vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         let elem = RandomAccess::<String>::last(v);
         let abs_last = last(&abs_list);
         assert_eq!(elem, abs_last);
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_lazy_unique_vec_nth(ref mut v in lazy_unique_vec(".*", 0..100), n in 0usize..100) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         let elem = RandomAccess::<String>::nth(v, n.clone());
         let abs_nth = nth(&abs_list, n);
         assert_eq!(elem, abs_nth);
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 }
 
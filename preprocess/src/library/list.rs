@@ -8,7 +8,7 @@ use std::marker::PhantomData;
 use std::iter::FromIterator;
 // nightly features
 use std::collections::linked_list::CursorMut;
-use crate::traits::{Container, Stack, RandomAccess};
+use crate::traits::{Container, Stack, RandomAccess, ModelAbstraction};
 use crate::proptest::*;
 use proptest::prelude::*;
 use proptest::collection::linked_list;
@@ -22,6 +22,7 @@ Container
 *ENDIMPL*/
 impl<T: Ord> Container<T> for LinkedList<T> {
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     len op-len pre-len post-len
@@ -34,6 +35,7 @@ impl<T: Ord> Container<T> for LinkedList<T> {
         LinkedList::len(self)
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     contains op-contains pre-contains post-contains
@@ -49,6 +51,7 @@ impl<T: Ord> Container<T> for LinkedList<T> {
         LinkedList::contains(self, x)
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     is-empty op-is-empty pre-is-empty post-is-empty
@@ -61,6 +64,7 @@ impl<T: Ord> Container<T> for LinkedList<T> {
         LinkedList::is_empty(self)
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     clear op-clear pre-clear post-clear 
@@ -73,6 +77,7 @@ impl<T: Ord> Container<T> for LinkedList<T> {
         LinkedList::clear(self);
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     insert op-insert pre-insert post-insert
@@ -85,6 +90,7 @@ impl<T: Ord> Container<T> for LinkedList<T> {
         LinkedList::push_back(self, elt);
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     remove op-remove pre-remove post-remove
@@ -94,7 +100,7 @@ impl<T: Ord> Container<T> for LinkedList<T> {
         [(list? (member x xs)) (cons (remove x xs) x)]
         [else (cons xs null)]))
     (define (pre-remove xs) #t)
-    (define (post-remove xs r) (equal? r (op-remove xs)))
+    (define (post-remove xs x r) (equal? r (op-remove xs x)))
     *ENDLIBSPEC*/
     fn remove(&mut self, elt: T) -> Option<T> {
         let mut c = self.cursor_front_mut();
@@ -120,6 +126,7 @@ impl<T: Ord> Container<T> for LinkedList<T> {
 Stack
 *ENDIMPL*/
 impl<T> Stack<T> for LinkedList<T> {
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     push push pre-push post-push
@@ -132,6 +139,7 @@ impl<T> Stack<T> for LinkedList<T> {
         LinkedList::push_back(self, elt);
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     pop pop pre-pop post-pop
@@ -152,6 +160,7 @@ impl<T> Stack<T> for LinkedList<T> {
 RandomAccess
 *ENDIMPL*/
 impl<T> RandomAccess<T> for LinkedList<T> {
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     first op-first pre-first post-first
@@ -167,6 +176,7 @@ impl<T> RandomAccess<T> for LinkedList<T> {
         LinkedList::front(self)
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     last op-last pre-last post-last
@@ -182,6 +192,7 @@ impl<T> RandomAccess<T> for LinkedList<T> {
         LinkedList::back(self)
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     nth op-nth pre-nth post-nth
@@ -217,9 +228,10 @@ impl<T: 'static + Ord> Constructor for Con<T> {
     }
 }
 
-fn abstraction<T>(l: LinkedList<T>) -> ConsList<T> {
-    let list: ConsList<T> = ConsList::from_iter(l);
-    list
+impl<T: Clone> ModelAbstraction<T> for LinkedList<T> {
+    fn abstraction(&self) -> ConsList<T> {
+        ConsList::from_iter(self.clone())
+    }
 }
 
 proptest! {
@@ -229,91 +241,91 @@ proptest! {
     
     #[test]
     fn test_list_len(ref mut l in linked_list(".*", 0..100)) {
-        let abs_list = abstraction(l.clone());
+        let abs_list = l.abstraction();
         assert_eq!(Container::<String>::len(l), abs_list.len());
-        assert_eq!(abstraction(l.clone()), abs_list);
+        assert_eq!(l.abstraction(), abs_list);
     }
 
     #[test]
     fn test_list_contains(ref mut l in linked_list(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(l.clone());
+        let abs_list = l.abstraction();
         assert_eq!(Container::<String>::contains(l, &a), contains(&abs_list, &a));
-        assert_eq!(abstraction(l.clone()), abs_list);
+        assert_eq!(l.abstraction(), abs_list);
     }
 
     #[test]
     fn test_list_is_empty(ref mut l in linked_list(".*", 0..100)) {
-        let abs_list = abstraction(l.clone());
+        let abs_list = l.abstraction();
         assert_eq!(Container::<String>::is_empty(l), abs_list.is_empty());
-        assert_eq!(abstraction(l.clone()), abs_list);
+        assert_eq!(l.abstraction(), abs_list);
     }
 
     #[test]
     fn test_list_insert(ref mut l in linked_list(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(l.clone());
+        let abs_list = l.abstraction();
         let after_list = abs_list.append(conslist![a.clone()]);
         Container::<String>::insert(l, a.clone());
-        assert_eq!(abstraction(l.clone()), after_list);
+        assert_eq!(l.abstraction(), after_list);
     }
 
     #[test]
     fn test_list_clear(ref mut l in linked_list(".*", 0..100)) {
-        let abs_list = abstraction(l.clone());
+        let abs_list = l.abstraction();
         let after_list = clear(&abs_list);
         Container::<String>::clear(l);
-        assert_eq!(abstraction(l.clone()), after_list);
+        assert_eq!(l.abstraction(), after_list);
     }
 
     #[test]
     fn test_list_remove(ref mut l in linked_list(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(l.clone());
+        let abs_list = l.abstraction();
         let (after_list, abs_elem) = remove(&abs_list, a.clone());
         let elem = Container::<String>::remove(l, a.clone());
-        assert_eq!(abstraction(l.clone()), after_list);
+        assert_eq!(l.abstraction(), after_list);
         assert_eq!(elem, abs_elem);
     }
 
     #[test]
     fn test_list_first(ref mut l in linked_list(".*", 0..100)) {
-        let abs_list = abstraction(l.clone());
+        let abs_list = l.abstraction();
         let elem = RandomAccess::<String>::first(l);
         let abs_first = first(&abs_list);
         assert_eq!(elem, abs_first);
-        assert_eq!(abstraction(l.clone()), abs_list);
+        assert_eq!(l.abstraction(), abs_list);
     }
 
     #[test]
     fn test_list_last(ref mut l in linked_list(".*", 0..100)) {
-        let abs_list = abstraction(l.clone());
+        let abs_list = l.abstraction();
         let elem = RandomAccess::<String>::last(l);
         let abs_last = last(&abs_list);
         assert_eq!(elem, abs_last);
-        assert_eq!(abstraction(l.clone()), abs_list);
+        assert_eq!(l.abstraction(), abs_list);
     }
 
     #[test]
     fn test_list_nth(ref mut l in linked_list(".*", 0..100), n in 0usize..100) {
-        let abs_list = abstraction(l.clone());
+        let abs_list = l.abstraction();
         let elem = RandomAccess::<String>::nth(l, n.clone());
         let abs_nth = nth(&abs_list, n);
         assert_eq!(elem, abs_nth);
-        assert_eq!(abstraction(l.clone()), abs_list);
+        assert_eq!(l.abstraction(), abs_list);
     }
 
     #[test]
     fn test_list_push(ref mut l in linked_list(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(l.clone());
+        let abs_list = l.abstraction();
         let after_list = push(&abs_list, a.clone());
         Stack::<String>::push(l, a.clone());
-        assert_eq!(abstraction(l.clone()), after_list);
+        assert_eq!(l.abstraction(), after_list);
     }
 
     #[test]
     fn test_list_pop(ref mut l in linked_list(".*", 0..100)) {
-        let abs_list = abstraction(l.clone());
+        let abs_list = l.abstraction();
         let (after_list, abs_elem) = pop(&abs_list);
         let elem = Stack::<String>::pop(l);
-        assert_eq!(abstraction(l.clone()), after_list);
+        assert_eq!(l.abstraction(), after_list);
         assert_eq!(elem.map(|x| Arc::new(x)), abs_elem);
     }
 }
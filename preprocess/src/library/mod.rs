@@ -5,4 +5,5 @@ pub mod hashset;
 pub mod eager_unique_vector;
 pub mod lazy_unique_vector;
 pub mod eager_sorted_vector;
-pub mod lazy_sorted_vector;
\ No newline at end of file
+pub mod lazy_sorted_vector;
+pub mod adaptive_set;
\ No newline at end of file
@@ -4,7 +4,7 @@ rust-btreeset-spec std::collections::BTreeSet
 
 use std::collections::BTreeSet;
 use std::iter::FromIterator;
-use crate::traits::{Container, RandomAccess};
+use crate::traits::{Container, RandomAccess, ModelAbstraction};
 use crate::proptest::*;
 
 use proptest::prelude::*;
@@ -19,6 +19,7 @@ Container
 *ENDIMPL*/
 impl<T: Ord> Container<T> for BTreeSet<T> {
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     len op-len pre-len post-len
@@ -31,6 +32,7 @@ impl<T: Ord> Container<T> for BTreeSet<T> {
         BTreeSet::len(self)
     }
 
+    /*COST* O(log n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     contains op-contains pre-contains post-contains
@@ -46,6 +48,7 @@ impl<T: Ord> Container<T> for BTreeSet<T> {
         BTreeSet::contains(self, x)
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     is-empty op-is-empty pre-is-empty post-is-empty
@@ -58,6 +61,7 @@ impl<T: Ord> Container<T> for BTreeSet<T> {
         BTreeSet::is_empty(self)
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     clear op-clear pre-clear post-clear 
@@ -70,6 +74,7 @@ impl<T: Ord> Container<T> for BTreeSet<T> {
         BTreeSet::clear(self);
     }
 
+    /*COST* O(log n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     insert op-insert pre-insert post-insert
@@ -82,6 +87,7 @@ impl<T: Ord> Container<T> for BTreeSet<T> {
         BTreeSet::insert(self, elt);
     }
 
+    /*COST* O(log n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     remove op-remove pre-remove post-remove
@@ -91,7 +97,7 @@ impl<T: Ord> Container<T> for BTreeSet<T> {
         [(list? (member x xs)) (cons (remove x xs) x)]
         [else (cons xs null)]))
     (define (pre-remove xs) (equal? xs (remove-duplicates (sort xs <))))
-    (define (post-remove xs r) (equal? r (op-remove xs)))
+    (define (post-remove xs x r) (equal? r (op-remove xs x)))
     *ENDLIBSPEC*/
     fn remove(&mut self, elt: T) -> Option<T> {
         match BTreeSet::remove(self, &elt) {
@@ -105,6 +111,7 @@ impl<T: Ord> Container<T> for BTreeSet<T> {
 RandomAccess
 *ENDIMPL*/
 impl<T: Ord> RandomAccess<T> for BTreeSet<T> {
+    /*COST* O(log n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     first op-first pre-first post-first
@@ -120,6 +127,7 @@ impl<T: Ord> RandomAccess<T> for BTreeSet<T> {
         BTreeSet::first(self)
     }
 
+    /*COST* O(log n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     last op-last pre-last post-last
@@ -135,6 +143,7 @@ impl<T: Ord> RandomAccess<T> for BTreeSet<T> {
         BTreeSet::last(self)
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     nth op-nth pre-nth post-nth
@@ -145,16 +154,17 @@ impl<T: Ord> RandomAccess<T> for BTreeSet<T> {
         [(< n 0) (cons xs null)]
         [else (cons xs (list-ref xs n))]))
     (define (pre-nth xs) (equal? xs (remove-duplicates (sort xs <))))
-    (define (post-nth n xs r) (equal? r (op-nth xs n)))
+    (define (post-nth xs n r) (equal? r (op-nth xs n)))
     *ENDLIBSPEC*/
     fn nth(&mut self, n: usize) -> Option<&T> {
         BTreeSet::iter(self).nth(n)
     }                                      
 }
 
-fn abstraction<T>(t: BTreeSet<T>) -> ConsList<T> {
-    let list: ConsList<T> = ConsList::from_iter(t);
-    list
+impl<T: Clone> ModelAbstraction<T> for BTreeSet<T> {
+    fn abstraction(&self) -> ConsList<T> {
+        ConsList::from_iter(self.clone())
+    }
 }
 
 proptest!{
@@ -163,102 +173,102 @@ proptest!{
       })]
     #[test]
     fn test_btree_len(ref mut t in btree_set(".*", 0..100)) {
-        let abs_list = abstraction(t.clone());
+        let abs_list = t.abstraction();
         // pre: our list model is a sorted and unique list
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         assert_eq!(Container::<String>::len(t), abs_list.len());
-        assert_eq!(abstraction(t.clone()), abs_list);
+        assert_eq!(t.abstraction(), abs_list);
     }
 
     #[test]
     fn test_btree_contains(ref mut t in btree_set(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(t.clone());
+        let abs_list = t.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         assert_eq!(Container::<String>::contains(t, &a), contains(&abs_list, &a));
-        assert_eq!(abstraction(t.clone()), abs_list);
+        assert_eq!(t.abstraction(), abs_list);
     }
 
     #[test]
     fn test_btree_is_empty(ref mut t in btree_set(".*", 0..100)) {
-        let abs_list = abstraction(t.clone());
+        let abs_list = t.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         assert_eq!(Container::<String>::is_empty(t), abs_list.is_empty());
-        assert_eq!(abstraction(t.clone()), abs_list);
+        assert_eq!(t.abstraction(), abs_list);
     }
 
     #[test]
     fn test_btree_insert(ref mut t in btree_set(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(t.clone());
+        let abs_list = t.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         let after_list = unique(&abs_list.append(conslist![a.clone()]).sort());
         Container::<String>::insert(t, a.clone());
-        assert_eq!(abstraction(t.clone()), after_list);
+        assert_eq!(t.abstraction(), after_list);
     }
 
     #[test]
     fn test_btree_clear(ref mut t in btree_set(".*", 0..100)) {
-        let abs_list = abstraction(t.clone());
+        let abs_list = t.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         let after_list = clear(&abs_list);
         Container::<String>::clear(t);
-        assert_eq!(abstraction(t.clone()), after_list);
+        assert_eq!(t.abstraction(), after_list);
     }
 
     #[test]
     fn test_btree_remove(ref mut t in btree_set(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(t.clone());
+        let abs_list = t.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         let (after_list, abs_elem) = remove(&abs_list, a.clone());
         let elem = Container::<String>::remove(t, a.clone());
-        assert_eq!(abstraction(t.clone()), after_list);
+        assert_eq!(t.abstraction(), after_list);
         assert_eq!(elem, abs_elem);
     }
 
     #[test]
     fn test_btree_first(ref mut t in btree_set(".*", 0..100)) {
-        let abs_list = abstraction(t.clone());
+        let abs_list = t.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         let elem = RandomAccess::<String>::first(t);
         let abs_first = first(&abs_list);
         assert_eq!(elem, abs_first);
-        assert_eq!(abstraction(t.clone()), abs_list);
+        assert_eq!(t.abstraction(), abs_list);
     }
 
     #[test]
     fn test_btree_last(ref mut t in btree_set(".*", 0..100)) {
-        let abs_list = abstraction(t.clone());
+        let abs_list = t.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         let elem = RandomAccess::<String>::last(t);
         let abs_last = last(&abs_list);
         assert_eq!(elem, abs_last);
-        assert_eq!(abstraction(t.clone()), abs_list);
+        assert_eq!(t.abstraction(), abs_list);
     }
 
     #[test]
     fn test_btree_nth(ref mut t in btree_set(".*", 0..100), n in 0usize..100) {
-        let abs_list = abstraction(t.clone());
+        let abs_list = t.abstraction();
         //pre
         assert_eq!(abs_list, unique(&abs_list.sort()));
         //post
         let elem = RandomAccess::<String>::nth(t, n.clone());
         let abs_nth = nth(&abs_list, n);
         assert_eq!(elem, abs_nth);
-        assert_eq!(abstraction(t.clone()), abs_list);
+        assert_eq!(t.abstraction(), abs_list);
     }
 }
 
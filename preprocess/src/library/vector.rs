@@ -3,7 +3,7 @@ rust-vec-spec std::vec::Vec
 *ENDLIBSPEC-NAME*/
 
 use std::vec::Vec;
-use crate::traits::{Container, Stack, RandomAccess};
+use crate::traits::{Container, Stack, RandomAccess, ModelAbstraction};
 use crate::proptest::*;
 
 use proptest::prelude::*;
@@ -18,6 +18,7 @@ Container
 *ENDIMPL*/
 impl<T: PartialEq> Container<T> for Vec<T> {
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     len op-len pre-len post-len
@@ -30,6 +31,7 @@ impl<T: PartialEq> Container<T> for Vec<T> {
         Vec::len(self)
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     contains op-contains pre-contains post-contains
@@ -45,6 +47,7 @@ impl<T: PartialEq> Container<T> for Vec<T> {
         <[T]>::contains(self, x) // use fully qualified syntax to avoid function name collision
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     is-empty op-is-empty pre-is-empty post-is-empty
@@ -57,6 +60,7 @@ impl<T: PartialEq> Container<T> for Vec<T> {
         Vec::is_empty(self)
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     clear op-clear pre-clear post-clear 
@@ -69,6 +73,7 @@ impl<T: PartialEq> Container<T> for Vec<T> {
         Vec::clear(self);
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     insert op-insert pre-insert post-insert
@@ -81,6 +86,7 @@ impl<T: PartialEq> Container<T> for Vec<T> {
         Vec::push(self, elt);
     }
 
+    /*COST* O(n) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     remove op-remove pre-remove post-remove
@@ -90,7 +96,7 @@ impl<T: PartialEq> Container<T> for Vec<T> {
         [(list? (member x xs)) (cons (remove x xs) x)]
         [else (cons xs null)]))
     (define (pre-remove xs) #t)
-    (define (post-remove xs r) (equal? r (op-remove xs)))
+    (define (post-remove xs x r) (equal? r (op-remove xs x)))
     *ENDLIBSPEC*/
     fn remove(&mut self, elt: T) -> Option<T> {
         match self.iter().position(|x| *x == elt) {
@@ -106,6 +112,7 @@ impl<T: PartialEq> Container<T> for Vec<T> {
 Stack
 *ENDIMPL*/
 impl<T> Stack<T> for Vec<T> {
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     push push pre-push post-push
@@ -118,6 +125,7 @@ impl<T> Stack<T> for Vec<T> {
         Vec::push(self, elt);
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     pop pop pre-pop post-pop
@@ -138,6 +146,7 @@ impl<T> Stack<T> for Vec<T> {
 RandomAccess
 *ENDIMPL*/
 impl<T> RandomAccess<T> for Vec<T> {
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     first op-first pre-first post-first
@@ -153,6 +162,7 @@ impl<T> RandomAccess<T> for Vec<T> {
         <[T]>::first(self)
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     last op-last pre-last post-last
@@ -168,6 +178,7 @@ impl<T> RandomAccess<T> for Vec<T> {
         <[T]>::last(self)
     }
 
+    /*COST* O(1) *ENDCOST*/
     /*LIBSPEC*
     /*OPNAME*
     nth op-nth pre-nth post-nth
@@ -185,9 +196,10 @@ impl<T> RandomAccess<T> for Vec<T> {
     }                                      
 }
 
-fn abstraction<T>(v: Vec<T>) -> ConsList<T> {
-    let list: ConsList<T> = ConsList::from(v);
-    list
+impl<T: Clone> ModelAbstraction<T> for Vec<T> {
+    fn abstraction(&self) -> ConsList<T> {
+        ConsList::from(self.clone())
+    }
 }
 
 proptest! {
@@ -197,91 +209,91 @@ proptest! {
 
     #[test]
     fn test_vec_len(ref mut v in vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         assert_eq!(Container::<String>::len(v), abs_list.len());
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_vec_contains(ref mut v in vec(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         assert_eq!(Container::<String>::contains(v, &a), contains(&abs_list, &a));
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_vec_is_empty(ref mut v in vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         assert_eq!(Container::<String>::is_empty(v), abs_list.is_empty());
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_vec_insert(ref mut v in vec(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         let after_list = abs_list.append(conslist![a.clone()]);
         Container::<String>::insert(v, a.clone());
-        assert_eq!(abstraction(v.clone()), after_list);
+        assert_eq!(v.abstraction(), after_list);
     }
 
     #[test]
     fn test_vec_clear(ref mut v in vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         let after_list = clear(&abs_list);
         Container::<String>::clear(v);
-        assert_eq!(abstraction(v.clone()), after_list);
+        assert_eq!(v.abstraction(), after_list);
     }
 
     #[test]
     fn test_vec_remove(ref mut v in vec(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         let (after_list, abs_elem) = remove(&abs_list, a.clone());
         let elem = Container::<String>::remove(v, a.clone());
-        assert_eq!(abstraction(v.clone()), after_list);
+        assert_eq!(v.abstraction(), after_list);
         assert_eq!(elem, abs_elem);
     }
 
     #[test]
     fn test_vec_first(ref mut v in vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         let elem = RandomAccess::<String>::first(v);
         let abs_first = first(&abs_list);
         assert_eq!(elem, abs_first);
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_vec_last(ref mut v in vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         let elem = RandomAccess::<String>::last(v);
         let abs_last = last(&abs_list);
         assert_eq!(elem, abs_last);
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_vec_nth(ref mut v in vec(".*", 0..100), n in 0usize..100) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         let elem = RandomAccess::<String>::nth(v, n.clone());
         let abs_nth = nth(&abs_list, n);
         assert_eq!(elem, abs_nth);
-        assert_eq!(abstraction(v.clone()), abs_list);
+        assert_eq!(v.abstraction(), abs_list);
     }
 
     #[test]
     fn test_vec_push(ref mut v in vec(".*", 0..100), a in ".*") {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         let after_list = push(&abs_list, a.clone());
         Stack::<String>::push(v, a.clone());
-        assert_eq!(abstraction(v.clone()), after_list);
+        assert_eq!(v.abstraction(), after_list);
     }
 
     #[test]
     fn test_vec_pop(ref mut v in vec(".*", 0..100)) {
-        let abs_list = abstraction(v.clone());
+        let abs_list = v.abstraction();
         let (after_list, abs_elem) = pop(&abs_list);
         let elem = Stack::<String>::pop(v);
-        assert_eq!(abstraction(v.clone()), after_list);
+        assert_eq!(v.abstraction(), after_list);
         assert_eq!(elem.map(|x| Arc::new(x)), abs_elem);
     }
 }
@@ -1,16 +1,283 @@
-use preprocess::generator::{run};
+use preprocess::generator::{MatchOptions, Matcher, bench_src, check_src, match_src, process_and_check_src, process_src_with, regenerate_in_place, summarise_src};
+use preprocess::benchmark::{CRITERIONDIR, snake_case};
+use preprocess::lib_spec_processor::{process_lib_specs};
+use preprocess::cost::{fmt_repr_costs};
+use preprocess::parser::{Dispatch};
+use preprocess::match_cache::{CACHEFILE, MatchCache};
+use preprocess::run_matching::{setup_dirs};
 use std::env;
-use std::io::{Error, ErrorKind};
-
-fn main() -> Result<(), Error> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() == 1 { // skip the first arg
-        println!("{:?}", run("./spec_code/example.rs".to_string(), "example_output.rs".to_string()));
-        Ok(())    
-    } else if args.len() == 3 { // skip the first arg
-        println!("{:?}", run("./spec_code/".to_string() + args.get(1).unwrap(), args.get(2).unwrap().to_string()));
-        Ok(())
+use std::fs;
+use std::path::Path;
+use std::process;
+
+type ErrorMessage = String;
+
+const BENCHDIR: &str = "./benches/";
+
+const USAGE: &str =
+"Usage: main <command> [options]
+
+Commands:
+  generate <input> [-o <output>]    generate the container types of a program
+  generate <input> --in-place       regenerate the container types within the program
+  check <input>                     parse and type check a program only
+  match <input>                     list the candidate structs for each container type
+  lib                               process and list the library specifications
+  bench <input> [-o <dir>]          generate a criterion benchmark of the candidate structs of each container type
+  summary <input>                   choose the fastest struct of each container type from its benchmark results
+  cache [clear | invalidate <name>] inspect or invalidate the cached match results
+
+Options:
+  -o, --output <path>       the generated file, by default ./gen_code/<input file name>,
+                            or the directory of the benchmarks, by default ./benches/
+  --criterion-dir <dir>     the results of the benchmarks, by default ./target/criterion/
+  --lib-dir <dir>           the directory of the library specifications, by default ./src/library/
+  --work-dir <dir>          the directory of the generated specifications and the match cache,
+                            by default ./racket_specs/
+  --model-size <n>          the length bound of the list model used for matching
+  --jobs <n>                the number of checks run in parallel
+  --rosette                 match with the Rosette scripts instead of the built-in checker
+  --record                  generate container types recording their usage in ./profiles/
+  --profiles <dir>          choose the structs by the usage recorded in the directory
+  --contracts               generate container types checking their properties after each change, in debug builds
+  --tests                   generate a property-based test module for each container type
+  --static                  generate container types calling the chosen structs directly, not through a box
+  --no-verify               write the generated program without checking that it compiles
+  --in-place                regenerate the /*CODEGEN*/ region of the input itself
+  -h, --help                print this message
+
+Environment:
+  PREPROCESS_CRATE_DIR      the preprocess crate the generated programs are checked with,
+                            by default the one this tool was built from";
+
+struct Cli {
+    command: String,
+    args: Vec<String>,
+    output: Option<String>,
+    criterion_dir: String,
+    verify: bool,
+    in_place: bool,
+    options: MatchOptions,
+}
+
+fn parse_number(flag: &str, value: Option<String>) -> Result<usize, ErrorMessage> {
+    match value.as_ref().and_then(|v| v.parse::<usize>().ok()) {
+        Some(n) if n > 0 => Ok(n),
+        _ => Err(format!("Error, {} expects a positive number", flag))
+    }
+}
+
+fn parse_args(args: Vec<String>) -> Result<Cli, ErrorMessage> {
+    let mut positional = Vec::<String>::new();
+    let mut output = None;
+    let mut criterion_dir = CRITERIONDIR.to_string();
+    let mut verify = true;
+    let mut in_place = false;
+    let mut options = MatchOptions::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                output = Some(args.next().ok_or_else(|| format!("Error, {} expects a path", arg))?);
+            },
+            "--lib-dir" => {
+                let dir = args.next().ok_or("Error, --lib-dir expects a directory")?;
+                options.lib_dir = if dir.ends_with('/') { dir } else { dir + "/" };
+            },
+            "--work-dir" => {
+                let dir = args.next().ok_or("Error, --work-dir expects a directory")?;
+                options.work_dir = if dir.ends_with('/') { dir } else { dir + "/" };
+            },
+            "--model-size" => options.model_size = parse_number(&arg, args.next())?,
+            "--jobs" => options.jobs = parse_number(&arg, args.next())?,
+            "--rosette" => options.matcher = Matcher::Rosette,
+            "--criterion-dir" => {
+                criterion_dir = args.next().ok_or("Error, --criterion-dir expects a directory")?;
+            },
+            "--record" => options.record = true,
+            "--contracts" => options.contracts = true,
+            "--tests" => options.tests = true,
+            "--static" => options.dispatch = Dispatch::Static,
+            "--no-verify" => verify = false,
+            "--in-place" => in_place = true,
+            "--profiles" => {
+                options.profile_dir = Some(args.next().ok_or("Error, --profiles expects a directory")?);
+            },
+            "-h" | "--help" => positional.insert(0, "help".to_string()),
+            _ if arg.starts_with('-') => return Err("Error, unknown option: ".to_string() + &arg),
+            _ => positional.push(arg)
+        }
+    }
+    if positional.is_empty() {
+        return Err("Error, no command given".to_string());
+    }
+    let command = positional.remove(0);
+    Ok(Cli { command, args: positional, output, criterion_dir, verify, in_place, options })
+}
+
+fn input(cli: &Cli) -> Result<String, ErrorMessage> {
+    match cli.args.as_slice() {
+        [input] => Ok(input.to_string()),
+        _ => Err(format!("Error, {} expects exactly one input file", cli.command))
+    }
+}
+
+fn generate(cli: &Cli) -> Result<(), ErrorMessage> {
+    let input = input(cli)?;
+    if cli.in_place {
+        if cli.output.is_some() {
+            return Err("Error, --in-place writes to the input, not to --output".to_string());
+        }
+        let changed = regenerate_in_place(input.clone(), &cli.options, cli.verify).map_err(|e| e.to_string())?;
+        println!("{} {}", if changed { "Regenerated" } else { "Up to date:" }, input);
+        return Ok(());
+    }
+    let output = match &cli.output {
+        Some(o) => o.to_string(),
+        None => {
+            let name = Path::new(&input).file_name().ok_or("Error, invalid input file")?;
+            "./gen_code/".to_string() + &name.to_string_lossy()
+        }
+    };
+    let code = if cli.verify {
+        process_and_check_src(input, &cli.options)
     } else {
-        Err(Error::new(ErrorKind::Other, "Invalid source code paths"))
+        process_src_with(input, &cli.options)
+    };
+    let code = code.map_err(|e| e.to_string())?;
+    if let Some(dir) = Path::new(&output).parent() {
+        if !dir.as_os_str().is_empty() && fs::create_dir_all(dir).is_err() {
+            return Err("Error, unable to create the output directory: ".to_string() + &dir.to_string_lossy());
+        }
+    }
+    fs::write(&output, code).map_err(|_| "Error, unable to write the output file: ".to_string() + &output)?;
+    println!("Generated {}", output);
+    Ok(())
+}
+
+fn check(cli: &Cli) -> Result<(), ErrorMessage> {
+    let input = input(cli)?;
+    check_src(input.clone()).map_err(|e| e.to_string())?;
+    println!("{}: ok", input);
+    Ok(())
+}
+
+fn match_cons(cli: &Cli) -> Result<(), ErrorMessage> {
+    let (matches, diagnostics) = match_src(input(cli)?, &cli.options).map_err(|e| e.to_string())?;
+    for (con, structs, reason) in matches.iter() {
+        if structs.is_empty() {
+            println!("{}: no matching struct", con);
+        } else {
+            println!("{}: {}", con, structs.join(", "));
+            println!("  chosen {}: {}", structs[0], reason);
+        }
+    }
+    for d in diagnostics.iter() {
+        println!("  {}", d);
+    }
+    if matches.iter().any(|(_, structs, _)| structs.is_empty()) {
+        return Err("Error, some container types have no matching struct".to_string());
+    }
+    Ok(())
+}
+
+fn bench(cli: &Cli) -> Result<(), ErrorMessage> {
+    let dir = cli.output.clone().unwrap_or_else(|| BENCHDIR.to_string());
+    let benches = bench_src(input(cli)?, &cli.options).map_err(|e| e.to_string())?;
+    if fs::create_dir_all(&dir).is_err() {
+        return Err("Error, unable to create the output directory: ".to_string() + &dir);
+    }
+    for (con, code) in benches.iter() {
+        let name = snake_case(con) + "_bench";
+        let path = dir.trim_end_matches('/').to_string() + "/" + &name + ".rs";
+        fs::write(&path, code).map_err(|_| "Error, unable to write the benchmark: ".to_string() + &path)?;
+        println!("Generated {}, run it with cargo bench --bench {} after adding to Cargo.toml", path, name);
+        println!("[[bench]]\nname = \"{}\"\npath = \"{}\"\nharness = false\n", name, path.trim_start_matches("./"));
+    }
+    Ok(())
+}
+
+fn summary(cli: &Cli) -> Result<(), ErrorMessage> {
+    let summaries = summarise_src(input(cli)?, &cli.criterion_dir).map_err(|e| e.to_string())?;
+    for s in summaries.iter() {
+        println!("{}", s);
+    }
+    Ok(())
+}
+
+fn lib(cli: &Cli) -> Result<(), ErrorMessage> {
+    setup_dirs(&cli.options.work_dir).map_err(|_| "Error, unable to set up the working directory ".to_string() + &cli.options.work_dir)?;
+    let lib_specs = process_lib_specs(cli.options.lib_dir.to_string(), &cli.options.work_dir)?;
+    let mut names: Vec<&String> = lib_specs.keys().collect();
+    names.sort();
+    for name in names {
+        let (spec_file, bounds, (_, ops), model, costs, abstraction) = lib_specs.get(name).unwrap();
+        let mut interfaces: Vec<&String> = bounds.keys().collect();
+        interfaces.sort();
+        println!("{} ({})", name, spec_file);
+        println!("  interfaces: {}", interfaces.iter().map(|i| i.as_str()).collect::<Vec<&str>>().join(", "));
+        println!("  operations: {}", ops.join(", "));
+        println!("  model: {}", model);
+        println!("  abstraction: {}", abstraction.as_deref().unwrap_or("none"));
+        for interface in interfaces.iter() {
+            let op_costs = costs.get(*interface).map(|c| c.iter()
+                .map(|(op, cost)| op.to_string() + " " + &cost.as_ref().map(fmt_repr_costs).unwrap_or_else(|| "?".to_string()))
+                .collect::<Vec<String>>().join(", ")).unwrap_or_default();
+            println!("  costs of {}: {}", interface, op_costs);
+        }
+    }
+    Ok(())
+}
+
+// cache: list the cached match results
+// cache clear: invalidate all results
+// cache invalidate <struct or property>: invalidate the results involving it
+fn cache(cli: &Cli) -> Result<(), ErrorMessage> {
+    let mut cache = MatchCache::load(&(cli.options.work_dir.to_owned() + CACHEFILE))?;
+    let invalidated = match cli.args.as_slice() {
+        [] => {
+            for (key, entry) in cache.entries() {
+                let result = match &entry.result {
+                    None => "match".to_string(),
+                    Some(None) => "no match".to_string(),
+                    Some(Some(c)) => "no match, ".to_string() + &c.to_string()
+                };
+                println!("{} {} {} {}: {}", key, entry.lib_struct, entry.property, entry.bound, result);
+            }
+            println!("{} cached result(s)", cache.len());
+            return Ok(());
+        },
+        [cmd] if cmd == "clear" => cache.invalidate(None),
+        [cmd, name] if cmd == "invalidate" => cache.invalidate(Some(name)),
+        _ => return Err("Error, invalid cache command".to_string())
+    };
+    cache.save().map_err(|_| "Error, unable to write the match cache".to_string())?;
+    println!("{} cached result(s) invalidated", invalidated);
+    Ok(())
+}
+
+fn run(cli: &Cli) -> Result<(), ErrorMessage> {
+    match cli.command.as_str() {
+        "generate" => generate(cli),
+        "check" => check(cli),
+        "match" => match_cons(cli),
+        "lib" => lib(cli),
+        "bench" => bench(cli),
+        "summary" => summary(cli),
+        "cache" => cache(cli),
+        "help" => {
+            println!("{}", USAGE);
+            Ok(())
+        },
+        c => Err("Error, unknown command: ".to_string() + c)
+    }
+}
+
+fn main() {
+    let result = parse_args(env::args().skip(1).collect()).and_then(|cli| run(&cli));
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        eprintln!("Run with --help for usage.");
+        process::exit(1);
     }
-}
\ No newline at end of file
+}
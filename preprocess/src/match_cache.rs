@@ -0,0 +1,210 @@
+// A persistent cache of match results, keyed by a hash of the contents of the
+// property specification, the library specification, the setups and the operations they
+// require, with the version of the matcher.
+use std::fs;
+use std::io::{Write, Error};
+use std::collections::BTreeMap;
+
+use crate::native_matching::counterexample::{Counterexample};
+
+pub const CACHEFILE: &str = "match-cache.txt"; // in the working directory
+
+type CacheError = String;
+
+// None if the struct matches, otherwise the counterexample if the matcher provides one
+pub type MatchResult = Option<Option<Counterexample>>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CacheEntry {
+    pub lib_struct: String,
+    pub property: String,
+    pub bound: String,
+    pub result: MatchResult,
+}
+
+// 64-bit FNV-1a, which unlike std's DefaultHasher is stable across compiler versions
+fn fnv1a(h: u64, bytes: &[u8]) -> u64 {
+    let mut h = h;
+    for b in bytes.iter() {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+    h
+}
+
+// The key of a check from the contents of the files and the names it depends on
+pub fn cache_key(contents: &[&str]) -> String {
+    let mut h = 0xcbf29ce484222325;
+    for c in contents.iter() {
+        h = fnv1a(h, c.as_bytes());
+        h = fnv1a(h, &[0]); // separate the fields
+    }
+    format!("{:016x}", h)
+}
+
+fn encode_result(result: &MatchResult) -> String {
+    match result {
+        None => "match".to_string(),
+        Some(None) => "nomatch".to_string(),
+        Some(Some(Counterexample::Contradiction { op })) => "contradiction\t".to_string() + op,
+        Some(Some(Counterexample::Unsatisfied { input })) => "unsatisfied\t".to_string() + input,
+        Some(Some(Counterexample::Violation { op, input, elem, output })) => {
+            let elem = elem.clone().unwrap_or_default();
+            ["violation", op, input, &elem, output].join("\t")
+        },
+        Some(Some(Counterexample::OrderDependent { input, reordered })) => {
+            ["order", input, reordered].join("\t")
+        }
+    }
+}
+
+pub fn decode_result(fields: &[&str]) -> Option<MatchResult> {
+    match fields {
+        ["match"] => Some(None),
+        ["nomatch"] => Some(Some(None)),
+        ["contradiction", op] => Some(Some(Some(Counterexample::Contradiction { op: op.to_string() }))),
+        ["unsatisfied", input] => Some(Some(Some(Counterexample::Unsatisfied { input: input.to_string() }))),
+        ["violation", op, input, elem, output] => Some(Some(Some(Counterexample::Violation {
+            op: op.to_string(),
+            input: input.to_string(),
+            elem: if elem.is_empty() { None } else { Some(elem.to_string()) },
+            output: output.to_string()
+        }))),
+        ["order", input, reordered] => Some(Some(Some(Counterexample::OrderDependent {
+            input: input.to_string(),
+            reordered: reordered.to_string()
+        }))),
+        _ => None
+    }
+}
+
+pub struct MatchCache {
+    path: String,
+    entries: BTreeMap<String, CacheEntry>,
+}
+
+impl MatchCache {
+    pub fn new(path: &str) -> MatchCache {
+        MatchCache {
+            path: path.to_string(),
+            entries: BTreeMap::new(),
+        }
+    }
+
+    // Load the cache, starting from an empty one if the file does not exist
+    pub fn load(path: &str) -> Result<MatchCache, CacheError> {
+        let mut cache = MatchCache::new(path);
+        let contents = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => return Ok(cache)
+        };
+        for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+            // key, struct, property, bound, result ...
+            let fields: Vec<&str> = line.split('\t').collect();
+            let result = if fields.len() > 4 { decode_result(&fields[4..]) } else { None };
+            match result {
+                Some(result) => {
+                    cache.entries.insert(fields[0].to_string(), CacheEntry {
+                        lib_struct: fields[1].to_string(),
+                        property: fields[2].to_string(),
+                        bound: fields[3].to_string(),
+                        result
+                    });
+                },
+                None => return Err("Error, invalid entry in the match cache: ".to_string() + line)
+            }
+        }
+        Ok(cache)
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let mut output = fs::File::create(&self.path)?;
+        for (key, entry) in self.entries.iter() {
+            writeln!(output, "{}\t{}\t{}\t{}\t{}", key, entry.lib_struct, entry.property, entry.bound, encode_result(&entry.result))?;
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&CacheEntry> {
+        self.entries.get(key)
+    }
+
+    pub fn insert(&mut self, key: String, entry: CacheEntry) {
+        self.entries.insert(key, entry);
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &CacheEntry)> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Remove the entries of the given struct or property, or all entries if None,
+    // and give the number of removed entries
+    pub fn invalidate(&mut self, name: Option<&str>) -> usize {
+        let before = self.entries.len();
+        match name {
+            Some(n) => self.entries.retain(|_, e| e.lib_struct != n && e.property != n),
+            None => self.entries.clear()
+        }
+        before - self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::match_cache::{MatchCache, CacheEntry, cache_key};
+    use crate::native_matching::counterexample::{Counterexample};
+
+    #[test]
+    fn test_cache_key_separates_fields() {
+        assert_eq!(cache_key(&["ab", "c"]), cache_key(&["ab", "c"]));
+        assert_ne!(cache_key(&["ab", "c"]), cache_key(&["a", "bc"]));
+    }
+
+    #[test]
+    fn test_cache_round_trip() {
+        let path = std::env::temp_dir().join("preprocess-match-cache-test.txt");
+        let path = path.to_str().unwrap();
+        let mut cache = MatchCache::new(path);
+        let violation = Counterexample::Violation {
+            op: "insert".to_string(),
+            input: "(0)".to_string(),
+            elem: Some("0".to_string()),
+            output: "(0 0)".to_string()
+        };
+        cache.insert("k1".to_string(), CacheEntry {
+            lib_struct: "std::vec::Vec".to_string(),
+            property: "unique".to_string(),
+            bound: "Container".to_string(),
+            result: Some(Some(violation))
+        });
+        cache.insert("k2".to_string(), CacheEntry {
+            lib_struct: "std::collections::BTreeSet".to_string(),
+            property: "unique".to_string(),
+            bound: "Container".to_string(),
+            result: None
+        });
+        cache.insert("k3".to_string(), CacheEntry {
+            lib_struct: "std::collections::HashSet".to_string(),
+            property: "ascending".to_string(),
+            bound: "Container".to_string(),
+            result: Some(Some(Counterexample::OrderDependent { input: "(0 2)".to_string(), reordered: "(2 0)".to_string() }))
+        });
+        cache.save().unwrap();
+        let mut loaded = MatchCache::load(path).unwrap();
+        assert_eq!(loaded.get("k1"), cache.get("k1"));
+        assert_eq!(loaded.get("k2"), cache.get("k2"));
+        assert_eq!(loaded.get("k3"), cache.get("k3"));
+        assert_eq!(loaded.invalidate(Some("std::vec::Vec")), 1);
+        assert_eq!(loaded.invalidate(None), 2);
+        assert!(loaded.is_empty());
+        std::fs::remove_file(path).unwrap();
+    }
+}
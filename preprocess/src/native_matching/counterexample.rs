@@ -0,0 +1,37 @@
+use std::fmt;
+
+// A witness that a library specification does not preserve a property,
+// found by the model checker on the list model
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Counterexample {
+    // no list of length > 1 satisfies both the property and the precondition of op
+    Contradiction { op: String },
+    // a list accepted by the preconditions of all operations which violates the property
+    Unsatisfied { input: String },
+    // op applied to input (and elem) returns output, which violates the property
+    Violation { op: String, input: String, elem: Option<String>, output: String },
+    // the property holds on input but not on its reordering, which an unordered struct does not tell apart
+    OrderDependent { input: String, reordered: String },
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Counterexample::Contradiction { op } => {
+                write!(f, "the property contradicts the precondition of {}", op)
+            },
+            Counterexample::Unsatisfied { input } => {
+                write!(f, "the list {} satisfies the preconditions but violates the property", input)
+            },
+            Counterexample::Violation { op, input, elem: Some(x), output } => {
+                write!(f, "{} with {} on the list {} gives {}, which violates the property", op, x, input, output)
+            },
+            Counterexample::Violation { op, input, elem: None, output } => {
+                write!(f, "{} on the list {} gives {}, which violates the property", op, input, output)
+            },
+            Counterexample::OrderDependent { input, reordered } => {
+                write!(f, "the property holds on {} but not on {}, whose order the unordered struct does not keep", input, reordered)
+            },
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::parser::{Id, Term};
+use crate::native_matching::value::{Value, Proc, Prim, Env, EvalError};
+
+// An interpreter for library specifications and property terms over the list model
+pub struct Interp {
+    globals: HashMap<Id, Value>,
+    props: HashMap<Id, Term>,
+    domain: Vec<i64>,
+}
+
+impl Interp {
+    pub fn new(domain: Vec<i64>) -> Interp {
+        Interp {
+            globals: HashMap::new(),
+            props: HashMap::new(),
+            domain
+        }
+    }
+
+    pub fn define(&mut self, id: Id, v: Value) {
+        self.globals.insert(id, v);
+    }
+
+    pub fn define_prop(&mut self, id: Id, term: Term) {
+        self.props.insert(id, term);
+    }
+
+    pub fn get_domain(&self) -> &Vec<i64> {
+        &self.domain
+    }
+
+    pub fn extend_domain(&mut self, elems: impl Iterator<Item = i64>) {
+        self.domain.extend(elems);
+        self.domain.sort();
+        self.domain.dedup();
+    }
+
+    pub fn lookup(&self, env: &Env, id: &str) -> Result<Value, EvalError> {
+        if let Some((_, v)) = env.iter().rev().find(|(k, _)| k == id) {
+            return Ok(v.clone());
+        }
+        if let Some(v) = self.globals.get(id) {
+            return Ok(v.clone());
+        }
+        if let Some(t) = self.props.get(id) {
+            return self.eval_term(t, &Env::new());
+        }
+        match Prim::lookup(id) {
+            Some(p) => Ok(Value::Proc(Rc::new(Proc::Prim(p)))),
+            None => Err("Unbound identifier: ".to_string() + id)
+        }
+    }
+
+    pub fn apply(&self, f: &Value, args: Vec<Value>) -> Result<Value, EvalError> {
+        let p = match f {
+            Value::Proc(p) => p.clone(),
+            _ => return Err("Application: not a procedure: ".to_string() + &f.to_string())
+        };
+        match p.as_ref() {
+            Proc::Prim(prim) => {
+                if *prim != Prim::List && args.len() < prim.arity() {
+                    Ok(Value::Proc(Rc::new(Proc::Partial(p.clone(), args))))
+                } else {
+                    self.apply_prim(*prim, args)
+                }
+            },
+            Proc::Lambda(params, body, env) => {
+                if args.len() < params.len() {
+                    return Ok(Value::Proc(Rc::new(Proc::Partial(p.clone(), args))));
+                }
+                if args.len() > params.len() {
+                    return Err("Application: arity mismatch".to_string());
+                }
+                let mut local = env.clone();
+                for (param, arg) in params.iter().zip(args) {
+                    local.push((param.to_string(), arg));
+                }
+                self.eval_body(body, &local)
+            },
+            Proc::TermLambda(param, body, env) => {
+                let mut args = args;
+                if args.is_empty() {
+                    return Ok(f.clone());
+                }
+                let rest = args.split_off(1);
+                let mut local = env.clone();
+                local.push((param.to_string(), args.pop().unwrap()));
+                let result = self.eval_term(body, &local)?;
+                if rest.is_empty() {
+                    Ok(result)
+                } else {
+                    self.apply(&result, rest)
+                }
+            },
+            Proc::Partial(inner, prev) => {
+                let mut all = prev.clone();
+                all.extend(args);
+                self.apply(&Value::Proc(inner.clone()), all)
+            }
+        }
+    }
+
+    fn apply_prim(&self, prim: Prim, args: Vec<Value>) -> Result<Value, EvalError> {
+        let arg = |i: usize| -> Result<&Value, EvalError> {
+            args.get(i).ok_or_else(|| format!("{:?}: arity mismatch", prim))
+        };
+        match prim {
+            Prim::Cons => Ok(Value::cons(arg(0)?.clone(), arg(1)?.clone())),
+            Prim::Car => arg(0)?.car(),
+            Prim::Cdr => arg(0)?.cdr(),
+            Prim::List => Ok(Value::List(args.clone())),
+            Prim::Length => Ok(Value::Int(arg(0)?.as_list()?.len() as i64)),
+            Prim::IsNull => Ok(Value::Bool(matches!(arg(0)?, Value::List(l) if l.is_empty()))),
+            Prim::IsList => Ok(Value::Bool(matches!(arg(0)?, Value::List(_)))),
+            Prim::Member => {
+                let l = arg(1)?.as_list()?;
+                match l.iter().position(|y| y == arg(0).unwrap()) {
+                    Some(i) => Ok(Value::List(l[i..].to_vec())),
+                    None => Ok(Value::Bool(false))
+                }
+            },
+            Prim::Remove => {
+                let mut l = arg(1)?.as_list()?.clone();
+                if let Some(i) = l.iter().position(|y| y == arg(0).unwrap()) {
+                    l.remove(i);
+                }
+                Ok(Value::List(l))
+            },
+            Prim::RemoveDuplicates => {
+                let mut result = Vec::<Value>::new();
+                for v in arg(0)?.as_list()?.iter() {
+                    if !result.contains(v) {
+                        result.push(v.clone());
+                    }
+                }
+                Ok(Value::List(result))
+            },
+            Prim::Sort => {
+                // a stable insertion sort, as Racket's sort is stable
+                let less = arg(1)?;
+                let mut result = Vec::<Value>::new();
+                for v in arg(0)?.as_list()?.iter() {
+                    let mut i = result.len();
+                    while i > 0 && self.apply(less, vec![v.clone(), result[i - 1].clone()])?.as_bool() {
+                        i -= 1;
+                    }
+                    result.insert(i, v.clone());
+                }
+                Ok(Value::List(result))
+            },
+            Prim::Append => {
+                let mut result = Vec::<Value>::new();
+                for a in args.iter() {
+                    result.extend(a.as_list()?.iter().cloned());
+                }
+                Ok(Value::List(result))
+            },
+            Prim::Take | Prim::Drop => {
+                let l = arg(0)?.as_list()?;
+                let n = arg(1)?.as_int()?;
+                if n < 0 || n as usize > l.len() {
+                    return Err(format!("{:?}: index out of range: {}", prim, n));
+                }
+                if prim == Prim::Take {
+                    Ok(Value::List(l[..n as usize].to_vec()))
+                } else {
+                    Ok(Value::List(l[n as usize..].to_vec()))
+                }
+            },
+            Prim::First => arg(0)?.as_list()?.first().cloned().ok_or_else(|| "first: empty list".to_string()),
+            Prim::Last => arg(0)?.as_list()?.last().cloned().ok_or_else(|| "last: empty list".to_string()),
+            Prim::ListRef => {
+                let l = arg(0)?.as_list()?;
+                let n = arg(1)?.as_int()?;
+                if n < 0 || n as usize >= l.len() {
+                    return Err(format!("list-ref: index out of range: {}", n));
+                }
+                Ok(l[n as usize].clone())
+            },
+            Prim::Reverse => {
+                let mut l = arg(0)?.as_list()?.clone();
+                l.reverse();
+                Ok(Value::List(l))
+            },
+            Prim::Add => {
+                let mut sum = 0;
+                for a in args.iter() {
+                    sum += a.as_int()?;
+                }
+                Ok(Value::Int(sum))
+            },
+            Prim::Mul => {
+                let mut product = 1;
+                for a in args.iter() {
+                    product *= a.as_int()?;
+                }
+                Ok(Value::Int(product))
+            },
+            Prim::Sub => {
+                if args.len() == 1 {
+                    return Ok(Value::Int(-arg(0)?.as_int()?));
+                }
+                let mut result = arg(0)?.as_int()?;
+                for a in args[1..].iter() {
+                    result -= a.as_int()?;
+                }
+                Ok(Value::Int(result))
+            },
+            Prim::Lt | Prim::Gt | Prim::Le | Prim::Ge | Prim::NumEq | Prim::Leq | Prim::Geq => {
+                let mut ns = Vec::<i64>::new();
+                for a in args.iter() {
+                    ns.push(a.as_int()?);
+                }
+                let holds = ns.windows(2).all(|w| match prim {
+                    Prim::Lt => w[0] < w[1],
+                    Prim::Gt => w[0] > w[1],
+                    Prim::Le | Prim::Leq => w[0] <= w[1],
+                    Prim::Ge | Prim::Geq => w[0] >= w[1],
+                    _ => w[0] == w[1],
+                });
+                Ok(Value::Bool(holds))
+            },
+            Prim::Not => Ok(Value::Bool(!arg(0)?.as_bool())),
+            Prim::IsEqual => Ok(Value::Bool(arg(0)? == arg(1)?)),
+            Prim::NotEqual => Ok(Value::Bool(arg(0)? != arg(1)?)),
+            Prim::ForAllUniquePairs => {
+                let l = arg(0)?.as_list()?;
+                let f = arg(1)?;
+                for a in l.iter() {
+                    let mut others = l.clone();
+                    if let Some(i) = others.iter().position(|y| y == a) {
+                        others.remove(i);
+                    }
+                    for b in others.into_iter() {
+                        if !self.apply(f, vec![a.clone(), b])?.as_bool() {
+                            return Ok(Value::Bool(false));
+                        }
+                    }
+                }
+                Ok(Value::Bool(true))
+            },
+            Prim::ForAllConsecutivePairs => {
+                let l = arg(0)?.as_list()?;
+                let f = arg(1)?;
+                for w in l.windows(2) {
+                    if !self.apply(f, vec![w[0].clone(), w[1].clone()])?.as_bool() {
+                        return Ok(Value::Bool(false));
+                    }
+                }
+                Ok(Value::Bool(true))
+            },
+            Prim::ForAllElems => {
+                let l = arg(0)?.as_list()?;
+                let f = arg(1)?;
+                for a in l.iter() {
+                    if !self.apply(f, vec![a.clone()])?.as_bool() {
+                        return Ok(Value::Bool(false));
+                    }
+                }
+                Ok(Value::Bool(true))
+            },
+            Prim::UniqueCount => {
+                let x = arg(0)?;
+                let count = arg(1)?.as_list()?.iter().filter(|y| *y == x).count();
+                Ok(Value::Bool(count == 1))
+            }
+        }
+    }
+}
@@ -0,0 +1,436 @@
+// An in-process bounded model checker which implements the `check` procedures
+// of racket_specs/*-setup.rkt by enumerating the list model up to a length bound.
+pub mod value;
+pub mod interp;
+pub mod racket;
+pub mod property;
+pub mod counterexample;
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::parser::{Term};
+use crate::spec_map::{PropTerms};
+use crate::lib_spec::{Model};
+use crate::native_matching::interp::{Interp};
+use crate::native_matching::value::{Value, Env};
+use crate::native_matching::counterexample::{Counterexample};
+
+pub type CheckError = String;
+
+// The length of the list model used in the generated property specifications
+pub const MODELSIZE: usize = 5;
+
+// The version of the checker, in the keys of the match cache; bump it with any change which
+// can change the result of a check, so that the results cached before are not used
+pub const CHECKERVERSION: u32 = 1;
+
+// How the check of one operation applies its specification
+struct OpCheck {
+    name: &'static str, // the operation, for reporting counterexamples
+    with_elem: bool, // the operation takes the symbolic element as argument
+    takes_car: bool, // the resulting list is the car of the result
+}
+
+// In the order of the specifications provided by the library, i.e. sorted by operation name
+const CONTAINER_OPS: [OpCheck; 6] = [
+    OpCheck { name: "clear", with_elem: false, takes_car: false },
+    OpCheck { name: "contains", with_elem: true, takes_car: true },
+    OpCheck { name: "insert", with_elem: true, takes_car: false },
+    OpCheck { name: "is-empty", with_elem: false, takes_car: true },
+    OpCheck { name: "len", with_elem: false, takes_car: true },
+    OpCheck { name: "remove", with_elem: true, takes_car: true },
+];
+
+const RANDOMACCESS_OPS: [OpCheck; 3] = [
+    OpCheck { name: "first", with_elem: false, takes_car: true },
+    OpCheck { name: "last", with_elem: false, takes_car: true },
+    OpCheck { name: "nth", with_elem: true, takes_car: true },
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Setup {
+    Container,
+    RandomAccess,
+    Stack,
+}
+
+impl Setup {
+    pub fn from_bound(bound: &str) -> Option<Setup> {
+        match bound {
+            "Container" => Some(Setup::Container),
+            "RandomAccess" => Some(Setup::RandomAccess),
+            "Stack" => Some(Setup::Stack),
+            _ => None
+        }
+    }
+}
+
+// All lists of length up to n, up to an order preserving renaming of their elements:
+// a list using d distinct values uses exactly 0, 2, .., 2(d-1), which leaves room
+// for the symbolic element before, between or after them.
+fn gen_lists(n: usize) -> Vec<Value> {
+    let mut result = Vec::<Vec<i64>>::new();
+    // each list paired with its number of distinct values
+    let mut prev = vec![(Vec::<i64>::new(), 0)];
+    for _ in 0..n {
+        let mut next = Vec::<(Vec<i64>, i64)>::new();
+        for (l, d) in prev.iter() {
+            // append an existing value
+            for v in 0..*d {
+                let mut l1 = l.clone();
+                l1.push(v);
+                next.push((l1, *d));
+            }
+            // append a new value of rank v
+            for v in 0..=*d {
+                let mut l1: Vec<i64> = l.iter().map(|x| if *x >= v { x + 1 } else { *x }).collect();
+                l1.push(v);
+                next.push((l1, d + 1));
+            }
+        }
+        result.extend(prev.into_iter().map(|(l, _)| l));
+        prev = next;
+    }
+    result.extend(prev.into_iter().map(|(l, _)| l));
+    result.into_iter()
+        .map(|l| Value::List(l.into_iter().map(|v| Value::Int(2 * v)).collect()))
+        .collect()
+}
+
+fn gen_elems(n: usize) -> Vec<i64> {
+    (-1..(2 * n as i64)).collect()
+}
+
+// The integer literals of a term. The renaming of gen_lists only preserves the order of the
+// elements, not how they compare to a literal, so the elements checked include each literal
+// and its neighbours.
+fn int_literals(term: &Term, literals: &mut BTreeSet<i64>) {
+    match term {
+        Term::LitTerm(lit) => {
+            if let Ok(n) = lit.parse::<i64>() {
+                literals.extend([n.saturating_sub(1), n, n.saturating_add(1)]);
+            }
+        },
+        Term::VarTerm(_) => (),
+        Term::LambdaTerm(_, body) => int_literals(body, literals),
+        Term::AppTerm(t1, t2) => {
+            int_literals(t1, literals);
+            int_literals(t2, literals);
+        }
+    }
+}
+
+pub struct ModelChecker {
+    interp: Interp,
+    lists: Vec<Value>,
+    model: Model, // the model of the library struct
+    memo: RefCell<HashMap<String, bool>>, // the property evaluated on lists of the current check
+}
+
+impl ModelChecker {
+    pub fn new(model_size: usize) -> ModelChecker {
+        ModelChecker {
+            interp: Interp::new(gen_elems(model_size)),
+            lists: gen_lists(model_size),
+            model: Model::List,
+            memo: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn set_model(&mut self, model: Model) {
+        self.model = model;
+    }
+
+    pub fn load_props(&mut self, prop_terms: &PropTerms) {
+        let mut literals = BTreeSet::new();
+        for (id, term) in prop_terms.iter() {
+            int_literals(term, &mut literals);
+            self.interp.define_prop(id.to_string(), term.clone());
+        }
+        self.interp.extend_domain(literals.into_iter());
+    }
+
+    pub fn load_lib_spec(&mut self, src: &str) -> Result<(), CheckError> {
+        self.interp.load_lib_spec(src)
+    }
+
+    pub fn load_lib_spec_file(&mut self, filename: &str) -> Result<(), CheckError> {
+        self.interp.load_lib_spec_file(filename)
+    }
+
+    fn holds(&self, f: &Value, args: Vec<Value>) -> Result<bool, CheckError> {
+        Ok(self.interp.apply(f, args)?.as_bool())
+    }
+
+    fn prop_holds(&self, prop: &Value, xs: &Value) -> Result<bool, CheckError> {
+        let key = xs.to_string();
+        if let Some(r) = self.memo.borrow().get(&key) {
+            return Ok(*r);
+        }
+        let r = self.holds(prop, vec![xs.clone()])?;
+        self.memo.borrow_mut().insert(key, r);
+        Ok(r)
+    }
+
+    // Check whether the library interface preserves the property
+    pub fn check(&self, prop: &str, setup: Setup, interface_spec: &str) -> Result<bool, CheckError> {
+        Ok(self.find_counterexample(prop, setup, interface_spec)?.is_none())
+    }
+
+    // None if the library interface preserves the property
+    pub fn find_counterexample(&self, prop: &str, setup: Setup, interface_spec: &str) -> Result<Option<Counterexample>, CheckError> {
+        self.memo.borrow_mut().clear();
+        let env = Env::new();
+        let prop = self.interp.lookup(&env, prop)?;
+        let interface = self.interp.lookup(&env, interface_spec)?;
+        let specs = interface.car()?.as_list()?.clone();
+        let pres = interface.cdr()?.as_list()?.clone();
+        if self.model.is_unordered() {
+            if let Some(c) = self.check_order(&prop, &pres)? {
+                return Ok(Some(c));
+            }
+        }
+        match setup {
+            Setup::Container => self.check_ops(&prop, &CONTAINER_OPS, &specs, &pres),
+            Setup::RandomAccess => self.check_ops(&prop, &RANDOMACCESS_OPS, &specs, &pres),
+            Setup::Stack => self.check_push_pop(&prop, &pres),
+        }
+    }
+
+    fn check_ops(&self, prop: &Value, ops: &[OpCheck], specs: &[Value], pres: &[Value]) -> Result<Option<Counterexample>, CheckError> {
+        if specs.len() < ops.len() || pres.len() < ops.len() {
+            return Err("Error, the library specification does not provide all operations of the interface".to_string());
+        }
+        // the property must not contradict any precondition
+        for (op, pre) in ops.iter().zip(pres.iter()) {
+            let mut satisfiable = false;
+            for xs in self.lists.iter() {
+                if xs.as_list()?.len() > 1 && self.prop_holds(prop, xs)? && self.holds(pre, vec![xs.clone()])? {
+                    satisfiable = true;
+                    break;
+                }
+            }
+            if !satisfiable {
+                return Ok(Some(Counterexample::Contradiction { op: op.name.to_string() }));
+            }
+        }
+        // every operation preserves the property
+        let elems: Vec<Value> = self.interp.get_domain().iter().map(|x| Value::Int(*x)).collect();
+        for (i, op) in ops.iter().enumerate() {
+            for xs in self.lists.iter() {
+                if !(self.prop_holds(prop, xs)? && self.holds(&pres[i], vec![xs.clone()])?) {
+                    continue;
+                }
+                let args: Vec<Vec<Value>> = if op.with_elem {
+                    elems.iter().map(|x| vec![xs.clone(), x.clone()]).collect()
+                } else {
+                    vec![vec![xs.clone()]]
+                };
+                for a in args.into_iter() {
+                    let mut result = self.interp.apply(&specs[i], a.clone())?;
+                    if op.takes_car {
+                        result = result.car()?;
+                    }
+                    if !self.prop_holds(prop, &result)? {
+                        return Ok(Some(Counterexample::Violation {
+                            op: op.name.to_string(),
+                            input: xs.to_string(),
+                            elem: a.get(1).map(|x| x.to_string()),
+                            output: result.to_string()
+                        }));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    // The list model of an unordered struct is one arbitrary ordering of its elements,
+    // so the property must hold for every ordering of a list it holds for, i.e. be
+    // preserved by swapping the first two elements and by rotation, which generate them
+    fn check_order(&self, prop: &Value, pres: &[Value]) -> Result<Option<Counterexample>, CheckError> {
+        let pre = match pres.first() {
+            Some(pre) => pre,
+            None => return Err("Error, the library specification does not provide any operation of the interface".to_string())
+        };
+        for xs in self.lists.iter() {
+            let l = xs.as_list()?;
+            if l.len() < 2 || !(self.prop_holds(prop, xs)? && self.holds(pre, vec![xs.clone()])?) {
+                continue;
+            }
+            let mut swapped = l.clone();
+            swapped.swap(0, 1);
+            let mut rotated = l.clone();
+            rotated.rotate_left(1);
+            for reordered in [Value::List(swapped), Value::List(rotated)] {
+                if !self.prop_holds(prop, &reordered)? {
+                    return Ok(Some(Counterexample::OrderDependent {
+                        input: xs.to_string(),
+                        reordered: reordered.to_string()
+                    }));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn check_push_pop(&self, prop: &Value, pres: &[Value]) -> Result<Option<Counterexample>, CheckError> {
+        if pres.len() < 2 {
+            return Err("Error, the library specification does not provide all operations of the interface".to_string());
+        }
+        for xs in self.lists.iter() {
+            if self.holds(&pres[0], vec![xs.clone()])? && self.holds(&pres[1], vec![xs.clone()])?
+                && !self.prop_holds(prop, xs)? {
+                return Ok(Some(Counterexample::Unsatisfied { input: xs.to_string() }));
+            }
+        }
+        Ok(None)
+    }
+}
+
+// The native counterpart of generating a match script and running it with Racket,
+// giving a counterexample if the library struct does not match; lib_spec_file is the path
+// of the generated library specification
+pub fn check_match(prop: String, bound: String, prop_terms: &PropTerms, lib_spec_file: String, interface_spec: String, model_size: usize, model: Model) -> Result<Option<Counterexample>, CheckError> {
+    let setup = match Setup::from_bound(&bound) {
+        Some(s) => s,
+        None => return Err("Error, no match setup for the bound: ".to_string() + &bound)
+    };
+    let mut checker = ModelChecker::new(model_size);
+    checker.set_model(model);
+    checker.load_props(prop_terms);
+    checker.load_lib_spec_file(&lib_spec_file)?;
+    checker.find_counterexample(&prop, setup, &interface_spec)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::native_matching::{ModelChecker, Setup};
+    use crate::lib_spec::{Model};
+    use crate::native_matching::counterexample::{Counterexample};
+    use crate::parser::{spec};
+    use crate::spec_map::{PropTerms};
+
+    const LIST_OPS: &str = r#"
+    (define (op-len xs) (cons xs (length xs)))
+    (define (op-contains xs x)
+      (cond
+        [(list? (member x xs)) (cons xs #t)]
+        [else (cons xs #f)]))
+    (define (op-is-empty xs) (cons xs (null? xs)))
+    (define (op-clear xs) null)
+    (define (op-remove xs x)
+      (cond
+        [(list? (member x xs)) (cons (remove x xs) x)]
+        [else (cons xs null)]))
+    (define (pop xs)
+      (cond
+        [(null? xs) (cons xs null)]
+        [else (cons (take xs (- (length xs) 1)) (last xs))]))
+    (define (push xs x) (append xs (list x)))
+    (define (pre-push xs) #t)
+    (define (pre-pop xs) #t)
+    (define stack-specs (list pop push))
+    (define stack-pres (list pre-pop pre-push))
+    (define stack (cons stack-specs stack-pres))
+    "#;
+
+    fn checker(insert: &str, pre: &str) -> ModelChecker {
+        let src = LIST_OPS.to_string() + &format!(r#"
+        (define (op-insert xs x) {insert})
+        (define (pre xs) {pre})
+        (define container-specs (list op-clear op-contains op-insert op-is-empty op-len op-remove))
+        (define container-pres (list pre pre pre pre pre pre))
+        (define container (cons container-specs container-pres))
+        "#);
+        let mut props = PropTerms::new();
+        props.insert("unique".to_string(), spec::term(r#"\c -> ((for-all-elems c) \a -> ((unique-count? a) c))"#).unwrap());
+        props.insert("ascending".to_string(), spec::term(r#"\c -> ((for-all-consecutive-pairs c) leq?)"#).unwrap());
+        props.insert("descending".to_string(), spec::term(r#"\c -> ((for-all-consecutive-pairs c) geq?)"#).unwrap());
+        props.insert("lifo".to_string(), spec::term(r#"\c <: (Stack) -> (forall \x -> ((equal? (pop ((push c) x))) x))"#).unwrap());
+        props.insert("lifo-surface".to_string(), spec::term(r#"\c <: (Stack) -> forall(\x -> pop(push(c, x)) == x)"#).unwrap());
+        props.insert("ascending-or-empty".to_string(), spec::term(r#"\c -> if null?(c) { true } else { for-all-consecutive-pairs(c, leq?) && !false }"#).unwrap());
+        props.insert("non-negative".to_string(), spec::term(r#"\c -> for-all-elems(c, \a -> a >= 0) && length(c) < 64"#).unwrap());
+        props.insert("first-zero".to_string(), spec::term(r#"\c -> length(c) == 0 || first(c) * 2 == 0"#).unwrap());
+        props.insert("not-ten".to_string(), spec::term(r#"\c -> for-all-elems(c, \a -> a != 10)"#).unwrap());
+        let mut checker = ModelChecker::new(4);
+        checker.load_props(&props);
+        checker.load_lib_spec(&src).unwrap();
+        checker
+    }
+
+    #[test]
+    fn test_vec_is_not_unique() {
+        let c = checker("(append xs (list x))", "#t");
+        assert_eq!(c.check("unique", Setup::Container, "container"), Ok(false));
+        assert_eq!(c.check("ascending", Setup::Container, "container"), Ok(false));
+        assert_eq!(c.check("lifo", Setup::Stack, "stack"), Ok(true));
+        assert_eq!(c.check("lifo-surface", Setup::Stack, "stack"), Ok(true));
+        assert_eq!(c.check("non-negative", Setup::Container, "container"), Ok(false));
+        assert_eq!(c.check("first-zero", Setup::Container, "container"), Ok(false));
+        assert_eq!(c.check("ascending-or-empty", Setup::Container, "container"), Ok(false));
+    }
+
+    #[test]
+    fn test_sorted_set_is_unique_and_ascending() {
+        let c = checker("(remove-duplicates (sort (append xs (list x)) <))", "(equal? xs (remove-duplicates (sort xs <)))");
+        assert_eq!(c.check("unique", Setup::Container, "container"), Ok(true));
+        assert_eq!(c.check("ascending", Setup::Container, "container"), Ok(true));
+        assert_eq!(c.check("descending", Setup::Container, "container"), Ok(false));
+        assert_eq!(c.check("ascending-or-empty", Setup::Container, "container"), Ok(true));
+    }
+
+    #[test]
+    fn test_unique_vec_is_unique() {
+        let c = checker("(remove-duplicates (append xs (list x)))", "(equal? xs (remove-duplicates xs))");
+        assert_eq!(c.check("unique", Setup::Container, "container"), Ok(true));
+        assert_eq!(c.check("ascending", Setup::Container, "container"), Ok(false));
+    }
+
+    #[test]
+    fn test_non_negative_vec() {
+        let c = checker("(if (< x 0) xs (append xs (list x)))", "#t");
+        assert_eq!(c.check("non-negative", Setup::Container, "container"), Ok(true));
+        assert_eq!(c.check("unique", Setup::Container, "container"), Ok(false));
+    }
+
+    #[test]
+    fn test_literal_outside_the_renamed_elements() {
+        let c = checker("(append xs (list x))", "#t");
+        assert_eq!(
+            c.find_counterexample("not-ten", Setup::Container, "container"),
+            Ok(Some(Counterexample::Violation {
+                op: "insert".to_string(),
+                input: "()".to_string(),
+                elem: Some("10".to_string()),
+                output: "(10)".to_string()
+            }))
+        );
+    }
+
+    #[test]
+    fn test_unordered_set() {
+        let mut c = checker("(remove-duplicates (append xs (list x)))", "(equal? xs (remove-duplicates xs))");
+        c.set_model(Model::Set);
+        assert_eq!(c.check("unique", Setup::Container, "container"), Ok(true));
+        assert_eq!(
+            c.find_counterexample("ascending", Setup::Container, "container"),
+            Ok(Some(Counterexample::OrderDependent { input: "(0 2)".to_string(), reordered: "(2 0)".to_string() }))
+        );
+    }
+
+    #[test]
+    fn test_vec_counterexample_for_unique() {
+        let c = checker("(append xs (list x))", "#t");
+        assert_eq!(
+            c.find_counterexample("unique", Setup::Container, "container"),
+            Ok(Some(Counterexample::Violation {
+                op: "insert".to_string(),
+                input: "(0)".to_string(),
+                elem: Some("0".to_string()),
+                output: "(0 0)".to_string()
+            }))
+        );
+    }
+}
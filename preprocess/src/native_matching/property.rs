@@ -0,0 +1,79 @@
+use std::rc::Rc;
+
+use crate::parser::{Term};
+use crate::native_matching::interp::{Interp};
+use crate::native_matching::value::{Value, Proc, Env, EvalError};
+
+impl Interp {
+    // Evaluate a property term, following the translation in Analyser::analyse_term
+    pub fn eval_term(&self, term: &Term, env: &Env) -> Result<Value, EvalError> {
+        match term {
+            Term::LitTerm(lit) => {
+                match lit.parse::<i64>() {
+                    Ok(n) => Ok(Value::Int(n)),
+                    Err(_) => Ok(Value::Bool(lit.to_string() == "true"))
+                }
+            },
+            Term::VarTerm(id) => self.lookup(env, id),
+            Term::LambdaTerm((id, _), t) => {
+                Ok(Value::Proc(Rc::new(Proc::TermLambda(id.to_string(), Rc::new(*t.clone()), env.clone()))))
+            },
+            Term::AppTerm(t1, t2) => {
+                if t1.is_quantifier() {
+                    // quantify over the element domain of the model
+                    let body = self.eval_term(t2, env)?;
+                    for x in self.get_domain().iter() {
+                        if !self.apply(&body, vec![Value::Int(*x)])?.as_bool() {
+                            return Ok(Value::Bool(false));
+                        }
+                    }
+                    return Ok(Value::Bool(true));
+                }
+                if let Some(result) = self.eval_special_form(term, env)? {
+                    return Ok(result);
+                }
+                let f = self.eval_term(t1, env)?;
+                let arg = self.eval_term(t2, env)?;
+                let result = self.apply(&f, vec![arg])?;
+                // operations such as pop return a pair of the new list and the result
+                if t1.require_cdr() {
+                    result.cdr()
+                } else {
+                    Ok(result)
+                }
+            }
+        }
+    }
+
+    // if, and and or evaluate their branches lazily, as in Racket
+    fn eval_special_form(&self, term: &Term, env: &Env) -> Result<Option<Value>, EvalError> {
+        let mut args = Vec::<&Term>::new();
+        let mut head = term;
+        while let Term::AppTerm(t1, t2) = head {
+            args.insert(0, t2);
+            head = t1;
+        }
+        let form = match head {
+            Term::VarTerm(id) if !env.iter().any(|(k, _)| k == id.as_str()) => id.as_str(),
+            _ => return Ok(None)
+        };
+        match (form, args.as_slice()) {
+            ("if", [c, t, e]) => {
+                if self.eval_term(c, env)?.as_bool() {
+                    self.eval_term(t, env).map(Some)
+                } else {
+                    self.eval_term(e, env).map(Some)
+                }
+            },
+            ("and", [t1, t2]) => {
+                let v = self.eval_term(t1, env)?;
+                if v.as_bool() { self.eval_term(t2, env).map(Some) } else { Ok(Some(v)) }
+            },
+            ("or", [t1, t2]) => {
+                let v = self.eval_term(t1, env)?;
+                if v.as_bool() { Ok(Some(v)) } else { self.eval_term(t2, env).map(Some) }
+            },
+            _ => Ok(None)
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use std::fs;
+use std::rc::Rc;
+
+use crate::parser::{Id};
+use crate::lib_spec::sexpr::{SExpr, read_sexprs};
+use crate::native_matching::interp::{Interp};
+use crate::native_matching::value::{Value, Proc, Env, EvalError};
+
+impl Interp {
+    // Load the definitions of a generated library specification file
+    pub fn load_lib_spec_file(&mut self, filename: &str) -> Result<(), EvalError> {
+        match fs::read_to_string(filename) {
+            Ok(src) => self.load_lib_spec(&src),
+            Err(_) => Err("Unable to read library specification file: ".to_string() + filename)
+        }
+    }
+
+    pub fn load_lib_spec(&mut self, src: &str) -> Result<(), EvalError> {
+        for expr in read_sexprs(src)?.iter() {
+            self.eval_toplevel(expr)?;
+        }
+        Ok(())
+    }
+
+    fn eval_toplevel(&mut self, expr: &SExpr) -> Result<(), EvalError> {
+        if let SExpr::List(l) = expr {
+            match l.first().and_then(|h| h.as_atom()) {
+                Some("define") => {
+                    match l.get(1) {
+                        Some(SExpr::List(sig)) if !sig.is_empty() => {
+                            // (define (f x ...) body ...)
+                            let name = sig[0].as_atom().ok_or("Invalid definition")?.to_string();
+                            let params = sig[1..].iter()
+                                .map(|p| p.as_atom().map(|s| s.to_string()).ok_or_else(|| "Invalid parameter".to_string()))
+                                .collect::<Result<Vec<Id>, EvalError>>()?;
+                            let lambda = Proc::Lambda(params, Rc::new(l[2..].to_vec()), Env::new());
+                            self.define(name, Value::Proc(Rc::new(lambda)));
+                        },
+                        Some(SExpr::Atom(name)) => {
+                            // (define x e)
+                            let e = l.get(2).ok_or("Invalid definition")?;
+                            let v = self.eval_sexpr(e, &Env::new())?;
+                            self.define(name.to_string(), v);
+                        },
+                        _ => return Err("Invalid definition: ".to_string() + &expr.to_string())
+                    }
+                    return Ok(());
+                },
+                Some("provide") | Some("require") => return Ok(()),
+                _ => {}
+            }
+        }
+        self.eval_sexpr(expr, &Env::new()).map(|_| ())
+    }
+
+    pub fn eval_body(&self, body: &[SExpr], env: &Env) -> Result<Value, EvalError> {
+        let mut result = Value::null();
+        for e in body.iter() {
+            result = self.eval_sexpr(e, env)?;
+        }
+        Ok(result)
+    }
+
+    pub fn eval_sexpr(&self, expr: &SExpr, env: &Env) -> Result<Value, EvalError> {
+        match expr {
+            SExpr::Atom(a) => {
+                match a.as_str() {
+                    "#t" | "#true" => Ok(Value::Bool(true)),
+                    "#f" | "#false" => Ok(Value::Bool(false)),
+                    "null" | "empty" => Ok(Value::null()),
+                    _ => match a.parse::<i64>() {
+                        Ok(n) => Ok(Value::Int(n)),
+                        Err(_) => self.lookup(env, a)
+                    }
+                }
+            },
+            SExpr::List(l) => {
+                if l.is_empty() {
+                    return Err("Missing procedure expression".to_string());
+                }
+                match l[0].as_atom() {
+                    Some("quote") => match l.get(1) {
+                        Some(SExpr::List(q)) if q.is_empty() => Ok(Value::null()),
+                        _ => Err("Unsupported quoted expression: ".to_string() + &expr.to_string())
+                    },
+                    Some("lambda") => {
+                        let params = match l.get(1) {
+                            Some(SExpr::List(ps)) => ps.iter()
+                                .map(|p| p.as_atom().map(|s| s.to_string()).ok_or_else(|| "Invalid parameter".to_string()))
+                                .collect::<Result<Vec<Id>, EvalError>>()?,
+                            _ => return Err("Invalid lambda: ".to_string() + &expr.to_string())
+                        };
+                        Ok(Value::Proc(Rc::new(Proc::Lambda(params, Rc::new(l[2..].to_vec()), env.clone()))))
+                    },
+                    Some("if") => {
+                        if l.len() != 4 {
+                            return Err("Invalid if: ".to_string() + &expr.to_string());
+                        }
+                        if self.eval_sexpr(&l[1], env)?.as_bool() {
+                            self.eval_sexpr(&l[2], env)
+                        } else {
+                            self.eval_sexpr(&l[3], env)
+                        }
+                    },
+                    Some("cond") => {
+                        for clause in l[1..].iter() {
+                            match clause {
+                                SExpr::List(c) if !c.is_empty() => {
+                                    let holds = match c[0].as_atom() {
+                                        Some("else") => true,
+                                        _ => self.eval_sexpr(&c[0], env)?.as_bool()
+                                    };
+                                    if holds {
+                                        return self.eval_body(&c[1..], env);
+                                    }
+                                },
+                                _ => return Err("Invalid cond clause: ".to_string() + &clause.to_string())
+                            }
+                        }
+                        Err("cond: no clause matched".to_string())
+                    },
+                    Some("and") => {
+                        let mut result = Value::Bool(true);
+                        for e in l[1..].iter() {
+                            result = self.eval_sexpr(e, env)?;
+                            if !result.as_bool() {
+                                break;
+                            }
+                        }
+                        Ok(result)
+                    },
+                    Some("or") => {
+                        let mut result = Value::Bool(false);
+                        for e in l[1..].iter() {
+                            result = self.eval_sexpr(e, env)?;
+                            if result.as_bool() {
+                                break;
+                            }
+                        }
+                        Ok(result)
+                    },
+                    Some("let") => {
+                        let mut local = env.clone();
+                        match l.get(1) {
+                            Some(SExpr::List(bindings)) => {
+                                for b in bindings.iter() {
+                                    match b {
+                                        SExpr::List(kv) if kv.len() == 2 && kv[0].as_atom().is_some() => {
+                                            let v = self.eval_sexpr(&kv[1], env)?;
+                                            local.push((kv[0].as_atom().unwrap().to_string(), v));
+                                        },
+                                        _ => return Err("Invalid let binding: ".to_string() + &b.to_string())
+                                    }
+                                }
+                            },
+                            _ => return Err("Invalid let: ".to_string() + &expr.to_string())
+                        }
+                        self.eval_body(&l[2..], &local)
+                    },
+                    _ => {
+                        let f = self.eval_sexpr(&l[0], env)?;
+                        let args = l[1..].iter()
+                            .map(|e| self.eval_sexpr(e, env))
+                            .collect::<Result<Vec<Value>, EvalError>>()?;
+                        self.apply(&f, args)
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::rc::Rc;
+
+use crate::parser::{Id, Term};
+use crate::lib_spec::sexpr::SExpr;
+
+pub type EvalError = String;
+
+// Local bindings captured by closures, the innermost binding last
+pub type Env = Vec<(Id, Value)>;
+
+// Racket values in the list model: integers, booleans, lists and pairs
+#[derive(Clone, Debug)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    List(Vec<Value>),
+    Pair(Box<Value>, Box<Value>),
+    Proc(Rc<Proc>),
+}
+
+#[derive(Debug)]
+pub enum Proc {
+    Prim(Prim),
+    Lambda(Vec<Id>, Rc<Vec<SExpr>>, Env),     // a lambda from a library specification
+    TermLambda(Id, Rc<Term>, Env),            // a lambda from a property term
+    Partial(Rc<Proc>, Vec<Value>),            // curried application of a procedure
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Prim {
+    Cons, Car, Cdr, List, Length, IsNull, IsList, Member, Remove, RemoveDuplicates,
+    Sort, Append, Take, Drop, First, Last, ListRef, Reverse,
+    Add, Sub, Mul, Lt, Gt, Le, Ge, NumEq, Not, IsEqual, NotEqual, Leq, Geq,
+    ForAllUniquePairs, ForAllConsecutivePairs, ForAllElems, UniqueCount,
+}
+
+impl Prim {
+    pub fn lookup(name: &str) -> Option<Prim> {
+        let p = match name {
+            "cons" => Prim::Cons,
+            "car" => Prim::Car,
+            "cdr" | "rest" => Prim::Cdr,
+            "list" => Prim::List,
+            "length" => Prim::Length,
+            "null?" | "empty?" => Prim::IsNull,
+            "list?" => Prim::IsList,
+            "member" => Prim::Member,
+            "remove" => Prim::Remove,
+            "remove-duplicates" => Prim::RemoveDuplicates,
+            "sort" => Prim::Sort,
+            "append" => Prim::Append,
+            "take" => Prim::Take,
+            "drop" => Prim::Drop,
+            "first" => Prim::First,
+            "last" => Prim::Last,
+            "list-ref" => Prim::ListRef,
+            "reverse" => Prim::Reverse,
+            "+" => Prim::Add,
+            "-" => Prim::Sub,
+            "*" => Prim::Mul,
+            "<" => Prim::Lt,
+            ">" => Prim::Gt,
+            "<=" => Prim::Le,
+            ">=" => Prim::Ge,
+            "=" => Prim::NumEq,
+            "not" => Prim::Not,
+            "equal?" => Prim::IsEqual,
+            "not-equal?" | "neq" => Prim::NotEqual,
+            "leq?" => Prim::Leq,
+            "geq?" => Prim::Geq,
+            "for-all-unique-pairs" => Prim::ForAllUniquePairs,
+            "for-all-consecutive-pairs" => Prim::ForAllConsecutivePairs,
+            "for-all-elems" => Prim::ForAllElems,
+            "unique-count?" => Prim::UniqueCount,
+            _ => return None,
+        };
+        Some(p)
+    }
+
+    // Number of arguments after which a curried application fires
+    pub fn arity(&self) -> usize {
+        match self {
+            Prim::List | Prim::Car | Prim::Cdr | Prim::Length | Prim::IsNull | Prim::IsList
+                | Prim::RemoveDuplicates | Prim::First | Prim::Last | Prim::Reverse | Prim::Not => 1,
+            _ => 2,
+        }
+    }
+}
+
+impl Value {
+    pub fn null() -> Value {
+        Value::List(Vec::new())
+    }
+
+    pub fn as_bool(&self) -> bool {
+        // everything except #f counts as true in Racket
+        !matches!(self, Value::Bool(false))
+    }
+
+    pub fn as_int(&self) -> Result<i64, EvalError> {
+        match self {
+            Value::Int(n) => Ok(*n),
+            _ => Err("Expected an integer, found: ".to_string() + &self.to_string())
+        }
+    }
+
+    pub fn as_list(&self) -> Result<&Vec<Value>, EvalError> {
+        match self {
+            Value::List(l) => Ok(l),
+            _ => Err("Expected a list, found: ".to_string() + &self.to_string())
+        }
+    }
+
+    pub fn cons(a: Value, d: Value) -> Value {
+        match d {
+            Value::List(mut l) => {
+                l.insert(0, a);
+                Value::List(l)
+            },
+            _ => Value::Pair(Box::new(a), Box::new(d))
+        }
+    }
+
+    pub fn car(&self) -> Result<Value, EvalError> {
+        match self {
+            Value::List(l) if !l.is_empty() => Ok(l[0].clone()),
+            Value::Pair(a, _) => Ok(*a.clone()),
+            _ => Err("car: contract violation, expected a pair, found: ".to_string() + &self.to_string())
+        }
+    }
+
+    pub fn cdr(&self) -> Result<Value, EvalError> {
+        match self {
+            Value::List(l) if !l.is_empty() => Ok(Value::List(l[1..].to_vec())),
+            Value::Pair(_, d) => Ok(*d.clone()),
+            _ => Err("cdr: contract violation, expected a pair, found: ".to_string() + &self.to_string())
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::List(a), Value::List(b)) => a == b,
+            (Value::Pair(a1, d1), Value::Pair(a2, d2)) => a1 == a2 && d1 == d2,
+            _ => false
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Bool(true) => write!(f, "#t"),
+            Value::Bool(false) => write!(f, "#f"),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::List(l) => {
+                let elems: Vec<String> = l.iter().map(|v| v.to_string()).collect();
+                write!(f, "({})", elems.join(" "))
+            },
+            Value::Pair(a, d) => write!(f, "({} . {})", a, d),
+            Value::Proc(_) => write!(f, "#<procedure>"),
+        }
+    }
+}
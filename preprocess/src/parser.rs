@@ -14,6 +14,25 @@ pub type Literal = String;
 pub enum Refinement {
     Prop(Box<Term>),
     AndProps(Box<Refinement>, Box<Refinement>),
+    OrProps(Box<Refinement>, Box<Refinement>),
+    NotProp(Box<Refinement>),
+    ImpliesProps(Box<Refinement>, Box<Refinement>),
+}
+
+impl Refinement {
+    // The refinement as one boolean term, p => q being (not p) or q
+    pub fn to_term(&self) -> Term {
+        match self {
+            Refinement::Prop(t) => *t.clone(),
+            Refinement::AndProps(r1, r2) => Term::apps(Term::var("and"), vec![r1.to_term(), r2.to_term()]),
+            Refinement::OrProps(r1, r2) => Term::apps(Term::var("or"), vec![r1.to_term(), r2.to_term()]),
+            Refinement::NotProp(r) => Term::apps(Term::var("not"), vec![r.to_term()]),
+            Refinement::ImpliesProps(r1, r2) => {
+                let premise = Term::apps(Term::var("not"), vec![r1.to_term()]);
+                Term::apps(Term::var("or"), vec![premise, r2.to_term()])
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -25,6 +44,22 @@ pub enum Term {
 }
 
 impl Term {
+    pub fn var(id: &str) -> Term {
+        Term::VarTerm(Box::new(id.to_string()))
+    }
+
+    // Curried application of f to the arguments: f(a, b) is ((f a) b)
+    pub fn apps(f: Term, args: Vec<Term>) -> Term {
+        args.into_iter().fold(f, |t, arg| Term::AppTerm(Box::new(t), Box::new(arg)))
+    }
+
+    pub fn is_int_literal(&self) -> bool {
+        match self {
+            Term::LitTerm(lit) => lit.parse::<i64>().is_ok(),
+            _ => false
+        }
+    }
+
     pub fn is_quantifier(&self) -> bool {
         match self {
             Term::VarTerm(id) => {
@@ -63,10 +98,49 @@ impl ToString for Term {
     }
 }
 
+// The relative frequencies of the operations on a container type
+pub type OpFreqs = Vec<(Id, u64)>;
+
+// The expected usage of a container type, used to rank the matching library structs,
+// e.g. workload { insert: 60, contains: 40, size: 1000000 }, also written with { ... } as it
+// was before it gave the size
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Workload {
+    pub ops: OpFreqs,
+    pub size: Option<u64>, // the expected number of elements
+}
+
+impl Workload {
+    // The entry size gives the number of elements, the others the frequencies of operations
+    pub fn new(entries: Vec<(Id, u64)>) -> Workload {
+        let mut workload = Workload::default();
+        for (key, n) in entries.into_iter() {
+            match key.as_str() {
+                "size" => workload.size = Some(n),
+                _ => workload.ops.push((key, n))
+            }
+        }
+        workload
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty() && self.size.is_none()
+    }
+}
+
+// How the generated container type reaches its implementation: through a boxed trait
+// object, or through a wrapper calling the chosen struct directly, e.g. dispatch static
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dispatch {
+    #[default]
+    Dynamic,
+    Static,
+}
+
 #[derive(Clone, Debug)]
 pub enum Decl {
     PropertyDecl((Box<Id>, Box<Type>), Box<Term>),
-    ConTypeDecl(Box<Type>, (Box<Id>, Box<Bounds>, Box<Refinement>))
+    ConTypeDecl(Box<Type>, (Box<Id>, Box<Bounds>, Box<Refinement>), Box<Workload>, Option<Dispatch>)
 }
 
 impl Decl {
@@ -79,14 +153,14 @@ impl Decl {
 
     pub fn is_contype_decl(&self) -> bool {
         match self {
-            Decl::ConTypeDecl(_, _) => true,
+            Decl::ConTypeDecl(_, _, _, _) => true,
             _ => false
         }
     }
 
     pub fn get_name(&self) -> String {
         match self {
-            Decl::ConTypeDecl(con_ty, _) => {
+            Decl::ConTypeDecl(con_ty, _, _, _) => {
                 let (con, _) = con_ty.get_con_elem().unwrap();
                 con 
             },
@@ -152,7 +226,7 @@ pub grammar spec() for str {
             ![ 'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '?' ]
 
     pub rule literal() -> Literal
-        = s:$("true" / "false")
+        = s:$("true" / "false" / "-"? ['0'..='9']+)
         { s.into() }
     
     pub rule ty() -> Type
@@ -164,29 +238,70 @@ pub grammar spec() for str {
             { Type::Var(TypeVar::new(n)) }
         }
   
+    // The operators desugar into applications of the predefined combinators
     pub rule term() -> Term
         = precedence!{
-            lit: literal() { Term::LitTerm(Box::new(lit)) }
+            x:(@) _ "||" _ y:@ { Term::apps(Term::var("or"), vec![x, y]) }
             --
-            v:id() { Term::VarTerm(Box::new(v)) }
+            x:(@) _ "&&" _ y:@ { Term::apps(Term::var("and"), vec![x, y]) }
             --
-            "\\" v:id() _ "->" _ t:term() { Term::LambdaTerm((Box::new(v), Box::new(Bounds::new())), Box::new(t)) }
+            x:(@) _ "==" _ y:@ { Term::apps(Term::var("equal?"), vec![x, y]) }
+            x:(@) _ "!=" _ y:@ { Term::apps(Term::var("neq"), vec![x, y]) }
+            x:(@) _ "<=" _ y:@ { Term::apps(Term::var("leq?"), vec![x, y]) }
+            x:(@) _ ">=" _ y:@ { Term::apps(Term::var("geq?"), vec![x, y]) }
+            x:(@) _ "<" _ y:@ { Term::apps(Term::var("<"), vec![x, y]) }
+            x:(@) _ ">" _ y:@ { Term::apps(Term::var(">"), vec![x, y]) }
             --
-            "\\" v:id() _ "<:" _ "(" _ b:bounds() _ ")" _ "->" _ t:term() { Term::LambdaTerm((Box::new(v), Box::new(b)), Box::new(t)) }
+            x:(@) _ "+" _ y:@ { Term::apps(Term::var("+"), vec![x, y]) }
+            x:(@) _ "-" _ y:@ { Term::apps(Term::var("-"), vec![x, y]) }
             --
-            "(" _ t1:term() __ t2:term() _ ")" { Term::AppTerm(Box::new(t1), Box::new(t2)) }
+            x:(@) _ "*" _ y:@ { Term::apps(Term::var("*"), vec![x, y]) }
+            --
+            "!" _ x:@ { Term::apps(Term::var("not"), vec![x]) }
+            --
+            t:atom() { t }
         }
-    
+
+    rule atom() -> Term
+        = lit: literal() { Term::LitTerm(Box::new(lit)) }
+        / f:id() "(" _ args:(term() ++ (_ "," _)) _ ")" { Term::apps(Term::VarTerm(Box::new(f)), args) }
+        / v:id() { Term::VarTerm(Box::new(v)) }
+        / "\\" v:id() _ "->" _ t:term() { Term::LambdaTerm((Box::new(v), Box::new(Bounds::new())), Box::new(t)) }
+        / "\\" v:id() _ "<:" _ "(" _ b:bounds() _ ")" _ "->" _ t:term() { Term::LambdaTerm((Box::new(v), Box::new(b)), Box::new(t)) }
+        / "if" __ c:term() _ "{" _ t:term() _ "}" _ "else" _ "{" _ e:term() _ "}" { Term::apps(Term::var("if"), vec![c, t, e]) }
+        // let x = e in t is (\x -> t) e
+        / "let" __ v:id() _ "=" _ e:term() __ "in" __ t:term()
+        {
+            Term::AppTerm(Box::new(Term::LambdaTerm((Box::new(v), Box::new(Bounds::new())), Box::new(t))), Box::new(e))
+        }
+        / "(" _ t1:term() __ t2:term() _ ")" { Term::AppTerm(Box::new(t1), Box::new(t2)) }
+        / "(" _ t:term() _ ")" { t }
+
     pub rule refinement() -> Refinement
         = precedence!{
             t:term() { Refinement::Prop(Box::new(t)) }
             --
             "(" _ p1:refinement() __ "and" __ p2:refinement() _ ")" { Refinement::AndProps(Box::new(p1), Box::new(p2)) }
+            "(" _ p1:refinement() __ "or" __ p2:refinement() _ ")" { Refinement::OrProps(Box::new(p1), Box::new(p2)) }
+            "(" _ p1:refinement() _ "=>" _ p2:refinement() _ ")" { Refinement::ImpliesProps(Box::new(p1), Box::new(p2)) }
+            "(" _ "not" __ p:refinement() _ ")" { Refinement::NotProp(Box::new(p)) }
         }
 
     pub rule bounds() -> Bounds
         = l: ((_ n:name() _ {n}) ++ "," ) { Bounds::from_iter(l.iter().cloned()) }
 
+    // a weight or size too large for a u64 is an error, not clamped
+    rule workload() -> Workload
+        = ("workload" / "with") _ "{" _ l:((_ k:id() _ ":" _ n:weight() _ { (k, n) }) ** ",") _ "}"
+        { Workload::new(l) }
+
+    rule weight() -> u64
+        = n:$(['0'..='9']+) {? n.parse::<u64>().or(Err("a workload weight or size of at most 18446744073709551615")) }
+
+    rule dispatch() -> Dispatch
+        = "dispatch" __ "static" { Dispatch::Static }
+        / "dispatch" __ "dynamic" { Dispatch::Dynamic }
+
     pub rule decl() -> Decl
         = precedence! {
             _ "property" __ p:id() _ "<" _ ty:ty() _ ">" _ "{" _ t:term() _ "}" _ 
@@ -194,9 +309,9 @@ pub grammar spec() for str {
                 Decl::PropertyDecl((Box::new(p), Box::new(ty)), Box::new(t))
             }
             --
-            _ "type" __ ty:ty() _ "=" _ "{" _ c:id() _ "impl" __ "(" _ b:bounds() _ ")" _ "|" _ t:refinement() _ "}" _
+            _ "type" __ ty:ty() _ "=" _ "{" _ c:id() _ "impl" __ "(" _ b:bounds() _ ")" _ "|" _ t:refinement() _ "}" _ w:workload()? _ d:dispatch()? _
             {
-                Decl::ConTypeDecl(Box::new(ty), (Box::new(c), Box::new(b), Box::new(t)))
+                Decl::ConTypeDecl(Box::new(ty), (Box::new(c), Box::new(b), Box::new(t)), Box::new(w.unwrap_or_default()), d)
             }
         }
 
@@ -405,4 +520,73 @@ pub grammar spec() for str {
 //             /*ENDCODE*/"#
 //         ).is_ok())
 //     }
-// }
\ No newline at end of file
+// }
+#[cfg(test)]
+mod surface_tests {
+    use crate::parser::{Decl, Dispatch, Term, spec};
+
+    fn desugar(t: &str) -> String {
+        format!("{:?}", spec::term(t).unwrap())
+    }
+
+    #[test]
+    fn test_call_and_comparison() {
+        assert_eq!(desugar(r#"\x -> pop(push(c, x)) == x"#), desugar(r#"\x -> ((equal? (pop ((push c) x))) x)"#));
+        assert_eq!(desugar("a != b"), desugar("((neq a) b)"));
+    }
+
+    #[test]
+    fn test_boolean_operators() {
+        assert_eq!(desugar("a <= b && b >= a || !c"), desugar("((or ((and ((leq? a) b)) ((geq? b) a))) (not c))"));
+        assert_eq!(desugar("!(a == b)"), desugar("(not ((equal? a) b))"));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let t = |s: &str| spec::term(s).unwrap();
+        let product = Term::apps(Term::var("*"), vec![t("1"), t("2")]);
+        let sum = Term::apps(Term::var("+"), vec![t("(length c)"), product]);
+        assert_eq!(desugar("length(c) + 1 * 2 < 64"), format!("{:?}", Term::apps(Term::var("<"), vec![sum, t("64")])));
+        let diff = Term::apps(Term::var("-"), vec![t("a"), t("-1")]);
+        assert_eq!(desugar("a - -1 > 0"), format!("{:?}", Term::apps(Term::var(">"), vec![diff, t("0")])));
+    }
+
+    #[test]
+    fn test_refinement_to_term() {
+        let r = spec::refinement("((unique c) => (not ((ascending c) or (descending c))))").unwrap();
+        assert_eq!(format!("{:?}", r.to_term()), desugar("!unique(c) || !(ascending(c) || descending(c))"));
+    }
+
+    #[test]
+    fn test_if_let() {
+        let branches = vec![spec::term("(is-empty c)").unwrap(), spec::term("true").unwrap(), spec::term("((equal? x) y)").unwrap()];
+        assert_eq!(desugar("if is-empty(c) { true } else { x == y }"), format!("{:?}", Term::apps(Term::var("if"), branches)));
+        assert_eq!(desugar("let y = f(x) in y == x"), desugar(r#"((\y -> ((equal? y) x)) (f x))"#));
+    }
+
+    #[test]
+    fn test_workload() {
+        let workload = |s: &str| match spec::decl(s).unwrap() {
+            Decl::ConTypeDecl(_, _, w, _) => *w,
+            _ => unreachable!()
+        };
+        let w = workload("type S<T> = {c impl (Container) | (unique c)} workload { insert: 60, contains: 40, size: 1000000 }");
+        assert_eq!(w.ops, vec![("insert".to_string(), 60), ("contains".to_string(), 40)]);
+        assert_eq!(w.size, Some(1000000));
+        assert_eq!(workload("type S<T> = {c impl (Container) | (unique c)} with { insert: 60, contains: 40 }").ops, w.ops);
+        assert!(workload("type S<T> = {c impl (Container) | (unique c)}").is_empty());
+        let e = spec::decl("type S<T> = {c impl (Container) | (unique c)} workload { insert: 18446744073709551616 }").unwrap_err();
+        assert!(e.expected.to_string().contains("a workload weight or size of at most"), "{}", e);
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let dispatch = |s: &str| match spec::decl(s).unwrap() {
+            Decl::ConTypeDecl(_, _, _, d) => d,
+            _ => unreachable!()
+        };
+        assert_eq!(dispatch("type S<T> = {c impl (Container) | (unique c)} workload { insert: 1 } dispatch static"), Some(Dispatch::Static));
+        assert_eq!(dispatch("type S<T> = {c impl (Container) | (unique c)} dispatch dynamic"), Some(Dispatch::Dynamic));
+        assert_eq!(dispatch("type S<T> = {c impl (Container) | (unique c)}"), None);
+    }
+}
@@ -0,0 +1,287 @@
+// Translating the property terms of the specifications into Rust predicates, checked against
+// the containers of the generated code. A predicate is a closure taking the implementation of
+// a container type by mutable reference and giving whether the property holds of it, e.g.
+//
+//     \c -> ((for-all-consecutive-pairs c) leq?)
+//
+// becomes
+//
+//     |c: &mut Vec<T>| { let l: Vec<_> = ...; l.windows(2).all(|w1| { let (e2, e3) = (&w1[0], &w1[1]); e2 <= e3 }) }
+//
+// The container is seen as the list its ModelAbstraction gives, and push and pop call its
+// Stack implementation. A quantifier forall ranges over the elements of the container, the
+// values of the element type at hand. Terms outside this fragment, e.g. the first element
+// of a list, which is undefined for the empty list, are not translated.
+use crate::parser::{Term};
+use crate::spec_map::{PropTerms};
+
+const TRAITCRATE: &str = "preprocess::traits::";
+const CON: &str = "&mut *c"; // the container, reborrowed so that it can be used again
+
+type ErrorMessage = String;
+
+// The Rust type of a translated term
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    Bool,
+    Int,     // i64
+    Elem,    // &T
+    OptElem, // Option<T>, the result of pop
+    Con,     // &mut C, after the pushes made by its code
+}
+
+type Code = (Kind, String);
+
+// The variables of the term in scope, innermost last
+type Env = Vec<(String, Code)>;
+
+fn lookup(env: &Env, id: &str) -> Option<Code> {
+    env.iter().rev().find(|(v, _)| v == id).map(|(_, code)| code.clone())
+}
+
+// The code without its outer parentheses, where they are not needed and rustc would warn of
+// them, e.g. as the body of a closure
+fn unparen(code: &str) -> &str {
+    if !code.starts_with('(') {
+        return code;
+    }
+    let mut depth = 0;
+    for (i, ch) in code.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => ()
+        }
+        if depth == 0 {
+            return if i == code.len() - 1 { &code[1..i] } else { code };
+        }
+    }
+    code
+}
+
+fn untranslatable(id: &str, n: usize) -> ErrorMessage {
+    format!("Error, unable to translate {} applied to {} argument(s) into Rust", id, n)
+}
+
+struct Translator<'a> {
+    props: &'a PropTerms,
+    fresh: usize,     // the number of Rust variables introduced so far
+    uses_list: bool,  // whether the contents of the container are needed
+    uses_con: bool,   // whether the container itself is called
+}
+
+impl Translator<'_> {
+    fn fresh(&mut self, prefix: &str) -> String {
+        self.fresh += 1;
+        prefix.to_string() + &self.fresh.to_string()
+    }
+
+    fn term(&mut self, t: &Term, env: &mut Env) -> Result<Code, ErrorMessage> {
+        match t {
+            Term::LitTerm(lit) => match lit.parse::<i64>() {
+                Ok(n) => Ok((Kind::Int, n.to_string() + "i64")),
+                Err(_) if lit.as_str() == "true" || lit.as_str() == "false" => Ok((Kind::Bool, lit.to_string())),
+                Err(_) => Err("Error, unable to translate the literal ".to_string() + lit + " into Rust")
+            },
+            Term::VarTerm(id) => lookup(env, id).ok_or_else(|| untranslatable(id, 0)),
+            Term::LambdaTerm((id, _), _) => Err("Error, unable to translate the function of ".to_string() + id + " into a Rust value"),
+            Term::AppTerm(_, _) => {
+                // ((f a) b) is f applied to a and b
+                let mut args = Vec::<&Term>::new();
+                let mut head = t;
+                while let Term::AppTerm(t1, t2) = head {
+                    args.insert(0, t2);
+                    head = t1;
+                }
+                match head {
+                    Term::VarTerm(id) if lookup(env, id).is_none() => self.prim(id, &args, env),
+                    Term::LambdaTerm(_, _) => {
+                        let codes = args.iter().map(|a| self.term(a, env)).collect::<Result<Vec<Code>, ErrorMessage>>()?;
+                        self.apply(head, codes, env)
+                    },
+                    _ => Err(untranslatable(&head.to_string(), args.len()))
+                }
+            }
+        }
+    }
+
+    // A function term applied to translated arguments
+    fn apply(&mut self, f: &Term, mut args: Vec<Code>, env: &mut Env) -> Result<Code, ErrorMessage> {
+        if args.is_empty() {
+            return self.term(f, env);
+        }
+        match f {
+            Term::LambdaTerm((id, _), body) => {
+                env.push((id.to_string(), args.remove(0)));
+                let result = self.apply(body, args, env);
+                env.pop();
+                result
+            },
+            Term::VarTerm(id) if lookup(env, id).is_none() => match self.props.get(id.as_str()) {
+                Some(prop) => self.apply(prop, args, &mut Env::new()),
+                None => self.call(id, args)
+            },
+            _ => Err(untranslatable(&f.to_string(), args.len()))
+        }
+    }
+
+    // The quantifiers and the combinators taking functions, with the terms of their arguments
+    fn prim(&mut self, id: &str, args: &[&Term], env: &mut Env) -> Result<Code, ErrorMessage> {
+        match (id, args) {
+            ("forall", [f]) => {
+                let l = self.list((Kind::Con, CON.to_string()))?;
+                let e = self.fresh("e");
+                let body = self.predicate(f, vec![(Kind::Elem, e.clone())], env)?;
+                Ok((Kind::Bool, format!("{l}.iter().all(|{e}| {body})")))
+            },
+            ("for-all-elems", [c, f]) => {
+                let c = self.term(c, env)?;
+                let l = self.list(c)?;
+                let e = self.fresh("e");
+                let body = self.predicate(f, vec![(Kind::Elem, e.clone())], env)?;
+                Ok((Kind::Bool, format!("{l}.iter().all(|{e}| {body})")))
+            },
+            ("for-all-consecutive-pairs", [c, f]) => {
+                let c = self.term(c, env)?;
+                let l = self.list(c)?;
+                let (w, a, b) = (self.fresh("w"), self.fresh("e"), self.fresh("e"));
+                let body = self.predicate(f, vec![(Kind::Elem, a.clone()), (Kind::Elem, b.clone())], env)?;
+                Ok((Kind::Bool, format!("{l}.windows(2).all(|{w}| {{ let ({a}, {b}) = (&{w}[0], &{w}[1]); {body} }})")))
+            },
+            ("for-all-unique-pairs", [c, f]) => {
+                let c = self.term(c, env)?;
+                let l = self.list(c)?;
+                let (i, a, j, b) = (self.fresh("i"), self.fresh("e"), self.fresh("i"), self.fresh("e"));
+                let body = self.predicate(f, vec![(Kind::Elem, a.clone()), (Kind::Elem, b.clone())], env)?;
+                Ok((Kind::Bool, format!("{l}.iter().enumerate().all(|({i}, {a})| {l}.iter().enumerate().all(|({j}, {b})| {i} == {j} || ({body})))")))
+            },
+            _ => {
+                let codes = args.iter().map(|a| self.term(a, env)).collect::<Result<Vec<Code>, ErrorMessage>>()?;
+                match self.props.get(id) {
+                    Some(prop) => self.apply(prop, codes, &mut Env::new()),
+                    None => self.call(id, codes)
+                }
+            }
+        }
+    }
+
+    fn predicate(&mut self, f: &Term, args: Vec<Code>, env: &mut Env) -> Result<String, ErrorMessage> {
+        match self.apply(f, args, env)? {
+            (Kind::Bool, code) => Ok(unparen(&code).to_string()),
+            _ => Err("Error, unable to translate ".to_string() + &f.to_string() + " into a Rust predicate, it is not boolean")
+        }
+    }
+
+    // The contents of a container, as a Vec
+    fn list(&mut self, (kind, code): Code) -> Result<String, ErrorMessage> {
+        if kind == Kind::Con && code == CON {
+            self.uses_list = true;
+            Ok("l".to_string())
+        } else if kind == Kind::Con {
+            Err("Error, unable to translate the contents of a container after push into Rust".to_string())
+        } else {
+            Err("Error, unable to translate a list operation on a value other than the container into Rust".to_string())
+        }
+    }
+
+    // The primitives taking values, with their translated arguments
+    fn call(&mut self, id: &str, args: Vec<Code>) -> Result<Code, ErrorMessage> {
+        let n = args.len();
+        let mut args = args.into_iter();
+        let (a, b, c) = (args.next(), args.next(), args.next());
+        match (id, a, b, c) {
+            ("and", Some((Kind::Bool, p)), Some((Kind::Bool, q)), None) => Ok((Kind::Bool, format!("({p} && {q})"))),
+            ("or", Some((Kind::Bool, p)), Some((Kind::Bool, q)), None) => Ok((Kind::Bool, format!("({p} || {q})"))),
+            ("not", Some((Kind::Bool, p)), None, None) => Ok((Kind::Bool, format!("!{p}"))),
+            ("if", Some((Kind::Bool, p)), Some((k1, t)), Some((k2, e))) if k1 == k2 => Ok((k1, format!("(if {} {{ {} }} else {{ {} }})", unparen(&p), unparen(&t), unparen(&e)))),
+            ("length", Some(c), None, None) => Ok((Kind::Int, format!("({}.len() as i64)", self.list(c)?))),
+            ("null?" | "empty?", Some(c), None, None) => Ok((Kind::Bool, format!("{}.is_empty()", self.list(c)?))),
+            ("unique-count?", Some((Kind::Elem, x)), Some(c), None) => {
+                let l = self.list(c)?;
+                let y = self.fresh("y");
+                Ok((Kind::Bool, format!("({l}.iter().filter(|{y}| *{y} == {x}).count() == 1)")))
+            },
+            ("push", Some((Kind::Con, c)), Some((Kind::Elem, x)), None) => {
+                self.uses_con = true;
+                let d = self.fresh("d");
+                Ok((Kind::Con, format!("{{ let {d} = {c}; {TRAITCRATE}Stack::push({d}, {x}.clone()); {d} }}")))
+            },
+            ("pop", Some((Kind::Con, c)), None, None) => {
+                self.uses_con = true;
+                Ok((Kind::OptElem, format!("{TRAITCRATE}Stack::pop({c})")))
+            },
+            ("+" | "-" | "*", Some((Kind::Int, x)), Some((Kind::Int, y)), None) => Ok((Kind::Int, format!("({x} {id} {y})"))),
+            (_, Some(x), Some(y), None) => match comparison(id) {
+                Some(op) => compare(op, x, y).ok_or_else(|| untranslatable(id, n)),
+                None => Err(untranslatable(id, n))
+            },
+            _ => Err(untranslatable(id, n))
+        }
+    }
+}
+
+// The Rust operator of a comparison
+fn comparison(id: &str) -> Option<&'static str> {
+    match id {
+        "equal?" | "=" => Some("=="),
+        "not-equal?" | "neq" => Some("!="),
+        "leq?" | "<=" => Some("<="),
+        "geq?" | ">=" => Some(">="),
+        "<" => Some("<"),
+        ">" => Some(">"),
+        _ => None
+    }
+}
+
+fn compare(op: &str, (k1, x): Code, (k2, y): Code) -> Option<Code> {
+    match (k1, k2) {
+        (Kind::Elem, Kind::Elem) | (Kind::Int, Kind::Int) | (Kind::Bool, Kind::Bool) => Some((Kind::Bool, format!("({x} {op} {y})"))),
+        // the result of pop is none on the empty container, equal to no element
+        (Kind::OptElem, Kind::Elem) if op == "==" || op == "!=" => Some((Kind::Bool, format!("({x}.as_ref() {op} Some({y}))"))),
+        (Kind::Elem, Kind::OptElem) if op == "==" || op == "!=" => Some((Kind::Bool, format!("(Some({x}) {op} {y}.as_ref())"))),
+        _ => None
+    }
+}
+
+// The Rust predicate of a property over the implementation impl_ty of a container type, where
+// props are the properties it may refer to
+pub fn gen_predicate(term: &Term, impl_ty: &str, props: &PropTerms) -> Result<String, ErrorMessage> {
+    let mut translator = Translator { props, fresh: 0, uses_list: false, uses_con: false };
+    let body = match translator.apply(term, vec![(Kind::Con, CON.to_string())], &mut Env::new())? {
+        (Kind::Bool, body) => unparen(&body).to_string(),
+        _ => return Err("Error, unable to translate a property which is not boolean into a Rust predicate".to_string())
+    };
+    let param = if translator.uses_list || translator.uses_con { "c" } else { "_c" };
+    let contents = if translator.uses_list {
+        format!("let l: Vec<_> = {TRAITCRATE}ModelAbstraction::abstraction(&*c).iter().map(|x| (*x).clone()).collect(); ")
+    } else {
+        String::new()
+    };
+    Ok(format!("|{param}: &mut {impl_ty}| {{ {contents}{body} }}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::{spec};
+    use crate::predicates::{gen_predicate};
+    use crate::spec_map::{PropTerms};
+
+    fn translate(term: &str, props: &PropTerms) -> Result<String, String> {
+        gen_predicate(&spec::term(term).unwrap(), "Vec<T>", props)
+    }
+
+    #[test]
+    fn test_gen_predicate() {
+        let mut props = PropTerms::new();
+        let unique = translate(r#"\c -> ((for-all-elems c) \a -> ((unique-count? a) c))"#, &props).unwrap();
+        assert!(unique.starts_with("|c: &mut Vec<T>| { let l: Vec<_> = preprocess::traits::ModelAbstraction::abstraction(&*c)"));
+        assert!(unique.ends_with("l.iter().all(|e1| l.iter().filter(|y2| *y2 == e1).count() == 1) }"));
+        let lifo = translate(r#"\c -> (forall \x -> ((equal? (pop ((push c) x))) x))"#, &props).unwrap();
+        assert!(lifo.contains("l.iter().all(|e1| preprocess::traits::Stack::pop({ let d2 = &mut *c; preprocess::traits::Stack::push(d2, e1.clone()); d2 }).as_ref() == Some(e1))"));
+        props.insert("ascending".to_string(), spec::term(r#"\c -> ((for-all-consecutive-pairs c) leq?)"#).unwrap());
+        let refinement = translate(r#"\c -> (not (ascending c))"#, &props).unwrap();
+        assert!(refinement.ends_with("!l.windows(2).all(|w1| { let (e2, e3) = (&w1[0], &w1[1]); e2 <= e3 }) }"));
+        assert_eq!(translate(r#"\c -> true"#, &props).unwrap(), "|_c: &mut Vec<T>| { true }");
+        assert!(translate(r#"\c -> ((leq? (first c)) 1)"#, &props).is_err());
+    }
+}
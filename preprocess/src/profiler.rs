@@ -0,0 +1,277 @@
+// Recording how the containers of a program are used. A generated container type
+// wraps its implementation in a Recorder, which counts the calls of each operation
+// and samples the size of the container, and appends its profile to
+// <dir>/<container type>.profile when dropped. The profiles are read back as the
+// workload of the container type to select its implementation.
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+
+use crate::parser::{Workload};
+use crate::traits::{Container, Stack, RandomAccess};
+
+pub const PROFILEDIR: &str = "./profiles/";
+const SAMPLES: usize = 64; // the number of sizes kept, at most twice as many before halving
+
+type ErrorMessage = String;
+
+pub struct Recorder<C> {
+    inner: C,
+    con: &'static str,
+    lib_struct: &'static str,
+    dir: String,
+    calls: BTreeMap<&'static str, u64>,
+    n: u64,             // the number of calls
+    interval: u64,      // the size is sampled every interval calls
+    size: usize,        // the size of the container, as tracked from the calls
+    sizes: Vec<usize>,
+}
+
+impl<C> Recorder<C> {
+    pub fn new(inner: C, con: &'static str, lib_struct: &'static str) -> Recorder<C> {
+        Recorder::with_dir(inner, con, lib_struct, PROFILEDIR)
+    }
+
+    pub fn with_dir(inner: C, con: &'static str, lib_struct: &'static str, dir: &str) -> Recorder<C> {
+        Recorder {
+            inner,
+            con,
+            lib_struct,
+            dir: dir.to_string(),
+            calls: BTreeMap::new(),
+            n: 0,
+            interval: 1,
+            size: 0,
+            sizes: Vec::new(),
+        }
+    }
+
+    // The size is not asked of the container, as len may sort or dedup a lazy struct and
+    // so change what is measured: it is tracked from the calls, counting an insert as
+    // adding an element even if the struct drops a duplicate, and set again whenever the
+    // program itself calls len
+    fn record(&mut self, op: &'static str) {
+        *self.calls.entry(op).or_insert(0) += 1;
+        self.n += 1;
+        if self.n.is_multiple_of(self.interval) {
+            self.sizes.push(self.size);
+            if self.sizes.len() == 2 * SAMPLES {
+                // keep every other sample, sampling half as often from now on
+                self.sizes = self.sizes.iter().skip(1).step_by(2).cloned().collect();
+                self.interval *= 2;
+            }
+        }
+    }
+
+    // The profile line: container type, struct, op=count,... and the sampled sizes
+    fn profile(&self) -> String {
+        let calls = self.calls.iter().map(|(op, n)| format!("{}={}", op, n)).collect::<Vec<String>>().join(",");
+        let sizes = self.sizes.iter().map(|s| s.to_string()).collect::<Vec<String>>().join(",");
+        [self.con, self.lib_struct, &calls, &sizes].join("\t")
+    }
+}
+
+impl<C> Drop for Recorder<C> {
+    fn drop(&mut self) {
+        if self.n == 0 || fs::create_dir_all(&self.dir).is_err() {
+            return;
+        }
+        let path = self.dir.trim_end_matches('/').to_string() + "/" + self.con + ".profile";
+        if let Ok(mut output) = fs::OpenOptions::new().create(true).append(true).open(path) {
+            let _ = writeln!(output, "{}", self.profile());
+        }
+    }
+}
+
+impl<T, C: Container<T>> Container<T> for Recorder<C> {
+    fn len(&mut self) -> usize {
+        self.record("len");
+        self.size = self.inner.len();
+        self.size
+    }
+
+    fn contains(&mut self, x: &T) -> bool {
+        self.record("contains");
+        self.inner.contains(x)
+    }
+
+    fn is_empty(&mut self) -> bool {
+        self.record("is-empty");
+        let empty = self.inner.is_empty();
+        if empty {
+            self.size = 0;
+        }
+        empty
+    }
+
+    fn insert(&mut self, elt: T) {
+        self.record("insert");
+        self.inner.insert(elt);
+        self.size += 1;
+    }
+
+    fn clear(&mut self) {
+        self.record("clear");
+        self.inner.clear();
+        self.size = 0;
+    }
+
+    fn remove(&mut self, elt: T) -> Option<T> {
+        self.record("remove");
+        let result = self.inner.remove(elt);
+        if result.is_some() {
+            self.size = self.size.saturating_sub(1);
+        }
+        result
+    }
+}
+
+impl<T, C: Container<T> + Stack<T>> Stack<T> for Recorder<C> {
+    fn push(&mut self, elt: T) {
+        self.record("push");
+        self.inner.push(elt);
+        self.size += 1;
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.record("pop");
+        let result = self.inner.pop();
+        if result.is_some() {
+            self.size = self.size.saturating_sub(1);
+        }
+        result
+    }
+}
+
+impl<T, C: Container<T> + RandomAccess<T>> RandomAccess<T> for Recorder<C> {
+    fn first(&mut self) -> Option<&T> {
+        self.record("first");
+        self.inner.first()
+    }
+
+    fn last(&mut self) -> Option<&T> {
+        self.record("last");
+        self.inner.last()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<&T> {
+        self.record("nth");
+        self.inner.nth(n)
+    }
+}
+
+// The workload recorded for a container type, summing the calls of all of its profiled
+// containers, with the mean of their sampled sizes; None if it has not been profiled.
+// Also gives the number of profiled containers.
+pub fn read_profile(dir: &str, con: &str) -> Result<Option<(Workload, usize)>, ErrorMessage> {
+    let path = dir.trim_end_matches('/').to_string() + "/" + con + ".profile";
+    let contents = match fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(_) => return Ok(None)
+    };
+    let mut calls = BTreeMap::<String, u64>::new();
+    let mut sizes = Vec::<u64>::new();
+    let mut instances = 0;
+    for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+        let invalid = || "Error, invalid profile in ".to_string() + &path + ": " + line;
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != 4 || fields[0] != con {
+            return Err(invalid());
+        }
+        for call in fields[2].split(',').filter(|c| !c.is_empty()) {
+            match call.split_once('=').and_then(|(op, n)| n.parse::<u64>().ok().map(|n| (op, n))) {
+                Some((op, n)) => *calls.entry(op.to_string()).or_insert(0) += n,
+                None => return Err(invalid())
+            }
+        }
+        for size in fields[3].split(',').filter(|s| !s.is_empty()) {
+            sizes.push(size.parse::<u64>().map_err(|_| invalid())?);
+        }
+        instances += 1;
+    }
+    let size = if sizes.is_empty() {
+        None
+    } else {
+        Some((sizes.iter().sum::<u64>() / sizes.len() as u64).max(1))
+    };
+    Ok(Some((Workload { ops: calls.into_iter().collect(), size }, instances)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::profiler::{Recorder, read_profile};
+    use crate::traits::{Container, Stack};
+
+    #[test]
+    fn test_record_and_read() {
+        let dir = std::env::temp_dir().join("preprocess-profile-test");
+        let dir = dir.to_str().unwrap();
+        let _ = fs::remove_dir_all(dir);
+        for _ in 0..2 {
+            let mut c = Recorder::with_dir(Vec::<u32>::new(), "StackCon", "std::vec::Vec", dir);
+            for x in 0..300 {
+                c.push(x);
+            }
+            assert!(c.contains(&7));
+            c.pop();
+        }
+        let (workload, instances) = read_profile(dir, "StackCon").unwrap().unwrap();
+        assert_eq!(instances, 2);
+        let expected: Vec<(String, u64)> = vec![("contains".to_string(), 2), ("pop".to_string(), 2), ("push".to_string(), 600)];
+        assert_eq!(workload.ops, expected);
+        assert!(workload.size.unwrap() > 100 && workload.size.unwrap() < 200);
+        assert!(read_profile(dir, "Other").unwrap().is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    // A struct whose len must not be called, as for a lazy struct it would change the struct
+    struct NoLen(Vec<u32>);
+
+    impl Container<u32> for NoLen {
+        fn len(&mut self) -> usize {
+            panic!("len called")
+        }
+
+        fn contains(&mut self, x: &u32) -> bool {
+            self.0.contains(x)
+        }
+
+        fn is_empty(&mut self) -> bool {
+            self.0.is_empty()
+        }
+
+        fn insert(&mut self, elt: u32) {
+            self.0.push(elt)
+        }
+
+        fn clear(&mut self) {
+            self.0.clear()
+        }
+
+        fn remove(&mut self, elt: u32) -> Option<u32> {
+            Container::remove(&mut self.0, elt)
+        }
+    }
+
+    #[test]
+    fn test_size_is_tracked() {
+        let dir = std::env::temp_dir().join("preprocess-profile-size-test");
+        let dir = dir.to_str().unwrap();
+        let _ = fs::remove_dir_all(dir);
+        {
+            let mut c = Recorder::with_dir(NoLen(Vec::new()), "SetCon", "NoLen", dir);
+            for x in 0..100 {
+                c.insert(x);
+            }
+            for x in 0..100 {
+                assert_eq!(c.remove(x), Some(x));
+                assert_eq!(c.remove(x), None);
+            }
+            c.insert(1);
+        }
+        let (workload, _) = read_profile(dir, "SetCon").unwrap().unwrap();
+        assert!(workload.size.unwrap() > 25 && workload.size.unwrap() < 75);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
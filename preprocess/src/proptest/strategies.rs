@@ -6,9 +6,12 @@ use crate::library::eager_unique_vector::{EagerUniqueVec};
 use crate::library::eager_sorted_vector::{EagerSortedVec};
 use crate::library::lazy_unique_vector::{LazyUniqueVec};
 use crate::library::lazy_sorted_vector::{LazySortedVec};
+use crate::library::adaptive_set::{AdaptiveSet};
 use proptest::collection::vec;
 use proptest::prelude::*;
 use im::conslist::{ConsList};
+use crate::differential::{Op, op_strategy};
+use crate::types::{bound_op_names};
 
 
 pub fn eager_unique_vec<T: Strategy + 'static>(element: T, size: Range<usize>) -> impl Strategy<Value = EagerUniqueVec<T::Value>>
@@ -35,3 +38,23 @@ where <T as Strategy>::Value: Ord
 {
     vec(element, size.clone()).prop_map(LazySortedVec::from_vec)
 }
+
+// Built by inserting with low thresholds, so that both representations are covered
+pub fn adaptive_set<T: Strategy + 'static>(element: T, size: Range<usize>) -> impl Strategy<Value = AdaptiveSet<T::Value>>
+where <T as Strategy>::Value: Ord
+{
+    (vec(element, size.clone()), 1usize..32, 1usize..8).prop_map(|(v, small, window)| {
+        let mut s = AdaptiveSet::with_thresholds(small, 2 * small, window);
+        for x in v {
+            s.insert(x);
+        }
+        s
+    })
+}
+
+// Sequences of the operations of the bounds of a container type, e.g. Container and Stack,
+// on few elements so that they repeat
+pub fn bound_ops(bounds: &[&str], len: Range<usize>) -> impl Strategy<Value = Vec<Op>> {
+    let names = bounds.iter().filter_map(|b| bound_op_names(b)).flatten().map(|n| n.to_string()).collect();
+    vec(op_strategy(names), len)
+}
@@ -4,17 +4,32 @@ use std::fs;
 use std::io::{Write, BufReader, BufRead, Error, ErrorKind};
 
 use crate::spec_map::{MatchSetup};
+use crate::match_cache::{MatchResult, decode_result};
 
 type ExecutionError = String;
 
 pub const LANGDECL: &str = "#lang rosette\n";
-const GENNAME: &str = "./racket_specs/gen_match/match-script.rkt";
+const GENNAME: &str = "match-script.rkt";
+const ORDERNAME: &str = "order-script.rkt";
 const LIBSPECPATH: &str = "../gen_lib_spec/";
-const PROPSPECPATH: &str = "../gen_prop_spec/";
 //const SETUP: &str = "(require \"../match-setup.rkt\")\n";
-const LIBDIR: &str =  "./racket_specs/gen_lib_spec/";
-const PROPDIR: &str =  "./racket_specs/gen_prop_spec/";
-const MATCHDIR: &str =  "./racket_specs/gen_match/";
+// The working directory of the pipeline holds the generated specifications under these
+// directories, next to the setups they require
+pub const WORKDIR: &str = "./racket_specs/";
+pub const LIBDIR: &str = "gen_lib_spec/";
+pub const PROPDIR: &str = "gen_prop_spec/";
+pub const MATCHDIR: &str = "gen_match/";
+const UNORDEREDSETUP: &str = "../unordered-setup.rkt";
+
+// The setups shipped with the crate, written to each working directory
+pub const SETUPS: [(&str, &str); 6] = [
+    ("combinators.rkt", include_str!("../racket_specs/combinators.rkt")),
+    ("counterexample.rkt", include_str!("../racket_specs/counterexample.rkt")),
+    ("container-setup.rkt", include_str!("../racket_specs/container-setup.rkt")),
+    ("randomaccess-setup.rkt", include_str!("../racket_specs/randomaccess-setup.rkt")),
+    ("stack-setup.rkt", include_str!("../racket_specs/stack-setup.rkt")),
+    ("unordered-setup.rkt", include_str!("../racket_specs/unordered-setup.rkt")),
+];
 
 pub fn initialise_match_setup() -> MatchSetup {
     let mut match_setup = MatchSetup::new();
@@ -25,62 +40,126 @@ pub fn initialise_match_setup() -> MatchSetup {
 }
 
 
-pub fn gen_match_script(prop: String, match_setup: String, prop_spec_file: String, lib_spec_file: String, interface_spec: String, symbolics: &Vec<String>) -> Result<String, Error>  {
-    let mut output = fs::File::create(GENNAME.to_owned())?;
+// Each matching job runs in its own directory under gen_match, holding its match script
+// and a copy of the property specification requiring the operations of the job's struct
+pub fn job_dir(work_dir: &str, job: usize) -> String {
+    work_dir.to_string() + MATCHDIR + "job-" + &job.to_string() + "/"
+}
+
+pub fn setup_job(work_dir: &str, dir: &str, prop_spec_file: &str, ops: &str) -> Result<(), Error> {
+    fs::create_dir_all(dir)?;
+    let prop_spec = fs::read_to_string(work_dir.to_owned() + PROPDIR + prop_spec_file)?
+        .replace("\"../combinators.rkt\"", "\"../../combinators.rkt\"")
+        .replace("\"../gen_lib_spec/ops.rkt\"", "\"ops.rkt\"");
+    fs::write(dir.to_owned() + prop_spec_file, prop_spec)?;
+    fs::write(dir.to_owned() + "ops.rkt", ops)?;
+    Ok(())
+}
+
+pub fn gen_match_script(dir: &str, prop: String, match_setup: String, prop_spec_file: String, lib_spec_file: String, interface_spec: String, symbolics: &Vec<String>) -> Result<String, Error>  {
+    let filename = dir.to_owned() + GENNAME;
+    let mut output = fs::File::create(&filename)?;
     write!(output, "{}", LANGDECL.to_string())?;
-    let require_prop = "(require \"".to_string() + PROPSPECPATH + &prop_spec_file + "\")\n";
+    let require_prop = "(require \"".to_string() + &prop_spec_file + "\")\n";
     write!(output, "{}", require_prop)?;
-    let require_lib = "(require \"".to_string() + LIBSPECPATH + &lib_spec_file + "\")\n";
+    let require_lib = "(require \"../".to_string() + LIBSPECPATH + &lib_spec_file + "\")\n";
     write!(output, "{}", require_lib)?;
-    write!(output, "{}", "(require \"".to_string() + &match_setup + "\")\n")?;
+    // the setup is relative to gen_match
+    write!(output, "{}", "(require \"../".to_string() + &match_setup + "\")\n")?;
     let s = symbolics.join(" ");
     let code = "(check ".to_string() + &prop + " (cdr " + &interface_spec +") (car " + &interface_spec + ") ls " + &s + ")\n";
     write!(output, "{}", code)?;
-    Ok(GENNAME.to_string())
+    Ok(filename)
 }
 
-pub fn run_matching(filename: String) -> Result<bool, ExecutionError> {
+// The property of an unordered struct must not depend on the order of the list model,
+// checked by a script of its own next to the match script
+pub fn gen_order_script(dir: &str, prop: String, prop_spec_file: String, lib_spec_file: String, interface_spec: String) -> Result<String, Error> {
+    let filename = dir.to_owned() + ORDERNAME;
+    let mut output = fs::File::create(&filename)?;
+    write!(output, "{}", LANGDECL)?;
+    writeln!(output, "(require \"{}\")", prop_spec_file)?;
+    writeln!(output, "(require \"../{}{}\")", LIBSPECPATH, lib_spec_file)?;
+    writeln!(output, "(require \"../{}\")", UNORDEREDSETUP)?;
+    writeln!(output, "(check-unordered {} (cdr {}) ls)", prop, interface_spec)?;
+    Ok(filename)
+}
+
+// The result printed by a match or order script: #t if the struct matches, otherwise #f after
+// the line of the counterexample found by the solver, see counterexample.rkt
+fn read_result(output: &str) -> Result<MatchResult, ExecutionError> {
+    let lines: Vec<&str> = output.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).collect();
+    match lines.last() {
+        Some(&"#t") => Ok(None),
+        Some(&"#f") => {
+            let counterexample = lines.iter().rev()
+                .filter_map(|l| l.strip_prefix("counterexample\t"))
+                .next()
+                .and_then(|l| decode_result(&l.split('\t').collect::<Vec<&str>>()))
+                .and_then(|r| r.flatten());
+            Ok(Some(counterexample))
+        },
+        _ => Err("Error: Not a valid output.".to_string())
+    }
+}
+
+pub fn run_matching(filename: String) -> Result<MatchResult, ExecutionError> {
     let output = Command::new("sh")
                 .arg("-c")
                 .arg("racket ".to_owned() + &filename)
                 .output()
                 .expect("failed to execute process");
     let raw = output.stdout;
-    let result_str = String::from_utf8_lossy(&raw).to_string();
-    let result = result_str.trim();
-    if (result == "#t") {
-        Ok(true)
-    } else if (result == "#f"){
-        Ok(false)
-    } else {
-        Err("Error: Not a valid output.".to_string())
+    read_result(&String::from_utf8_lossy(&raw))
+}
+
+pub fn cleanup_jobs(work_dir: &str) {
+    if let Ok(entries) = fs::read_dir(work_dir.to_owned() + MATCHDIR) {
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with("job-") {
+                let _ = fs::remove_dir_all(entry.path());
+            }
+        }
     }
 }
 
-pub fn cleanup_script() {
-    Command::new("sh")
-        .arg("-c")
-        .arg("rm -f ".to_owned() + GENNAME)
-        .output()
-        .expect("Fail to clean up");
+// Create the directories of the generated specifications under the working directory,
+// with the setups they require
+pub fn setup_dirs(work_dir: &str) -> Result<(), Error> {
+    for dir in [PROPDIR, LIBDIR, MATCHDIR].iter() {
+        fs::create_dir_all(work_dir.to_owned() + dir)?;
+    }
+    for (file, contents) in SETUPS.iter() {
+        let path = work_dir.to_owned() + file;
+        if fs::read_to_string(&path).map_or(true, |c| c != *contents) {
+            fs::write(&path, contents)?;
+        }
+    }
+    Ok(())
 }
 
-pub fn setup_dirs() {
-    Command::new("sh")
-        .arg("-c")
-        .arg("mkdir -p ".to_owned() + PROPDIR)
-        .output()
-        .expect("Fail to create the property specification directory");
-
-    Command::new("sh")
-        .arg("-c")
-        .arg("mkdir -p ".to_owned() + LIBDIR)
-        .output()
-        .expect("Fail to create the library specification directory");
-    
-    Command::new("sh")
-        .arg("-c")
-        .arg("mkdir -p ".to_owned() + MATCHDIR)
-        .output()
-        .expect("Fail to create the matching script directory");
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use crate::run_matching::{read_result};
+    use crate::native_matching::counterexample::{Counterexample};
+
+    #[test]
+    fn test_read_result() {
+        assert_eq!(read_result("#t\n"), Ok(None));
+        assert_eq!(read_result("#f\n"), Ok(Some(None)));
+        assert_eq!(
+            read_result("counterexample\tviolation\tinsert\t(0)\t0\t(0 0)\n#f\n"),
+            Ok(Some(Some(Counterexample::Violation {
+                op: "insert".to_string(),
+                input: "(0)".to_string(),
+                elem: Some("0".to_string()),
+                output: "(0 0)".to_string()
+            })))
+        );
+        assert_eq!(
+            read_result("counterexample\tcontradiction\tremove\n#f\n"),
+            Ok(Some(Some(Counterexample::Contradiction { op: "remove".to_string() })))
+        );
+        assert!(read_result("car: contract violation\n").is_err());
+    }
+}
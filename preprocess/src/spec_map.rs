@@ -1,6 +1,10 @@
 use std::collections::HashMap;
 use std::collections::hash_map::Iter;
 
+use crate::parser::{Term};
+use crate::lib_spec::{Model};
+use crate::cost::{OpCosts};
+
 type StructName = String;
 type LibSpecDir = String;
 type BoundName = String;
@@ -8,11 +12,14 @@ type BoundProvide = String;
 type MatchSetupDir = String;
 pub type Bounds = HashMap<BoundName, BoundProvide>;
 pub type ProvidedOps = (Vec<String>, Vec<String>);
+pub type Costs = HashMap<BoundName, OpCosts>;
+pub type Abstraction = Option<String>; // the trait giving the list model of a struct, if declared
 
 type PropertyName = String;
 type PropSpecDir = String;
 type PropSymbolics = Vec<String>;
 
-pub type LibSpecs = HashMap<StructName, (LibSpecDir, Bounds, ProvidedOps)>;
+pub type LibSpecs = HashMap<StructName, (LibSpecDir, Bounds, ProvidedOps, Model, Costs, Abstraction)>;
 pub type PropSpecs = HashMap<PropertyName, (PropSpecDir, PropSymbolics)>;
+pub type PropTerms = HashMap<PropertyName, Term>;
 pub type MatchSetup = HashMap<BoundName, MatchSetupDir>;
\ No newline at end of file
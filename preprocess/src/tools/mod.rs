@@ -6,6 +6,8 @@ use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 
+use crate::traits::{Container};
+
 pub fn gen_dataset_1() -> Vec<u32> {
     let size = 1024 * 1024; // 1 MB
     let amount = size / size_of::<u32>();
@@ -40,4 +42,19 @@ pub fn gen_dataset_512() -> Vec<u32> {
     let mut rng = StdRng::seed_from_u64(222);
     data.shuffle(&mut rng);
     data
-}
\ No newline at end of file
+}
+// A shuffled dataset of n distinct elements
+pub fn gen_dataset(n: usize) -> Vec<u32> {
+    let mut data: Vec<u32> = (1..=n as u32).collect();
+    let mut rng = StdRng::seed_from_u64(222);
+    data.shuffle(&mut rng);
+    data
+}
+
+// The container with the elements of the dataset inserted
+pub fn fill<C: Container<u32>>(mut c: C, data: &[u32]) -> C {
+    for x in data.iter() {
+        c.insert(*x);
+    }
+    c
+}
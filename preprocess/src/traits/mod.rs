@@ -1,5 +1,7 @@
 pub mod container_constructor;
 
+use im::conslist::{ConsList};
+
 pub trait Container<T> {
     fn len(&mut self) -> usize;
     fn contains(&mut self, x: &T) -> bool;
@@ -19,4 +21,10 @@ pub trait RandomAccess<T> {
     fn first(&mut self) -> Option<&T>;
     fn last(&mut self) -> Option<&T>;
     fn nth(&mut self, n: usize) -> Option<&T>;
-}
\ No newline at end of file
+}
+
+// The list model of the state of a library struct, as its specifications see it.
+// Implemented for the struct of a library file in the file, where the library finds it.
+pub trait ModelAbstraction<T> {
+    fn abstraction(&self) -> ConsList<T>;
+}
@@ -1,11 +1,12 @@
 use crate::parser::{Prog, Block, Spec, Decl, Term, Refinement, Id, spec};
-use crate::inference::{TypeEnv};
+use crate::inference::{TypeEnv, InferenceError};
 use crate::generator::{readfile};
-use crate::types::{Type, TypeVar, TypeScheme, TypeVarGen, Bounds};
+use crate::types::{Type, TypeVar, TypeScheme, TypeVarGen, Bounds, bound_op_names};
+use crate::error::{SpecError, DeclKind};
 
 use std::ops::Deref;
 
-type TypeError = String;
+type TypeError = SpecError;
 
 pub struct TypeChecker {
     global_ctx : TypeEnv,
@@ -101,6 +102,74 @@ impl TypeChecker {
             }
         );
 
+        // boolean connectives of the operator syntax
+        let bool_binary = Type::Fun(Box::new(Type::Bool()), Box::new(Type::Fun(Box::new(Type::Bool()), Box::new(Type::Bool()))));
+        for op in ["and", "or"] {
+            self.global_ctx.insert(op.to_string(),
+                TypeScheme {
+                    vars: Vec::new(),
+                    ty: bool_binary.clone()
+                }
+            );
+        }
+        self.global_ctx.insert("not".to_string(),
+            TypeScheme {
+                vars: Vec::new(),
+                ty: Type::Fun(Box::new(Type::Bool()), Box::new(Type::Bool()))
+            }
+        );
+
+        // integer arithmetic and comparison
+        let int_binary = Type::Fun(Box::new(Type::Int()), Box::new(Type::Fun(Box::new(Type::Int()), Box::new(Type::Int()))));
+        for op in ["+", "-", "*"] {
+            self.global_ctx.insert(op.to_string(),
+                TypeScheme {
+                    vars: Vec::new(),
+                    ty: int_binary.clone()
+                }
+            );
+        }
+        let int_cmp = Type::Fun(Box::new(Type::Int()), Box::new(Type::Fun(Box::new(Type::Int()), Box::new(Type::Bool()))));
+        for op in ["<", ">"] {
+            self.global_ctx.insert(op.to_string(),
+                TypeScheme {
+                    vars: Vec::new(),
+                    ty: int_cmp.clone()
+                }
+            );
+        }
+
+        // length, first and last of the list model
+        let con = Type::Con(Box::new("Con".to_string()),
+                    Box::new(Type::Var(TypeVar::new("T".to_string()))),
+                    Box::new(Bounds::from(["Container".to_string()])));
+        self.global_ctx.insert("length".to_string(),
+            TypeScheme {
+                vars: Vec::new(),
+                ty: Type::Fun(Box::new(con.clone()), Box::new(Type::Int()))
+            }
+        );
+        for op in ["first", "last"] {
+            self.global_ctx.insert(op.to_string(),
+                TypeScheme {
+                    vars: Vec::new(),
+                    ty: Type::Fun(Box::new(con.clone()), Box::new(Type::Var(TypeVar::new("T".to_string()))))
+                }
+            );
+        }
+
+        // if c { t } else { e }, instantiated at each use
+        let branch = TypeVar::new("B".to_string());
+        let if_fn = Type::Fun(Box::new(Type::Bool()),
+                        Box::new(Type::Fun(Box::new(Type::Var(branch.clone())),
+                        Box::new(Type::Fun(Box::new(Type::Var(branch.clone())), Box::new(Type::Var(branch.clone())))))));
+        self.global_ctx.insert("if".to_string(),
+            TypeScheme {
+                vars: vec![branch],
+                ty: if_fn
+            }
+        );
+
         // the forall quantifier
         let forall = Type::Fun(
                         Box::new(Type::Fun(Box::new(Type::Var(TypeVar::new("T".to_string()))), Box::new(Type::Bool()))),
@@ -164,19 +233,19 @@ impl TypeChecker {
 
     pub fn check_bound_decl(&mut self, decl: &Decl) -> Result<(), TypeError> {
         match decl {
-            Decl::ConTypeDecl(_, (_, ins, _)) => {
+            Decl::ConTypeDecl(_, (_, ins, _), _, _) => {
                 // Duplicate bound name checking
                 for i in ins.iter() {
                     match self.global_ctx.get(&i.to_string()) {
                         Some(_) => {
-                            return Err("Duplicate bound name declaration".to_string());
+                            return Err(SpecError::type_error(DeclKind::ConType, &decl.get_name(), "Duplicate bound name declaration: ".to_string() + i));
                         },
                         None => continue, // TODO: check each bound is a valid rust trait
                     }
                 }
                 Ok(())
             },
-            _ => Err("Not a valid bound declaration".to_string())
+            _ => Err(SpecError::Type { decl: None, message: "Not a valid bound declaration".to_string(), span: None })
         }
     }
 
@@ -199,7 +268,7 @@ impl TypeChecker {
             Decl::PropertyDecl((id, ty), term) => {
                 // Duplicate property decl checking
                 match self.global_ctx.get(&id.to_string()) {
-                    Some(_) => Err("Duplicate property declaration".to_string()),
+                    Some(_) => Err(SpecError::type_error(DeclKind::Property, id, "Duplicate property declaration".to_string())),
                     None => {
                         // check well formedness
                         match self.global_ctx.type_inference(term, &mut self.tvg) {
@@ -219,7 +288,7 @@ impl TypeChecker {
                                                     );
                                                     Ok(())
                                                 } else {
-                                                    Err("Not a valid property decl: input does not have basic container type Con<T>".to_string())
+                                                    Err(SpecError::type_error(DeclKind::Property, id, "Not a valid property decl: input does not have basic container type Con<T>".to_string()))
                                                 }
                                             },
                                             (_, Type::Bool()) => {
@@ -232,18 +301,18 @@ impl TypeChecker {
                                                 );
                                                 Ok(())
                                             },
-                                            _ => Err("Not a valid property decl: return type should be Bool".to_string())
+                                            _ => Err(SpecError::type_error(DeclKind::Property, id, "Not a valid property decl: return type should be Bool, found ".to_string() + &ty.to_string()))
                                         }
                                     },
-                                    _ => Err("Not a valid property decl: should have type Con<T> -> Bool".to_string())
+                                    _ => Err(SpecError::type_error(DeclKind::Property, id, "Not a valid property decl: should have type Con<T> -> Bool, found ".to_string() + &ty.to_string()))
                                 }
                             }
-                            Err(e) => Err(e)
+                            Err(e) => Err(SpecError::type_error(DeclKind::Property, id, e))
                         }
                     },
                 }
             },
-            _ => Err("Not a valid property declaration".to_string())
+            _ => Err(SpecError::Type { decl: None, message: "Not a valid property declaration".to_string(), span: None })
         }
     }
 
@@ -263,10 +332,22 @@ impl TypeChecker {
 
     pub fn check_contype_decl(&mut self, decl: &Decl) -> Result<(), TypeError> {
         match decl {
-            Decl::ConTypeDecl(con_ty, (vid, ins, r)) => {
+            Decl::ConTypeDecl(con_ty, (vid, ins, r), workload, _) => {
+                // Workload checking
+                for (n, (op, _)) in workload.ops.iter().enumerate() {
+                    if workload.ops[..n].iter().any(|(o, _)| o == op) {
+                        return Err(SpecError::type_error(DeclKind::ConType, &decl.get_name(), "Duplicate operation in the workload: ".to_string() + op));
+                    }
+                    if !ins.iter().any(|b| bound_op_names(b).is_some_and(|ops| ops.contains(&op.as_str()))) {
+                        return Err(SpecError::type_error(DeclKind::ConType, &decl.get_name(), "The workload names an operation of none of its interfaces: ".to_string() + op));
+                    }
+                }
+                if workload.size == Some(0) {
+                    return Err(SpecError::type_error(DeclKind::ConType, &decl.get_name(), "The workload size must be positive".to_string()));
+                }
                 // Duplicate container type decl checking
                 match self.global_ctx.get(&con_ty.to_string()) {
-                    Some(_) => Err("Duplicate container type declaration".to_string()),
+                    Some(_) => Err(SpecError::type_error(DeclKind::ConType, &decl.get_name(), "Duplicate container type declaration".to_string())),
                     None => {
                         let con = Type::Con(Box::new("Con".to_string()), Box::new(Type::Var(TypeVar::new("T".to_string()))), ins.clone());
                         let mut local_ctx = self.global_ctx.clone();
@@ -276,7 +357,7 @@ impl TypeChecker {
                                 ty: con
                             }
                         );
-                        match self.check_ref(&mut local_ctx, r) {
+                        match self.check_ref(&mut local_ctx, r).map_err(|e| SpecError::type_error(DeclKind::ConType, &decl.get_name(), e)) {
                             Ok(_) => {
                                 self.global_ctx.insert(decl.get_name(), 
                                     TypeScheme{
@@ -291,11 +372,11 @@ impl TypeChecker {
                     }
                 }
             },
-            _ => Err("Not a valid container type declaration".to_string())
+            _ => Err(SpecError::Type { decl: None, message: "Not a valid container type declaration".to_string(), span: None })
         }
     }
 
-    pub fn check_ref(&mut self, ctx: &mut TypeEnv, r: &Refinement) -> Result<(), TypeError> {
+    pub fn check_ref(&mut self, ctx: &mut TypeEnv, r: &Refinement) -> Result<(), InferenceError> {
         match r {
             Refinement::Prop(t) => {
                 match ctx.type_inference(t.deref(), &mut self.tvg) {
@@ -310,7 +391,7 @@ impl TypeChecker {
                     Err(e) => Err(e)
                 }
             },
-            Refinement::AndProps(r1, r2) => {
+            Refinement::AndProps(r1, r2) | Refinement::OrProps(r1, r2) | Refinement::ImpliesProps(r1, r2) => {
                 match self.check_ref(ctx, r1) {
                     Ok(_) => {
                         match self.check_ref(ctx, r2) {
@@ -320,7 +401,8 @@ impl TypeChecker {
                     },
                     Err(e) => Err(e)
                 }
-            }
+            },
+            Refinement::NotProp(r) => self.check_ref(ctx, r)
         }
     }
 }
@@ -333,7 +415,7 @@ pub fn check_prop_decl() -> Result<(), TypeError> {
     let f = readfile("./spec_code/example.rs".to_string());
     match spec::prog(&f) {
         Ok(prog) => tc.check_prog(prog),
-        Err(e) => Err(e.to_string())
+        Err(e) => Err(SpecError::Io(e.to_string()))
     }
 }
 
@@ -10,6 +10,16 @@ pub type Name = String;
 // traits
 pub type Bounds = HashSet<Name>;
 
+// The operations of a bound, by their names in the specifications
+pub fn bound_op_names(bound: &str) -> Option<&'static [&'static str]> {
+    match bound {
+        "Container" => Some(&["len", "contains", "is-empty", "insert", "clear", "remove"]),
+        "Stack" => Some(&["push", "pop"]),
+        "RandomAccess" => Some(&["first", "last", "nth"]),
+        _ => None
+    }
+}
+
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub enum Type {
     Bool(),
@@ -87,7 +97,7 @@ impl Type {
             // Unify con type
             (&Type::Con(ref n1, ref t1, _), &Type::Con(ref n2, ref t2, _)) => {
                 if n1.to_string() != n2.to_string() {
-                    Err("Cannot unify two different container".to_string())
+                    Err("Cannot unify two different container: ".to_string() + n1 + " and " + n2)
                 } else {
                     t1.mgu(t2)
                 }
@@ -104,9 +114,7 @@ impl Type {
 
             // Otherwise, the types cannot be unified.
             (t1, t2) => {
-                println!("{:?}", t1);
-                println!("{:?}", t2);
-                Err("types do not unify".to_string())
+                Err("types do not unify: ".to_string() + &t1.to_string() + " and " + &t2.to_string())
             }
         }
     }